@@ -1,5 +1,5 @@
 #!/bin/bash
-# A script that runs `cargo clippy` for the backend and frontend
+# A script that runs `cargo clippy` for the backend and frontend, and the shared library's tests
 # Assumes current working directory is project root
 
 display() {
@@ -24,4 +24,10 @@ cd src-tauri
 cargo clippy
 cd ..
 
+display "Shared"
+cd shared
+cargo clippy --all-targets
+cargo test
+cd ..
+
 echo "Check done"
\ No newline at end of file
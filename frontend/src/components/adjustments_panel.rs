@@ -0,0 +1,294 @@
+/*
+ * Color adjustments panel
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use super::{alert_error, backend_error, global_settings, preview_revision};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{prelude::*, use_context};
+
+/***** Glue *****/
+#[wasm_bindgen(module = "/src/static/glue.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = invokeGetAdjustments, catch)]
+    pub async fn get_adjustments_glue() -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeSetAdjustments, catch)]
+    pub async fn set_adjustments_glue(adjustments: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/***** Adjustments panel component *****/
+#[derive(PartialEq, Properties)]
+pub struct AdjustmentsPanelProps {
+    /// The adjustments of the current image
+    pub adjustments: global_settings::Adjustments,
+    /// Called with the revision of the adjusted image
+    pub on_adjusted: Callback<u64>,
+    /// Presets are kept in the settings
+    pub on_update_settings: Callback<global_settings::Settings>,
+}
+#[function_component(AdjustmentsPanel)]
+pub fn adjustments_panel(props: &AdjustmentsPanelProps) -> Html {
+    let settings =
+        use_context::<global_settings::Settings>().expect("Could not find settings context");
+
+    // Sliders only send their value once they're let go, so the backend isn't flooded
+    let adjust = |change: fn(&mut global_settings::Adjustments, &HtmlInputElement)| {
+        let adjustments = props.adjustments.clone();
+        let on_adjusted = props.on_adjusted.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                let mut new_adjustments = adjustments.clone();
+                change(&mut new_adjustments, &input);
+                set_adjustments(new_adjustments, on_adjusted.clone());
+            }
+        })
+    };
+    let on_brightness_change = adjust(|adjustments, input| {
+        adjustments.brightness = input.value().parse().unwrap_or(adjustments.brightness)
+    });
+    let on_contrast_change = adjust(|adjustments, input| {
+        adjustments.contrast = input.value().parse().unwrap_or(adjustments.contrast)
+    });
+    let on_gamma_change = adjust(|adjustments, input| {
+        adjustments.gamma = input.value().parse().unwrap_or(adjustments.gamma)
+    });
+    let on_hue_rotate_change = adjust(|adjustments, input| {
+        adjustments.hue_rotate = input.value().parse().unwrap_or(adjustments.hue_rotate)
+    });
+    let on_grayscale_change = adjust(|adjustments, input| adjustments.grayscale = input.checked());
+    let on_invert_change = adjust(|adjustments, input| adjustments.invert = input.checked());
+    let on_reset = {
+        let on_adjusted = props.on_adjusted.clone();
+        Callback::from(move |_| {
+            set_adjustments(global_settings::Adjustments::default(), on_adjusted.clone())
+        })
+    };
+
+    // Presets
+    let selected_preset_state = use_state_eq(|| None::<usize>);
+    let preset_name_state = use_state_eq(String::new);
+    let selected_preset = (*selected_preset_state)
+        .and_then(|index| settings.adjustment_presets.get(index))
+        .cloned();
+    let on_preset_select = {
+        let selected_preset_state = selected_preset_state.clone();
+        let preset_name_state = preset_name_state.clone();
+        let settings = settings.clone();
+        Callback::from(move |event: Event| {
+            if let Some(select) = event.target_dyn_into::<HtmlSelectElement>() {
+                let index = select.value().parse::<usize>().ok();
+                // Saving again under the same name replaces the preset
+                if let Some(preset) = index.and_then(|index| settings.adjustment_presets.get(index))
+                {
+                    preset_name_state.set(preset.name.clone());
+                }
+                selected_preset_state.set(index);
+            }
+        })
+    };
+    let on_preset_name_change = {
+        let preset_name_state = preset_name_state.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                preset_name_state.set(input.value());
+            }
+        })
+    };
+    let on_apply_preset = {
+        let selected_preset = selected_preset.clone();
+        let on_adjusted = props.on_adjusted.clone();
+        Callback::from(move |_| {
+            if let Some(preset) = &selected_preset {
+                set_adjustments(preset.adjustments.clone(), on_adjusted.clone());
+            }
+        })
+    };
+    let preset_name = preset_name_state.trim().to_string();
+    let on_save_preset = {
+        let settings = settings.clone();
+        let on_update_settings = props.on_update_settings.clone();
+        let adjustments = props.adjustments.clone();
+        let preset_name = preset_name.clone();
+        let selected_preset_state = selected_preset_state.clone();
+        Callback::from(move |_| {
+            let mut new_settings = settings.clone();
+            let preset = global_settings::AdjustmentPreset {
+                name: preset_name.clone(),
+                adjustments: adjustments.clone(),
+            };
+            let index = match new_settings
+                .adjustment_presets
+                .iter()
+                .position(|preset| preset.name == preset_name)
+            {
+                Some(index) => {
+                    new_settings.adjustment_presets[index] = preset;
+                    index
+                }
+                None => {
+                    new_settings.adjustment_presets.push(preset);
+                    new_settings.adjustment_presets.len() - 1
+                }
+            };
+            selected_preset_state.set(Some(index));
+            on_update_settings.emit(new_settings);
+        })
+    };
+    let on_delete_preset = {
+        let settings = settings.clone();
+        let on_update_settings = props.on_update_settings.clone();
+        let selected_preset_state = selected_preset_state.clone();
+        Callback::from(move |_| {
+            if let Some(index) = *selected_preset_state {
+                let mut new_settings = settings.clone();
+                if index < new_settings.adjustment_presets.len() {
+                    new_settings.adjustment_presets.remove(index);
+                }
+                selected_preset_state.set(None);
+                on_update_settings.emit(new_settings);
+            }
+        })
+    };
+
+    let adjustments = &props.adjustments;
+    html! {
+        <div class="flex flex-wrap gap-2 items-center text-sm">
+            <span>{ "Adjust:" }</span>
+            <label>
+                { "Brightness " }
+                <input
+                    onchange={ on_brightness_change }
+                    class="w-20 align-middle"
+                    type="range"
+                    min=-255
+                    max=255
+                    value={ adjustments.brightness.to_string() }
+                />
+            </label>
+            <label>
+                { "Contrast " }
+                <input
+                    onchange={ on_contrast_change }
+                    class="w-20 align-middle"
+                    type="range"
+                    min=-100
+                    max=100
+                    value={ adjustments.contrast.to_string() }
+                />
+            </label>
+            <label title="Gamma, where more than 1 brightens the dark tones">
+                { "Gamma " }
+                <input
+                    onchange={ on_gamma_change }
+                    class="w-16 text-black"
+                    type="number"
+                    min="0.1"
+                    max=10
+                    step="0.1"
+                    value={ adjustments.gamma.to_string() }
+                />
+            </label>
+            <label title="Hue rotation in degrees">
+                { "Hue " }
+                <input
+                    onchange={ on_hue_rotate_change }
+                    class="w-20 align-middle"
+                    type="range"
+                    min=-180
+                    max=180
+                    value={ adjustments.hue_rotate.to_string() }
+                />
+            </label>
+            <label>
+                <input
+                    onchange={ on_grayscale_change }
+                    type="checkbox"
+                    checked={ adjustments.grayscale }
+                />
+                { " Grayscale" }
+            </label>
+            <label>
+                <input onchange={ on_invert_change } type="checkbox" checked={ adjustments.invert } />
+                { " Invert" }
+            </label>
+            <button onclick={ on_reset } disabled={ adjustments.is_identity() } class={ BUTTON_CLASS }>
+                { "Reset adjustments" }
+            </button>
+            <select class="text-black" onchange={ on_preset_select }>
+                <option value="" selected={ selected_preset.is_none() }>{ "Presets" }</option>
+                {
+                    settings.adjustment_presets.iter().enumerate().map(|(index, preset)| {
+                        html! {
+                            <option
+                                value={ index.to_string() }
+                                selected={ *selected_preset_state == Some(index) }
+                            >
+                                { preset.name.clone() }
+                            </option>
+                        }
+                    }).collect::<Html>()
+                }
+            </select>
+            <button
+                onclick={ on_apply_preset }
+                disabled={ selected_preset.is_none() }
+                class={ BUTTON_CLASS }
+            >
+                { "Apply" }
+            </button>
+            <button
+                onclick={ on_delete_preset }
+                disabled={ selected_preset.is_none() }
+                class={ BUTTON_CLASS }
+            >
+                { "Delete" }
+            </button>
+            <input
+                onchange={ on_preset_name_change }
+                class="w-24 text-black"
+                type="text"
+                placeholder="Preset name"
+                value={ (*preset_name_state).clone() }
+            />
+            <button
+                onclick={ on_save_preset }
+                disabled={ preset_name.is_empty() }
+                title="Keep the current adjustments as a preset. Save the settings to keep it after closing"
+                class={ BUTTON_CLASS }
+            >
+                { "Save preset" }
+            </button>
+        </div>
+    }
+}
+
+/// The style of the panel buttons
+const BUTTON_CLASS: &str =
+    "px-2 py-1 text-black bg-gray-300 rounded-md hover:bg-gray-200 disabled:opacity-50";
+
+/// Replace the adjustments of the image using JavaScript glue
+fn set_adjustments(adjustments: global_settings::Adjustments, on_adjusted: Callback<u64>) {
+    spawn_local(async move {
+        let adjustments = JsValue::from_serde(&adjustments).unwrap();
+        match set_adjustments_glue(adjustments).await {
+            Ok(revision) => on_adjusted.emit(preview_revision(&revision)),
+            Err(e) => alert_error(&backend_error(e)),
+        }
+    })
+}
+
+/// Load the adjustments of the current image using JavaScript glue
+pub fn load_adjustments(adjustments_state: UseStateHandle<global_settings::Adjustments>) {
+    spawn_local(async move {
+        // There's no image yet when this fails, so there's nothing to adjust
+        let adjustments = match get_adjustments_glue().await {
+            Ok(adjustments) => adjustments.into_serde().unwrap_or_default(),
+            Err(_) => global_settings::Adjustments::default(),
+        };
+        adjustments_state.set(adjustments);
+    })
+}
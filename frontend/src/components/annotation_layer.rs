@@ -0,0 +1,455 @@
+/*
+ * Annotation layer and toolbar
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use super::{alert_error, backend_error, payloads, preview_revision};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+/***** Glue *****/
+#[wasm_bindgen(module = "/src/static/glue.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = invokeSetAnnotations, catch)]
+    pub async fn set_annotations_glue(annotations: JsValue) -> Result<JsValue, JsValue>;
+}
+
+/***** Tools *****/
+/// What dragging on the image draws
+#[derive(Clone, Copy, PartialEq)]
+pub enum AnnotationTool {
+    Arrow,
+    Rectangle,
+    Ellipse,
+    Freehand,
+    Text,
+    Highlight,
+}
+impl AnnotationTool {
+    /// Every tool, in the order they're shown
+    const ALL: [AnnotationTool; 6] = [
+        AnnotationTool::Arrow,
+        AnnotationTool::Rectangle,
+        AnnotationTool::Ellipse,
+        AnnotationTool::Freehand,
+        AnnotationTool::Text,
+        AnnotationTool::Highlight,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            AnnotationTool::Arrow => "Arrow",
+            AnnotationTool::Rectangle => "Box",
+            AnnotationTool::Ellipse => "Ellipse",
+            AnnotationTool::Freehand => "Pen",
+            AnnotationTool::Text => "Text",
+            AnnotationTool::Highlight => "Highlight",
+        }
+    }
+}
+
+/// The tool and how what it draws looks
+#[derive(Clone, PartialEq)]
+pub struct AnnotationStyle {
+    /// The tool being used, or `None` when not annotating
+    pub tool: Option<AnnotationTool>,
+    /// A hex color like "#ff0000"
+    pub color: String,
+    pub line_width: f32,
+    pub text_size: f32,
+}
+impl Default for AnnotationStyle {
+    fn default() -> AnnotationStyle {
+        AnnotationStyle {
+            tool: None,
+            color: "#ff0000".to_string(),
+            line_width: 4.0,
+            text_size: 24.0,
+        }
+    }
+}
+impl AnnotationStyle {
+    /// Start an annotation with the tool at a point in pixels of the image.
+    /// Text starts empty, as it's typed in instead of drawn
+    pub fn start(&self, point: [f32; 2]) -> Option<payloads::Annotation> {
+        let [x, y] = point;
+        let shape = match self.tool? {
+            AnnotationTool::Arrow => payloads::Shape::Arrow {
+                from: point,
+                to: point,
+            },
+            AnnotationTool::Rectangle => payloads::Shape::Rectangle {
+                x,
+                y,
+                width: 0.0,
+                height: 0.0,
+            },
+            AnnotationTool::Ellipse => payloads::Shape::Ellipse {
+                x,
+                y,
+                width: 0.0,
+                height: 0.0,
+            },
+            AnnotationTool::Freehand => payloads::Shape::Freehand {
+                points: vec![point],
+            },
+            AnnotationTool::Text => payloads::Shape::Text {
+                x,
+                y,
+                text: String::new(),
+            },
+            AnnotationTool::Highlight => payloads::Shape::Highlight {
+                x,
+                y,
+                width: 0.0,
+                height: 0.0,
+            },
+        };
+        let size = match self.tool? {
+            AnnotationTool::Text => self.text_size,
+            _ => self.line_width,
+        };
+
+        Some(payloads::Annotation {
+            shape,
+            color: self.color.clone(),
+            size,
+        })
+    }
+}
+
+/// Continue drawing a shape to a point
+pub fn extend_shape(shape: &mut payloads::Shape, point: [f32; 2]) {
+    match shape {
+        payloads::Shape::Arrow { to, .. } => *to = point,
+        payloads::Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+        | payloads::Shape::Ellipse {
+            x,
+            y,
+            width,
+            height,
+        }
+        | payloads::Shape::Highlight {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            *width = point[0] - *x;
+            *height = point[1] - *y;
+        }
+        payloads::Shape::Freehand { points } => points.push(point),
+        payloads::Shape::Text { .. } => (),
+    }
+}
+
+/// Whether enough of a shape was drawn to keep it, e.g. not a click with the box tool
+pub fn is_drawn(shape: &payloads::Shape) -> bool {
+    match shape {
+        payloads::Shape::Arrow { from, to } => {
+            payloads::Shape::arrow_geometry(*from, *to, 1.0).is_some()
+        }
+        payloads::Shape::Rectangle { width, height, .. }
+        | payloads::Shape::Ellipse { width, height, .. }
+        | payloads::Shape::Highlight { width, height, .. } => {
+            width.abs() >= 1.0 && height.abs() >= 1.0
+        }
+        payloads::Shape::Freehand { .. } => true,
+        payloads::Shape::Text { text, .. } => !text.trim().is_empty(),
+    }
+}
+
+/***** Layer *****/
+/// Draw annotations as SVG over an image of a size. They're drawn the same way the backend
+/// draws them onto the image when it's saved
+pub fn annotation_svg(
+    annotations: &[payloads::Annotation],
+    width: u32,
+    height: u32,
+    overlay_ref: NodeRef,
+) -> Html {
+    html! {
+        <svg
+            ref={ overlay_ref }
+            class="absolute pointer-events-none"
+            viewBox={ format!("0 0 {} {}", width, height) }
+            preserveAspectRatio="none"
+        >
+            { for annotations.iter().map(annotation_element) }
+        </svg>
+    }
+}
+
+/// Draw an annotation as an SVG element
+fn annotation_element(annotation: &payloads::Annotation) -> Html {
+    let color = annotation.color.clone();
+    let size = annotation.size.to_string();
+    // Rectangles can have a negative size from dragging up or left, which SVG doesn't allow
+    let normalized = |x: f32, y: f32, width: f32, height: f32| {
+        (
+            x.min(x + width),
+            y.min(y + height),
+            width.abs(),
+            height.abs(),
+        )
+    };
+
+    match &annotation.shape {
+        payloads::Shape::Arrow { from, to } => {
+            match payloads::Shape::arrow_geometry(*from, *to, annotation.size) {
+                Some((line_end, head)) => html! {
+                    <g>
+                        <line
+                            x1={ from[0].to_string() }
+                            y1={ from[1].to_string() }
+                            x2={ line_end[0].to_string() }
+                            y2={ line_end[1].to_string() }
+                            stroke={ color.clone() }
+                            stroke-width={ size }
+                            stroke-linecap="round"
+                        />
+                        <polygon points={ svg_points(&head) } fill={ color } />
+                    </g>
+                },
+                None => html! {},
+            }
+        }
+        payloads::Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let (x, y, width, height) = normalized(*x, *y, *width, *height);
+            html! {
+                <rect
+                    x={ x.to_string() }
+                    y={ y.to_string() }
+                    width={ width.to_string() }
+                    height={ height.to_string() }
+                    fill="none"
+                    stroke={ color }
+                    stroke-width={ size }
+                    stroke-linejoin="round"
+                />
+            }
+        }
+        payloads::Shape::Ellipse {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let (x, y, width, height) = normalized(*x, *y, *width, *height);
+            html! {
+                <ellipse
+                    cx={ (x + width / 2.0).to_string() }
+                    cy={ (y + height / 2.0).to_string() }
+                    rx={ (width / 2.0).to_string() }
+                    ry={ (height / 2.0).to_string() }
+                    fill="none"
+                    stroke={ color }
+                    stroke-width={ size }
+                />
+            }
+        }
+        // A click without moving is a dot
+        payloads::Shape::Freehand { points } if points.len() == 1 => html! {
+            <circle
+                cx={ points[0][0].to_string() }
+                cy={ points[0][1].to_string() }
+                r={ (annotation.size / 2.0).to_string() }
+                fill={ color }
+            />
+        },
+        payloads::Shape::Freehand { points } => html! {
+            <polyline
+                points={ svg_points(points) }
+                fill="none"
+                stroke={ color }
+                stroke-width={ size }
+                stroke-linecap="round"
+                stroke-linejoin="round"
+            />
+        },
+        payloads::Shape::Text { x, y, text } => html! {
+            <text
+                x={ x.to_string() }
+                y={ y.to_string() }
+                fill={ color }
+                font-size={ size }
+                font-family="Annotation"
+                style="white-space: pre"
+            >
+                { text.clone() }
+            </text>
+        },
+        payloads::Shape::Highlight {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let (x, y, width, height) = normalized(*x, *y, *width, *height);
+            html! {
+                <rect
+                    x={ x.to_string() }
+                    y={ y.to_string() }
+                    width={ width.to_string() }
+                    height={ height.to_string() }
+                    fill={ color }
+                    fill-opacity={ payloads::HIGHLIGHT_OPACITY.to_string() }
+                />
+            }
+        }
+    }
+}
+
+/// Format points for an SVG `points` attribute
+fn svg_points(points: &[[f32; 2]]) -> String {
+    points
+        .iter()
+        .map(|[x, y]| format!("{},{}", x, y))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/***** Annotation toolbar component *****/
+#[derive(PartialEq, Properties)]
+pub struct AnnotationToolbarProps {
+    pub style: AnnotationStyle,
+    pub on_style_change: Callback<AnnotationStyle>,
+    /// Whether there are annotations to undo or clear
+    pub has_annotations: bool,
+    pub on_undo: Callback<()>,
+    pub on_clear: Callback<()>,
+}
+#[function_component(AnnotationToolbar)]
+pub fn annotation_toolbar(props: &AnnotationToolbarProps) -> Html {
+    // Clicking the selected tool's button again stops annotating
+    let tool_button = |tool: AnnotationTool| {
+        let selected = props.style.tool == Some(tool);
+        let style = props.style.clone();
+        let on_style_change = props.on_style_change.clone();
+        let onclick = Callback::from(move |_| {
+            on_style_change.emit(AnnotationStyle {
+                tool: match selected {
+                    true => None,
+                    false => Some(tool),
+                },
+                ..style.clone()
+            })
+        });
+        html! {
+            <button
+                { onclick }
+                class={
+                    match selected {
+                        true => "px-2 py-1 text-black bg-pink-200 rounded-md",
+                        false => BUTTON_CLASS,
+                    }
+                }
+            >
+                { tool.label() }
+            </button>
+        }
+    };
+    let on_color_change = {
+        let style = props.style.clone();
+        let on_style_change = props.on_style_change.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                on_style_change.emit(AnnotationStyle {
+                    color: input.value(),
+                    ..style.clone()
+                });
+            }
+        })
+    };
+    // Text has its own size, as a line width makes for tiny text
+    let is_text = props.style.tool == Some(AnnotationTool::Text);
+    let (size, size_title) = match is_text {
+        true => (props.style.text_size, "Text size"),
+        false => (props.style.line_width, "Line width"),
+    };
+    let on_size_change = {
+        let style = props.style.clone();
+        let on_style_change = props.on_style_change.clone();
+        Callback::from(move |event: Event| {
+            let size = match event.target_dyn_into::<HtmlInputElement>() {
+                Some(input) => input.value().parse::<f32>().ok().filter(|size| *size > 0.0),
+                None => None,
+            };
+            if let Some(size) = size {
+                let mut new_style = style.clone();
+                match is_text {
+                    true => new_style.text_size = size,
+                    false => new_style.line_width = size,
+                }
+                on_style_change.emit(new_style);
+            }
+        })
+    };
+    let on_undo = {
+        let on_undo = props.on_undo.clone();
+        Callback::from(move |_| on_undo.emit(()))
+    };
+    let on_clear = {
+        let on_clear = props.on_clear.clone();
+        Callback::from(move |_| on_clear.emit(()))
+    };
+
+    html! {
+        <div class="flex flex-wrap gap-1 items-center text-sm">
+            <span>{ "Annotate:" }</span>
+            { for AnnotationTool::ALL.into_iter().map(tool_button) }
+            <input
+                onchange={ on_color_change }
+                type="color"
+                value={ props.style.color.clone() }
+            />
+            <input
+                onchange={ on_size_change }
+                class="w-16 text-black"
+                type="number"
+                min=1
+                title={ size_title }
+                value={ size.to_string() }
+            />
+            <button onclick={ on_undo } disabled={ !props.has_annotations } class={ BUTTON_CLASS }>
+                { "Undo annotation" }
+            </button>
+            <button onclick={ on_clear } disabled={ !props.has_annotations } class={ BUTTON_CLASS }>
+                { "Clear annotations" }
+            </button>
+        </div>
+    }
+}
+
+/// The style of the toolbar buttons
+const BUTTON_CLASS: &str =
+    "px-2 py-1 text-black bg-gray-300 rounded-md hover:bg-gray-200 disabled:opacity-50";
+
+/// Replace the annotations in the backend using JavaScript glue, and emit them with the new
+/// revision of the image once they're kept
+pub fn set_annotations(
+    annotations: Vec<payloads::Annotation>,
+    on_annotations_set: Callback<(Vec<payloads::Annotation>, u64)>,
+) {
+    spawn_local(async move {
+        let annotations_value = JsValue::from_serde(&annotations).unwrap();
+        match set_annotations_glue(annotations_value).await {
+            Ok(revision) => on_annotations_set.emit((annotations, preview_revision(&revision))),
+            Err(e) => alert_error(&backend_error(e)),
+        }
+    })
+}
@@ -0,0 +1,93 @@
+/*
+ * Debug view of the encoded output cache
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use super::{alert_error, backend_error, payloads};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use yew::prelude::*;
+#[path = "./widget.rs"]
+mod widget;
+
+/***** Glue *****/
+#[wasm_bindgen(module = "/src/static/glue.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = invokeGetCacheStats, catch)]
+    pub async fn get_cache_stats_glue() -> Result<JsValue, JsValue>;
+}
+
+/***** Cache stats component *****/
+/// How often saves, copies and previews reused an encoded output. Only shown in debug builds
+#[function_component(CacheStats)]
+pub fn cache_stats() -> Html {
+    let stats_state = use_state(|| None::<payloads::CacheStats>);
+    let on_refresh = {
+        let stats_state = stats_state.clone();
+        Callback::from(move |_| load_cache_stats(stats_state.clone()))
+    };
+    {
+        let stats_state = stats_state.clone();
+        use_effect_with_deps(
+            move |_| {
+                load_cache_stats(stats_state);
+                || ()
+            },
+            (),
+        );
+    }
+
+    let stats = match &*stats_state {
+        Some(stats) => {
+            let lookups = stats.hits + stats.misses;
+            let hit_rate = match lookups {
+                0 => 0.0,
+                lookups => stats.hits as f64 / lookups as f64 * 100.0,
+            };
+            html! {
+                <p class="text-sm">
+                    { format!(
+                        "{} hits, {} misses ({:.0}% hit rate), {} outputs cached ({:.1} MB)",
+                        stats.hits,
+                        stats.misses,
+                        hit_rate,
+                        stats.entries,
+                        stats.bytes as f64 / 1_000_000.0,
+                    ) }
+                </p>
+            }
+        }
+        None => html! {},
+    };
+
+    html! {
+        <widget::Widget>
+            <div class="flex gap-2 items-center">
+                <p class="flex-1 text-lg">{ "Output cache" }</p>
+                <button
+                    onclick={ on_refresh }
+                    class="px-2 py-1 text-black bg-gray-300 rounded-md hover:bg-gray-200"
+                >
+                    { "Refresh" }
+                </button>
+            </div>
+            { stats }
+        </widget::Widget>
+    }
+}
+
+/// Load the cache stats from the backend into the state
+fn load_cache_stats(stats_state: UseStateHandle<Option<payloads::CacheStats>>) {
+    spawn_local(async move {
+        match get_cache_stats_glue().await {
+            Ok(stats) => {
+                if let Ok(stats) = stats.into_serde::<payloads::CacheStats>() {
+                    stats_state.set(Some(stats));
+                }
+            }
+            Err(e) => alert_error(&backend_error(e)),
+        }
+    })
+}
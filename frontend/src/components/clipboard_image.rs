@@ -5,11 +5,22 @@
 
 /***** Setup *****/
 /* Imports */
-use super::global_settings;
+use super::{
+    alert_error, backend_error, copy_to_clipboard, error, global_settings, payloads,
+    use_tauri_event,
+};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{window, HtmlImageElement};
+use web_sys::{window, Element, HtmlImageElement};
 use yew::{prelude::*, virtual_dom::AttrValue};
+#[path = "./adjustments_panel.rs"]
+mod adjustments_panel;
+#[path = "./annotation_layer.rs"]
+mod annotation_layer;
+#[path = "./edit_toolbar.rs"]
+mod edit_toolbar;
+#[path = "./history.rs"]
+mod history;
 #[path = "./widget.rs"]
 mod widget;
 
@@ -18,13 +29,30 @@ mod widget;
 extern "C" {
     #[wasm_bindgen(js_name = invokeReadClipboard, catch)]
     pub async fn read_clipboard_glue() -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeOpenImage, catch)]
+    pub async fn open_image_glue(path: Option<String>) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeGetImageInfo, catch)]
+    pub async fn get_image_info_glue() -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = previewUrl)]
+    pub fn preview_url_glue(revision: f64) -> String;
 }
 
 /***** Image display component *****/
 #[derive(PartialEq, Properties, Clone)]
 struct ImageDisplayProps {
-    data_url: AttrValue,
+    /// The URL of the image's preview, or nothing before an image is pasted
+    image_url: AttrValue,
     reset_zoom: bool,
+    /// What dragging a rectangle on the image does, or `None` to move the image instead
+    selection_mode: Option<edit_toolbar::SelectionMode>,
+    /// Called with the edit for the rectangle once one was drawn
+    on_select: Callback<payloads::Edit>,
+    /// The annotations drawn over the image
+    annotations: Vec<payloads::Annotation>,
+    /// What dragging on the image draws, if a tool is picked
+    annotation_style: annotation_layer::AnnotationStyle,
+    /// Called with an annotation once it was drawn
+    on_annotate: Callback<payloads::Annotation>,
 }
 #[function_component(ImageDisplay)]
 fn image_display(props: &ImageDisplayProps) -> Html {
@@ -41,6 +69,43 @@ fn image_display(props: &ImageDisplayProps) -> Html {
         .cast::<HtmlImageElement>()
         .unwrap_or_else(|| HtmlImageElement::new().unwrap());
 
+    // Annotations are drawn in an SVG over the image, which has to follow it as it's moved
+    // and zoomed. The SVG's coordinates are the image's pixels, so its size is kept too
+    let overlay_ref = use_node_ref();
+    let natural_size_state = use_state_eq(|| (0, 0));
+    let sync_overlay = {
+        let image_ref = image_ref.clone();
+        let overlay_ref = overlay_ref.clone();
+        move || {
+            if let (Some(image), Some(overlay)) = (
+                image_ref.cast::<HtmlImageElement>(),
+                overlay_ref.cast::<Element>(),
+            ) {
+                let _ = overlay.set_attribute(
+                    "style",
+                    &format!(
+                        "left: {}px; top: {}px; width: {}px; height: {}px;",
+                        image.offset_left() + image.client_left(),
+                        image.offset_top() + image.client_top(),
+                        image.client_width(),
+                        image.client_height(),
+                    ),
+                );
+            }
+        }
+    };
+    let on_image_load = {
+        let image_ref = image_ref.clone();
+        let natural_size_state = natural_size_state.clone();
+        let sync_overlay = sync_overlay.clone();
+        Callback::from(move |_| {
+            if let Some(image) = image_ref.cast::<HtmlImageElement>() {
+                natural_size_state.set((image.natural_width(), image.natural_height()));
+            }
+            sync_overlay();
+        })
+    };
+
     // Dragging
     // The position of the cursor
     let cursor_pos_ref = use_mut_ref(|| [0, 0]);
@@ -54,6 +119,7 @@ fn image_display(props: &ImageDisplayProps) -> Html {
     let dragging_style_update = {
         let style = image_ref_element.style();
         let display_image_pos_ref = display_image_pos_ref.clone();
+        let sync_overlay = sync_overlay.clone();
         move || {
             let display_pos = *display_image_pos_ref.borrow();
             style
@@ -62,6 +128,7 @@ fn image_display(props: &ImageDisplayProps) -> Html {
             style
                 .set_property("top", format!("{}px", display_pos[1]).as_str())
                 .unwrap();
+            sync_overlay();
         }
     };
     let start_dragging = {
@@ -116,6 +183,194 @@ fn image_display(props: &ImageDisplayProps) -> Html {
         *dragging_ref.borrow_mut() = false;
     });
 
+    // Selecting a rectangle to crop or redact
+    // The rectangle being drawn as [left, top, right, bottom] in pixels of the displayed image
+    let selection_rect_state = use_state_eq(|| None::<[f64; 4]>);
+    // Get where the cursor is in pixels of the displayed image, kept inside of it
+    let cursor_image_pos = {
+        let image_ref = image_ref.clone();
+        move |event: &MouseEvent| -> Option<[f64; 2]> {
+            let image = image_ref.cast::<HtmlImageElement>()?;
+            let rect = image.get_bounding_client_rect();
+            let border = image.client_left() as f64;
+            Some([
+                (event.client_x() as f64 - rect.left() - border)
+                    .clamp(0.0, image.client_width() as f64),
+                (event.client_y() as f64 - rect.top() - border)
+                    .clamp(0.0, image.client_height() as f64),
+            ])
+        }
+    };
+    let start_selecting = {
+        let selection_rect_state = selection_rect_state.clone();
+        let cursor_image_pos = cursor_image_pos.clone();
+        Callback::from(move |event: MouseEvent| {
+            if let Some([x, y]) = cursor_image_pos(&event) {
+                selection_rect_state.set(Some([x, y, x, y]));
+            }
+        })
+    };
+    let selecting = {
+        let selection_rect_state = selection_rect_state.clone();
+        let cursor_image_pos = cursor_image_pos.clone();
+        Callback::from(move |event: MouseEvent| {
+            if let (Some([left, top, _, _]), Some([x, y])) =
+                (*selection_rect_state, cursor_image_pos(&event))
+            {
+                selection_rect_state.set(Some([left, top, x, y]));
+            }
+        })
+    };
+    let stop_selecting = {
+        let selection_rect_state = selection_rect_state.clone();
+        let image_ref = image_ref.clone();
+        let on_select = props.on_select.clone();
+        let selection_mode = props.selection_mode;
+        Callback::from(move |_| {
+            let rect = match *selection_rect_state {
+                Some(rect) => rect,
+                None => return,
+            };
+            selection_rect_state.set(None);
+            let image = match image_ref.cast::<HtmlImageElement>() {
+                Some(image) if image.client_width() > 0 => image,
+                _ => return,
+            };
+
+            // The displayed image is zoomed, so scale back to the image's pixels
+            let scale = image.natural_width() as f64 / image.client_width() as f64;
+            let [left, top, right, bottom] = rect;
+            let (x, y) = (left.min(right) * scale, top.min(bottom) * scale);
+            let (width, height) = ((right - left).abs() * scale, (bottom - top).abs() * scale);
+            // Clicks without dragging don't select anything
+            if width < 1.0 || height < 1.0 {
+                return;
+            }
+            if let Some(selection_mode) = selection_mode {
+                on_select.emit(selection_mode.edit([
+                    x.round() as u32,
+                    y.round() as u32,
+                    width.round() as u32,
+                    height.round() as u32,
+                ]));
+            }
+        })
+    };
+    let selection_overlay = match (*selection_rect_state, image_ref.cast::<HtmlImageElement>()) {
+        (Some([left, top, right, bottom]), Some(image)) => {
+            // The image is moved by dragging, so the rectangle is moved with it
+            let offset_left = (image.offset_left() + image.client_left()) as f64;
+            let offset_top = (image.offset_top() + image.client_top()) as f64;
+            // Crops darken what's cut off, while redactions darken what's hidden
+            let shade = match props.selection_mode {
+                Some(edit_toolbar::SelectionMode::Crop) => {
+                    "box-shadow: 0 0 0 9999px rgba(0, 0, 0, 0.4);"
+                }
+                _ => "background-color: rgba(0, 0, 0, 0.6);",
+            };
+            html! {
+                <div
+                    class="absolute border-2 border-pink-200 border-dashed pointer-events-none"
+                    style={
+                        format!(
+                            "left: {}px; top: {}px; width: {}px; height: {}px; {}",
+                            offset_left + left.min(right),
+                            offset_top + top.min(bottom),
+                            (right - left).abs(),
+                            (bottom - top).abs(),
+                            shade,
+                        )
+                    }
+                />
+            }
+        }
+        _ => html! {},
+    };
+
+    // Annotating
+    // The annotation being drawn, in pixels of the image
+    let drawing_state = use_state_eq(|| None::<payloads::Annotation>);
+    let image_pixel_pos = {
+        let image_ref = image_ref.clone();
+        move |event: &MouseEvent| -> Option<[f32; 2]> {
+            let image = image_ref.cast::<HtmlImageElement>()?;
+            if image.client_width() == 0 {
+                return None;
+            }
+            let scale = image.natural_width() as f64 / image.client_width() as f64;
+            let [x, y] = cursor_image_pos(event)?;
+            Some([(x * scale) as f32, (y * scale) as f32])
+        }
+    };
+    let start_drawing = {
+        let drawing_state = drawing_state.clone();
+        let image_pixel_pos = image_pixel_pos.clone();
+        let annotation_style = props.annotation_style.clone();
+        let on_annotate = props.on_annotate.clone();
+        Callback::from(move |event: MouseEvent| {
+            let mut annotation =
+                match image_pixel_pos(&event).and_then(|point| annotation_style.start(point)) {
+                    Some(annotation) => annotation,
+                    None => return,
+                };
+            // Text is typed in instead of drawn
+            if let payloads::Shape::Text { text, .. } = &mut annotation.shape {
+                let typed = window()
+                    .and_then(|window| window.prompt_with_message("Text to add").ok())
+                    .flatten();
+                if let Some(typed) = typed {
+                    *text = typed;
+                    if annotation_layer::is_drawn(&annotation.shape) {
+                        on_annotate.emit(annotation);
+                    }
+                }
+                return;
+            }
+            drawing_state.set(Some(annotation));
+        })
+    };
+    let drawing = {
+        let drawing_state = drawing_state.clone();
+        Callback::from(move |event: MouseEvent| {
+            if let (Some(mut annotation), Some(point)) =
+                ((*drawing_state).clone(), image_pixel_pos(&event))
+            {
+                annotation_layer::extend_shape(&mut annotation.shape, point);
+                drawing_state.set(Some(annotation));
+            }
+        })
+    };
+    let stop_drawing = {
+        let drawing_state = drawing_state.clone();
+        let on_annotate = props.on_annotate.clone();
+        Callback::from(move |_| {
+            if let Some(annotation) = (*drawing_state).clone() {
+                drawing_state.set(None);
+                if annotation_layer::is_drawn(&annotation.shape) {
+                    on_annotate.emit(annotation);
+                }
+            }
+        })
+    };
+    let annotation_overlay = {
+        let mut annotations = props.annotations.clone();
+        annotations.extend((*drawing_state).clone());
+        let (width, height) = *natural_size_state;
+        annotation_layer::annotation_svg(&annotations, width, height, overlay_ref)
+    };
+
+    // Dragging the image moves it, unless annotating or selecting a rectangle
+    let (on_mouse_down, on_mouse_move, on_mouse_up) = match (
+        props.annotation_style.tool.is_some(),
+        props.selection_mode.is_some(),
+    ) {
+        (true, _) => (start_drawing, drawing, stop_drawing),
+        (false, true) => (start_selecting, selecting, stop_selecting),
+        (false, false) => (start_dragging, dragging, stop_dragging),
+    };
+    let is_drawing_on_image =
+        props.annotation_style.tool.is_some() || props.selection_mode.is_some();
+
     // Zooming
     let image_size_percent_ref = use_mut_ref(|| 100);
     let zoom_style_update = {
@@ -128,6 +383,7 @@ fn image_display(props: &ImageDisplayProps) -> Html {
                     format!("{}%", *image_size_percent_ref.borrow()).as_str(),
                 )
                 .unwrap();
+            sync_overlay();
         }
     };
     let on_zoom_in = {
@@ -189,19 +445,28 @@ fn image_display(props: &ImageDisplayProps) -> Html {
                 h-full w-full
             "}
             // style="width: 1520px !important"
-            onmousemove={ dragging }
-            onmouseup={ stop_dragging }
+            onmousemove={ on_mouse_move }
+            onmouseup={ on_mouse_up }
         >
             <div class="w-full">
-                <div class="w-max">
+                <div class="relative w-max">
                     <img
-                        onmousedown={ start_dragging }
+                        onmousedown={ on_mouse_down }
+                        onload={ on_image_load }
                         ref={ image_ref }
                         alt="Image from clipboard"
                         id="clipboard-image"
                         draggable="false"
-                        class="relative border-2 border-white border-opacity-20 cursor-move"
-                        src={ props.data_url.clone() }
+                        class={
+                            format!(
+                                "relative border-2 border-white border-opacity-20 {}",
+                                match is_drawing_on_image {
+                                    true => "cursor-crosshair",
+                                    false => "cursor-move",
+                                }
+                            )
+                        }
+                        src={ props.image_url.clone() }
                         style={
                             (match anti_aliasing {
                                 true => "",
@@ -209,6 +474,8 @@ fn image_display(props: &ImageDisplayProps) -> Html {
                             }).to_string()
                         }
                     />
+                    { annotation_overlay }
+                    { selection_overlay }
                 </div>
             </div>
             <div class="flex absolute bottom-0 left-0 m-4">
@@ -269,6 +536,9 @@ fn image_display(props: &ImageDisplayProps) -> Html {
 #[derive(PartialEq, Properties)]
 pub struct ClipboardImageProps {
     pub should_update_clipboard: bool,
+    pub should_open_image: bool,
+    /// Adjustment presets are saved into the settings
+    pub on_update_settings: Callback<global_settings::Settings>,
 }
 #[function_component(ClipboardImage)]
 pub fn clipboard_image(props: &ClipboardImageProps) -> Html {
@@ -276,17 +546,191 @@ pub fn clipboard_image(props: &ClipboardImageProps) -> Html {
     let settings =
         use_context::<global_settings::Settings>().expect("Could not find settings context");
 
-    // Clipboard data
-    let clipboard_state = use_state_eq(|| AttrValue::from("".to_owned()));
+    // The revision of the current image, which the backend serves the preview of. It changes
+    // with every edit, so the preview is reloaded. 0 means nothing was pasted yet
+    let clipboard_state = use_state_eq(|| 0_u64);
+
+    // History, which is reloaded whenever a new image is captured
+    let history_refresh_ref = use_mut_ref(|| 0);
+    let history_refresh_state = use_state_eq(|| 0);
+    let on_captured = {
+        let history_refresh_state = history_refresh_state.clone();
+        Callback::from(move |_| {
+            *history_refresh_ref.borrow_mut() += 1;
+            history_refresh_state.set(*history_refresh_ref.borrow());
+        })
+    };
+    let on_restore = {
+        let clipboard_state = clipboard_state.clone();
+        Callback::from(move |revision: u64| clipboard_state.set(revision))
+    };
+
+    // Editing
+    let selection_mode_state = use_state_eq(|| None::<edit_toolbar::SelectionMode>);
+    let annotation_style_state = use_state_eq(annotation_layer::AnnotationStyle::default);
+    // Selecting a rectangle and annotating both use dragging, so picking one stops the other
+    let on_selection_mode_change = {
+        let selection_mode_state = selection_mode_state.clone();
+        let annotation_style_state = annotation_style_state.clone();
+        Callback::from(move |selection_mode: Option<edit_toolbar::SelectionMode>| {
+            if selection_mode.is_some() {
+                annotation_style_state.set(annotation_layer::AnnotationStyle {
+                    tool: None,
+                    ..(*annotation_style_state).clone()
+                });
+            }
+            selection_mode_state.set(selection_mode);
+        })
+    };
+    let on_annotation_style_change = {
+        let selection_mode_state = selection_mode_state.clone();
+        let annotation_style_state = annotation_style_state.clone();
+        Callback::from(move |annotation_style: annotation_layer::AnnotationStyle| {
+            if annotation_style.tool.is_some() {
+                selection_mode_state.set(None);
+            }
+            annotation_style_state.set(annotation_style);
+        })
+    };
+    let on_edited = {
+        let clipboard_state = clipboard_state.clone();
+        Callback::from(move |revision: u64| clipboard_state.set(revision))
+    };
+    let on_select = {
+        let selection_mode_state = selection_mode_state.clone();
+        let on_edited = on_edited.clone();
+        Callback::from(move |edit: payloads::Edit| {
+            // Cropping is done once, while there's often more than one thing to redact
+            if matches!(edit, payloads::Edit::Crop { .. }) {
+                selection_mode_state.set(None);
+            }
+            edit_toolbar::edit_image(edit, on_edited.clone());
+        })
+    };
+
+    // Image info and adjustments, which are reloaded whenever the image changes
+    let image_info_state = use_state_eq(|| None::<payloads::ImageInfo>);
+    let adjustments_state = use_state_eq(global_settings::Adjustments::default);
+    {
+        let image_info_state = image_info_state.clone();
+        let adjustments_state = adjustments_state.clone();
+        use_effect_with_deps(
+            move |_| {
+                load_image_info(image_info_state);
+                adjustments_panel::load_adjustments(adjustments_state);
+                || ()
+            },
+            *clipboard_state,
+        )
+    }
+    let (can_undo, can_redo) = match &*image_info_state {
+        Some(image_info) => (image_info.can_undo, image_info.can_redo),
+        None => (false, false),
+    };
+
+    // Annotations are kept by the backend, so they come with the image info
+    let annotations = match &*image_info_state {
+        Some(image_info) => image_info.annotations.clone(),
+        None => Vec::new(),
+    };
+    // The annotations are shown straight away, while the new revision reloads the image info
+    let on_annotations_set = {
+        let image_info_state = image_info_state.clone();
+        let clipboard_state = clipboard_state.clone();
+        Callback::from(
+            move |(annotations, revision): (Vec<payloads::Annotation>, u64)| {
+                if let Some(mut image_info) = (*image_info_state).clone() {
+                    image_info.annotations = annotations;
+                    image_info_state.set(Some(image_info));
+                }
+                clipboard_state.set(revision);
+            },
+        )
+    };
+    let on_annotate = {
+        let annotations = annotations.clone();
+        let on_annotations_set = on_annotations_set.clone();
+        Callback::from(move |annotation: payloads::Annotation| {
+            let mut new_annotations = annotations.clone();
+            new_annotations.push(annotation);
+            annotation_layer::set_annotations(new_annotations, on_annotations_set.clone());
+        })
+    };
+    let on_undo_annotation = {
+        let annotations = annotations.clone();
+        let on_annotations_set = on_annotations_set.clone();
+        Callback::from(move |_| {
+            let mut new_annotations = annotations.clone();
+            new_annotations.pop();
+            annotation_layer::set_annotations(new_annotations, on_annotations_set.clone());
+        })
+    };
+    let on_clear_annotations = Callback::from(move |_| {
+        annotation_layer::set_annotations(Vec::new(), on_annotations_set.clone())
+    });
+    // Where the image came from (e.g. a copied file instead of a bitmap) and its size
+    let source_info = match &*image_info_state {
+        Some(image_info) if image_info.width > 0 => {
+            // Images are resized when they're saved, so the size they'll have is shown too
+            let (output_width, output_height) = settings
+                .encoder_options
+                .resize
+                .output_size(image_info.width, image_info.height);
+            html! {
+                <p class="text-sm">
+                    {
+                        format!(
+                            "{}x{} from {}",
+                            image_info.width,
+                            image_info.height,
+                            image_info.source.description()
+                        )
+                    }
+                    {
+                        match (output_width, output_height) == (image_info.width, image_info.height) {
+                            true => String::new(),
+                            false => format!(", saved as {}x{}", output_width, output_height),
+                        }
+                    }
+                </p>
+            }
+        }
+        _ => html! {},
+    };
+    // Formats without transparency flatten the image onto the transparency background
+    let transparency_warning = match &*image_info_state {
+        Some(image_info)
+            if image_info.has_transparency && !settings.save_format.supports_alpha() =>
+        {
+            html! {
+                <p class="px-2 py-1 text-black bg-yellow-200 rounded-md">
+                    {
+                        format!(
+                            "This image has transparency, which {} can't store. It'll be put on a {} background when saved",
+                            settings.save_format.to_string().to_uppercase(),
+                            match settings.encoder_options.transparency_background {
+                                global_settings::TransparencyBackground::Color => {
+                                    settings.encoder_options.transparency_color.clone()
+                                }
+                                ref background => background.to_string().to_lowercase(),
+                            },
+                        )
+                    }
+                </p>
+            }
+        }
+        _ => html! {},
+    };
 
     // Pasting
     let should_update_clipboard = props.should_update_clipboard;
     {
         let clipboard_state = clipboard_state.clone();
+        let on_captured = on_captured.clone();
         use_effect_with_deps(
             move |_| {
                 if settings.auto_paste || should_update_clipboard {
-                    update_clipboard(clipboard_state);
+                    update_clipboard(clipboard_state, on_captured, !should_update_clipboard);
                 }
                 || ()
             },
@@ -294,27 +738,177 @@ pub fn clipboard_image(props: &ClipboardImageProps) -> Html {
         )
     }
 
+    // Opening from the open button
+    let should_open_image = props.should_open_image;
+    {
+        let clipboard_state = clipboard_state.clone();
+        let on_captured = on_captured.clone();
+        use_effect_with_deps(
+            move |_| {
+                if should_open_image {
+                    open_image(clipboard_state, on_captured, None);
+                }
+                || ()
+            },
+            should_open_image,
+        )
+    }
+
+    // Opening files dropped onto the window
+    let file_hover_state = use_state_eq(|| false);
+    {
+        let file_hover_state = file_hover_state.clone();
+        use_tauri_event("tauri://file-drop-hover", move |_| {
+            file_hover_state.set(true)
+        });
+    }
+    {
+        let file_hover_state = file_hover_state.clone();
+        use_tauri_event("tauri://file-drop-cancelled", move |_| {
+            file_hover_state.set(false)
+        });
+    }
+    {
+        let clipboard_state = clipboard_state.clone();
+        let on_captured = on_captured.clone();
+        let file_hover_state = file_hover_state.clone();
+        use_tauri_event("tauri://file-drop", move |paths: JsValue| {
+            file_hover_state.set(false);
+            // Only one image can be shown, so the first file is opened
+            let paths: Vec<String> = paths.into_serde().unwrap_or_default();
+            if let Some(path) = paths.into_iter().next() {
+                open_image(clipboard_state.clone(), on_captured.clone(), Some(path));
+            }
+        });
+    }
+    let file_hover_overlay = match *file_hover_state {
+        true => html! {
+            <div class="flex absolute inset-0 z-10 justify-center items-center text-2xl bg-gray-900 bg-opacity-80 rounded-lg border-4 border-pink-200 border-dashed pointer-events-none">
+                { "Drop to open the image" }
+            </div>
+        },
+        false => html! {},
+    };
+
+    // Images found by the backend clipboard watcher
+    {
+        let clipboard_state = clipboard_state.clone();
+        use_tauri_event("clipboard-image-changed", move |revision: JsValue| {
+            clipboard_state.set(preview_revision(&revision));
+            on_captured.emit(());
+        });
+    }
+
     html! {
-        <ImageDisplay
-            reset_zoom={ should_update_clipboard }
-            data_url={ AttrValue::from(format!("data:image/png;base64,{}", *clipboard_state)) }
-        />
+        <div class="flex gap-2 h-full">
+            <div class="flex relative flex-col flex-1 gap-2 min-w-0">
+                { source_info }
+                { transparency_warning }
+                <edit_toolbar::EditToolbar
+                    { can_undo }
+                    { can_redo }
+                    selection_mode={ *selection_mode_state }
+                    { on_selection_mode_change }
+                    on_edited={ on_edited.clone() }
+                />
+                <adjustments_panel::AdjustmentsPanel
+                    adjustments={ (*adjustments_state).clone() }
+                    on_adjusted={ on_edited }
+                    on_update_settings={ props.on_update_settings.clone() }
+                />
+                <annotation_layer::AnnotationToolbar
+                    style={ (*annotation_style_state).clone() }
+                    on_style_change={ on_annotation_style_change }
+                    has_annotations={ !annotations.is_empty() }
+                    on_undo={ on_undo_annotation }
+                    on_clear={ on_clear_annotations }
+                />
+                <ImageDisplay
+                    reset_zoom={ should_update_clipboard || should_open_image }
+                    selection_mode={ *selection_mode_state }
+                    { on_select }
+                    { annotations }
+                    annotation_style={ (*annotation_style_state).clone() }
+                    { on_annotate }
+                    image_url={
+                        match *clipboard_state {
+                            0 => AttrValue::from(""),
+                            revision => AttrValue::from(preview_url_glue(revision as f64)),
+                        }
+                    }
+                />
+                { file_hover_overlay }
+            </div>
+            <history::HistoryStrip refresh={ *history_refresh_state } { on_restore } />
+        </div>
     }
 }
 
+/// Get the revision of the current image returned by the JavaScript glue.
+/// The backend only counts up from 1, so 0 is never a real revision
+pub fn preview_revision(revision: &JsValue) -> u64 {
+    revision.as_f64().unwrap_or_default() as u64
+}
+
+/// Load information about the current image using JavaScript glue
+fn load_image_info(image_info_state: UseStateHandle<Option<payloads::ImageInfo>>) {
+    spawn_local(async move {
+        // No warning is better than an alert every time the image changes
+        let image_info = match get_image_info_glue().await {
+            Ok(image_info) => image_info.into_serde().ok(),
+            Err(_) => None,
+        };
+        image_info_state.set(image_info);
+    })
+}
+
+/// Open an image file into the clipboard state using JavaScript glue.
+/// Without a path, the backend asks the user to pick one
+fn open_image(
+    clipboard_state: UseStateHandle<u64>,
+    on_captured: Callback<()>,
+    path: Option<String>,
+) {
+    spawn_local(async move {
+        match open_image_glue(path).await {
+            Ok(revision) => {
+                clipboard_state.set(preview_revision(&revision));
+                on_captured.emit(());
+            }
+            Err(e) => match backend_error(e) {
+                error::Error::DecodeFailed { path, .. } => window()
+                    .unwrap()
+                    .alert_with_message(
+                        format!("{} isn't an image, or its format isn't supported", path).as_str(),
+                    )
+                    .unwrap(),
+                error => alert_error(&error),
+            },
+        }
+    })
+}
+
 /// Update clipboard state from JavaScript glue
-fn update_clipboard(clipboard_state: UseStateHandle<AttrValue>) {
+/// Automatic pastes (e.g. auto paste on start) don't complain about the clipboard being empty
+fn update_clipboard(
+    clipboard_state: UseStateHandle<u64>,
+    on_captured: Callback<()>,
+    automatic: bool,
+) {
     spawn_local(async move {
         match read_clipboard_glue().await {
-            Ok(clipboard_contents) => {
-                clipboard_state.set(AttrValue::from(clipboard_contents.as_string().unwrap()))
+            Ok(revision) => {
+                clipboard_state.set(preview_revision(&revision));
+                on_captured.emit(());
             }
-            Err(e) => {
-                window()
+            Err(e) => match backend_error(e) {
+                error::Error::NoImageInClipboard if automatic => (),
+                error::Error::NoImageInClipboard => window()
                     .unwrap()
-                    .alert_with_message(&e.as_string().unwrap())
-                    .unwrap();
-            }
+                    .alert_with_message("There's no image in the clipboard. Copy one, then paste")
+                    .unwrap(),
+                error => alert_error(&error),
+            },
         }
     })
 }
@@ -12,6 +12,9 @@ use yew::prelude::*;
 #[derive(Properties, PartialEq)]
 pub struct ControlButtonProps {
     pub onclick: Callback<MouseEvent>,
+    /// e.g. while a save is running
+    #[prop_or_default]
+    pub disabled: bool,
     pub children: Children,
 }
 #[function_component(ControlButton)]
@@ -21,7 +24,8 @@ pub fn control_button(props: &ControlButtonProps) -> Html {
     html! {
         <button
             { onclick }
-            class="p-2 w-full bg-blue-800 rounded-md hover:bg-blue-700"
+            disabled={ props.disabled }
+            class="p-2 w-full bg-blue-800 rounded-md hover:bg-blue-700 disabled:opacity-50"
         >
             { props.children.clone() }
         </button>
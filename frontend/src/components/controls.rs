@@ -5,7 +5,11 @@
 
 /***** Setup *****/
 /* Imports */
-use super::global_settings;
+use super::{
+    alert_error, backend_error, copy_to_clipboard, error, global_settings, payloads,
+    use_tauri_event,
+};
+use std::collections::BTreeMap;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
 use web_sys::window;
@@ -19,13 +23,20 @@ mod widget;
 #[wasm_bindgen(module = "/src/static/glue.js")]
 extern "C" {
     #[wasm_bindgen(js_name = invokeSaveImage, catch)]
-    pub async fn save_image_glue(path: String, format: String) -> Result<JsValue, JsValue>;
+    pub async fn save_image_glue(
+        path: String,
+        format: String,
+        confirmed_path: Option<String>,
+    ) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeCancelSaves, catch)]
+    pub async fn cancel_saves_glue() -> Result<JsValue, JsValue>;
 }
 
 /***** Controls component *****/
 #[derive(PartialEq, Properties)]
 pub struct ControlsProps {
     pub on_should_update_clipboard: Callback<bool>,
+    pub on_should_open_image: Callback<bool>,
 }
 #[function_component(Controls)]
 pub fn controls(props: &ControlsProps) -> Html {
@@ -33,6 +44,48 @@ pub fn controls(props: &ControlsProps) -> Html {
     let settings =
         use_context::<global_settings::Settings>().expect("Could not find settings context");
 
+    // Copy image
+    let on_copy_image = {
+        let format = settings.save_format.to_string();
+        Callback::from(move |_| copy_to_clipboard(None, format.clone()))
+    };
+
+    // Saves run in the backend, which reports their progress until they're finished. More than
+    // one can run at once (e.g. from the history), so the latest one is shown
+    let running_saves_ref = use_mut_ref(BTreeMap::<u64, payloads::SaveProgress>::new);
+    let busy_state = use_state(|| None::<payloads::SaveProgress>);
+    {
+        let busy_state = busy_state.clone();
+        use_tauri_event("save-progress", move |progress: JsValue| {
+            let progress = match progress.into_serde::<payloads::SaveProgress>() {
+                Ok(progress) => progress,
+                Err(_) => return,
+            };
+            let mut running_saves = running_saves_ref.borrow_mut();
+            match progress.stage {
+                payloads::SaveStage::Finished => running_saves.remove(&progress.job_id),
+                _ => running_saves.insert(progress.job_id, progress),
+            };
+            busy_state.set(running_saves.values().next_back().cloned());
+        });
+    }
+    let on_cancel_save = Callback::from(|_| {
+        spawn_local(async {
+            if let Err(e) = cancel_saves_glue().await {
+                alert_error(&backend_error(e));
+            }
+        })
+    });
+
+    // Where the last image was saved, shown instead of an alert
+    let status_state = use_state(|| None::<String>);
+    let on_saved = {
+        let status_state = status_state.clone();
+        Callback::from(move |saved_path: String| {
+            status_state.set(Some(format!("Saved to {}", saved_path)))
+        })
+    };
+
     // Save image
     let save_image_path = settings.save_path;
     let on_save_image = {
@@ -44,7 +97,12 @@ pub fn controls(props: &ControlsProps) -> Html {
                     .unwrap()
             }),
             false => Callback::from(move |_| {
-                save_clipboard_image(save_image_path.clone(), settings.save_format.to_string())
+                save_clipboard_image(
+                    save_image_path.clone(),
+                    settings.save_format.to_string(),
+                    None,
+                    on_saved.clone(),
+                )
             }),
         }
     };
@@ -58,6 +116,30 @@ pub fn controls(props: &ControlsProps) -> Html {
         })
     };
 
+    // Open image
+    let on_should_open_image = props.on_should_open_image.clone();
+    let on_open_image = Callback::from(move |_| {
+        on_should_open_image.emit(true);
+    });
+
+    let status = match (&*busy_state, &*status_state) {
+        (Some(progress), _) => html! {
+            <div class="flex gap-2 items-center">
+                <p class="flex-1 text-sm">{ save_progress_text(progress) }</p>
+                <button
+                    onclick={ on_cancel_save }
+                    class="px-2 py-1 text-black bg-gray-300 rounded-md hover:bg-gray-200"
+                >
+                    { "Cancel" }
+                </button>
+            </div>
+        },
+        (None, Some(status)) => html! {
+            <p class="text-sm">{ status }</p>
+        },
+        (None, None) => html! {},
+    };
+
     html! {
         <widget::Widget>
             <p class="text-2xl">{ "Controls" }</p>
@@ -66,32 +148,105 @@ pub fn controls(props: &ControlsProps) -> Html {
                 <control_button::ControlButton onclick={ on_update_clipboard }>
                     { "Refresh clipboard" }
                 </control_button::ControlButton>
+                // Open
+                <control_button::ControlButton onclick={ on_open_image }>
+                    { "Open image" }
+                </control_button::ControlButton>
                 // Save
-                <control_button::ControlButton onclick={ on_save_image }>
+                <control_button::ControlButton onclick={ on_save_image } disabled={ busy_state.is_some() }>
                     { "Save image" }
                 </control_button::ControlButton>
+                // Copy
+                <control_button::ControlButton onclick={ on_copy_image }>
+                    { "Copy to clipboard" }
+                </control_button::ControlButton>
             </div>
+            { status }
         </widget::Widget>
 
     }
 }
 
-/// Save clipboard to file from JavaScript glue
-fn save_clipboard_image(path: String, format: String) {
+/// Describe what a running save is doing, e.g. "Encoding (1.5 MB)…"
+fn save_progress_text(progress: &payloads::SaveProgress) -> String {
+    let stage = match progress.stage {
+        payloads::SaveStage::Rendering => "Rendering the image",
+        payloads::SaveStage::Encoding => "Encoding",
+        payloads::SaveStage::Writing => "Writing the file",
+        payloads::SaveStage::Finished => "Finished",
+    };
+    match progress.bytes_encoded {
+        0 => format!("{}…", stage),
+        bytes_encoded => format!("{} ({:.1} MB)…", stage, bytes_encoded as f64 / 1_000_000.0),
+    }
+}
+
+/// Save clipboard to file from JavaScript glue. The save runs in the background, so the window
+/// stays usable and its progress is shown; `on_saved` is called with the path once it's done.
+/// If the file exists and the user should be asked, this will be called again once confirmed
+fn save_clipboard_image(
+    path: String,
+    format: String,
+    confirmed_path: Option<String>,
+    on_saved: Callback<String>,
+) {
     spawn_local(async move {
-        match save_image_glue(path.clone(), format.clone()).await {
-            Ok(_) => {
-                window()
+        match save_image_glue(path.clone(), format.clone(), confirmed_path).await {
+            Ok(outcome) => match outcome.into_serde::<payloads::SaveOutcome>() {
+                Ok(payloads::SaveOutcome::Saved(saved_path)) => on_saved.emit(saved_path),
+                Ok(payloads::SaveOutcome::Exists(existing_path)) => {
+                    let overwrite = window()
+                        .unwrap()
+                        .confirm_with_message(
+                            format!("{} already exists. Overwrite it?", existing_path).as_str(),
+                        )
+                        .unwrap_or(false);
+                    if overwrite {
+                        save_clipboard_image(path, format, Some(existing_path), on_saved);
+                    }
+                }
+                Ok(payloads::SaveOutcome::Skipped(existing_path)) => window()
                     .unwrap()
-                    .alert_with_message(format!("Saved clipboard image to {}", path).as_str())
-                    .unwrap();
-            }
-            Err(e) => {
-                window()
+                    .alert_with_message(
+                        format!(
+                            "{} already exists, so the image wasn't saved",
+                            existing_path
+                        )
+                        .as_str(),
+                    )
+                    .unwrap(),
+                Err(_) => window()
                     .unwrap()
-                    .alert_with_message(&e.as_string().unwrap())
-                    .unwrap();
-            }
+                    .alert_with_message("Got an unexpected response when saving the image")
+                    .unwrap(),
+            },
+            Err(e) => match backend_error(e) {
+                error::Error::NoImageInClipboard => window()
+                    .unwrap()
+                    .alert_with_message("Paste an image before saving")
+                    .unwrap(),
+                error::Error::InvalidFormat(format) => window()
+                    .unwrap()
+                    .alert_with_message(
+                        format!(
+                            "This build can't save as {}, so please choose another save format",
+                            format
+                        )
+                        .as_str(),
+                    )
+                    .unwrap(),
+                error::Error::IoError { path, message } => window()
+                    .unwrap()
+                    .alert_with_message(
+                        format!(
+                            "Couldn't save to {}: {}\nCheck the save path in the settings",
+                            path, message
+                        )
+                        .as_str(),
+                    )
+                    .unwrap(),
+                error => alert_error(&error),
+            },
         }
     })
 }
@@ -0,0 +1,293 @@
+/*
+ * Edit toolbar
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use super::{alert_error, backend_error, global_settings, payloads, preview_revision};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use yew::{prelude::*, use_context};
+
+/***** Glue *****/
+#[wasm_bindgen(module = "/src/static/glue.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = invokeEditImage, catch)]
+    pub async fn edit_image_glue(edit: JsValue) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeUndoEdit, catch)]
+    pub async fn undo_edit_glue() -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeRedoEdit, catch)]
+    pub async fn redo_edit_glue() -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeResetEdits, catch)]
+    pub async fn reset_edits_glue() -> Result<JsValue, JsValue>;
+}
+
+/// What dragging a rectangle on the image does
+#[derive(Clone, Copy, PartialEq)]
+pub enum SelectionMode {
+    Crop,
+    Redact(payloads::RedactStyle),
+}
+impl SelectionMode {
+    /// Make the edit for a rectangle given as [x, y, width, height] in pixels of the image
+    pub fn edit(&self, rectangle: [u32; 4]) -> payloads::Edit {
+        let [x, y, width, height] = rectangle;
+        match self {
+            SelectionMode::Crop => payloads::Edit::Crop {
+                x,
+                y,
+                width,
+                height,
+            },
+            SelectionMode::Redact(style) => payloads::Edit::Redact {
+                x,
+                y,
+                width,
+                height,
+                style: *style,
+            },
+        }
+    }
+}
+
+/// Which edit stack action to take
+#[derive(Clone, Copy)]
+enum EditAction {
+    Undo,
+    Redo,
+    Reset,
+}
+
+/***** Edit toolbar component *****/
+#[derive(PartialEq, Properties)]
+pub struct EditToolbarProps {
+    pub can_undo: bool,
+    pub can_redo: bool,
+    /// What dragging a rectangle on the image does, or `None` to move the image instead
+    pub selection_mode: Option<SelectionMode>,
+    pub on_selection_mode_change: Callback<Option<SelectionMode>>,
+    /// Called with the revision of the edited image
+    pub on_edited: Callback<u64>,
+}
+#[function_component(EditToolbar)]
+pub fn edit_toolbar(props: &EditToolbarProps) -> Html {
+    let settings =
+        use_context::<global_settings::Settings>().expect("Could not find settings context");
+
+    // Free-angle rotation
+    let rotate_degrees_state = use_state_eq(|| 15.0_f32);
+    let on_rotate_degrees_change = {
+        let rotate_degrees_state = rotate_degrees_state.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                if let Ok(degrees) = input.value().parse::<f32>() {
+                    rotate_degrees_state.set(degrees);
+                }
+            }
+        })
+    };
+
+    // Padding
+    let pad_size_state = use_state_eq(|| 16_u32);
+    let pad_color_state = use_state_eq(|| "#ffffff".to_string());
+    let pad_transparent_state = use_state_eq(|| false);
+    let on_pad_size_change = {
+        let pad_size_state = pad_size_state.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                if let Ok(size) = input.value().parse::<u32>() {
+                    pad_size_state.set(size);
+                }
+            }
+        })
+    };
+    let on_pad_color_change = {
+        let pad_color_state = pad_color_state.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                pad_color_state.set(input.value());
+            }
+        })
+    };
+    let on_pad_transparent_change = {
+        let pad_transparent_state = pad_transparent_state.clone();
+        Callback::from(move |event: Event| {
+            if let Some(input) = event.target_dyn_into::<HtmlInputElement>() {
+                pad_transparent_state.set(input.checked());
+            }
+        })
+    };
+    // Transparent padding is the color with no alpha
+    let pad_color = match *pad_transparent_state {
+        true => format!("{}00", *pad_color_state),
+        false => (*pad_color_state).clone(),
+    };
+
+    let edit_button = |label: &str, title: &str, edit: payloads::Edit| {
+        let on_edited = props.on_edited.clone();
+        let onclick = Callback::from(move |_| edit_image(edit.clone(), on_edited.clone()));
+        html! {
+            <button { onclick } title={ title.to_string() } class={ BUTTON_CLASS }>
+                { label }
+            </button>
+        }
+    };
+    let action_button = |label: &str, action: EditAction, enabled: bool| {
+        let on_edited = props.on_edited.clone();
+        let onclick = Callback::from(move |_| change_edits(action, on_edited.clone()));
+        html! {
+            <button { onclick } disabled={ !enabled } class={ BUTTON_CLASS }>
+                { label }
+            </button>
+        }
+    };
+    // Clicking the selected mode's button again stops selecting
+    let selection_button = |label: &str, title: &str, selection_mode: SelectionMode| {
+        let selected = props.selection_mode == Some(selection_mode);
+        let on_selection_mode_change = props.on_selection_mode_change.clone();
+        let onclick = Callback::from(move |_| {
+            on_selection_mode_change.emit(match selected {
+                true => None,
+                false => Some(selection_mode),
+            })
+        });
+        html! {
+            <button
+                { onclick }
+                title={ title.to_string() }
+                class={
+                    match selected {
+                        true => "px-2 py-1 text-black bg-pink-200 rounded-md",
+                        false => BUTTON_CLASS,
+                    }
+                }
+            >
+                { label }
+            </button>
+        }
+    };
+
+    html! {
+        <div class="flex flex-wrap gap-1 items-center text-sm">
+            {
+                selection_button(
+                    "Crop",
+                    "Drag a rectangle on the image to crop it",
+                    SelectionMode::Crop,
+                )
+            }
+            { edit_button("⟲", "Rotate left", payloads::Edit::Rotate270) }
+            { edit_button("⟳", "Rotate right", payloads::Edit::Rotate90) }
+            { edit_button("180°", "Rotate 180 degrees", payloads::Edit::Rotate180) }
+            { edit_button("⇆", "Flip horizontally", payloads::Edit::FlipHorizontal) }
+            { edit_button("⇅", "Flip vertically", payloads::Edit::FlipVertical) }
+            <input
+                onchange={ on_rotate_degrees_change }
+                class="w-16 text-black"
+                type="number"
+                min=-360
+                max=360
+                step="any"
+                value={ rotate_degrees_state.to_string() }
+            />
+            {
+                edit_button(
+                    "Rotate",
+                    "Rotate clockwise by the degrees given",
+                    payloads::Edit::Rotate { degrees: *rotate_degrees_state },
+                )
+            }
+            {
+                edit_button(
+                    "Trim",
+                    "Crop off the border around the image",
+                    payloads::Edit::Trim { tolerance: settings.trim_tolerance },
+                )
+            }
+            <input
+                onchange={ on_pad_size_change }
+                class="w-16 text-black"
+                type="number"
+                min=1
+                value={ pad_size_state.to_string() }
+            />
+            <input
+                onchange={ on_pad_color_change }
+                type="color"
+                value={ (*pad_color_state).clone() }
+            />
+            <label title="Make the padding transparent">
+                <input
+                    onchange={ on_pad_transparent_change }
+                    type="checkbox"
+                    checked={ *pad_transparent_state }
+                />
+                { " Clear" }
+            </label>
+            {
+                edit_button(
+                    "Pad",
+                    "Add a border of the size and color given around the image",
+                    payloads::Edit::Pad { size: *pad_size_state, color: pad_color },
+                )
+            }
+            <span>{ "Redact:" }</span>
+            {
+                selection_button(
+                    "Black box",
+                    "Drag rectangles on the image to fill them with black",
+                    SelectionMode::Redact(payloads::RedactStyle::Fill),
+                )
+            }
+            {
+                selection_button(
+                    "Blur",
+                    "Drag rectangles on the image to blur them",
+                    SelectionMode::Redact(payloads::RedactStyle::Blur),
+                )
+            }
+            {
+                selection_button(
+                    "Pixelate",
+                    "Drag rectangles on the image to pixelate them",
+                    SelectionMode::Redact(payloads::RedactStyle::Pixelate),
+                )
+            }
+            { action_button("Undo", EditAction::Undo, props.can_undo) }
+            { action_button("Redo", EditAction::Redo, props.can_redo) }
+            { action_button("Reset edits", EditAction::Reset, props.can_undo || props.can_redo) }
+        </div>
+    }
+}
+
+/// The style of the toolbar buttons
+const BUTTON_CLASS: &str =
+    "px-2 py-1 text-black bg-gray-300 rounded-md hover:bg-gray-200 disabled:opacity-50";
+
+/// Add an edit to the image using JavaScript glue
+pub fn edit_image(edit: payloads::Edit, on_edited: Callback<u64>) {
+    spawn_local(async move {
+        let edit = JsValue::from_serde(&edit).unwrap();
+        match edit_image_glue(edit).await {
+            Ok(revision) => on_edited.emit(preview_revision(&revision)),
+            Err(e) => alert_error(&backend_error(e)),
+        }
+    })
+}
+
+/// Undo, redo or reset the edits using JavaScript glue
+fn change_edits(action: EditAction, on_edited: Callback<u64>) {
+    spawn_local(async move {
+        let result = match action {
+            EditAction::Undo => undo_edit_glue().await,
+            EditAction::Redo => redo_edit_glue().await,
+            EditAction::Reset => reset_edits_glue().await,
+        };
+        match result {
+            Ok(revision) => on_edited.emit(preview_revision(&revision)),
+            Err(e) => alert_error(&backend_error(e)),
+        }
+    })
+}
@@ -0,0 +1,278 @@
+/*
+ * Clipboard image history strip
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use super::{
+    alert_error, backend_error, copy_to_clipboard, error, global_settings, payloads,
+    preview_revision, use_tauri_event,
+};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::window;
+use yew::{prelude::*, virtual_dom::AttrValue};
+#[path = "./widget.rs"]
+mod widget;
+
+/***** Glue *****/
+#[wasm_bindgen(module = "/src/static/glue.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = invokeListHistory, catch)]
+    pub async fn list_history_glue() -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokePreviewHistoryEntry, catch)]
+    pub async fn preview_history_entry_glue(id: u32) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeRestoreHistoryEntry, catch)]
+    pub async fn restore_history_entry_glue(id: u32) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeDeleteHistoryEntry, catch)]
+    pub async fn delete_history_entry_glue(id: u32) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeSaveHistoryEntry, catch)]
+    pub async fn save_history_entry_glue(
+        id: u32,
+        path: String,
+        format: String,
+        confirmed_path: Option<String>,
+    ) -> Result<JsValue, JsValue>;
+}
+
+/// Thumbnails that were already loaded, by history entry ID
+type ThumbnailCache = Rc<RefCell<HashMap<u64, AttrValue>>>;
+
+/***** History strip component *****/
+#[derive(PartialEq, Properties)]
+pub struct HistoryStripProps {
+    /// Changing this reloads the history (e.g. after a new image was captured)
+    pub refresh: u32,
+    /// Called with the revision of the current image once an entry is restored
+    pub on_restore: Callback<u64>,
+}
+#[function_component(HistoryStrip)]
+pub fn history_strip(props: &HistoryStripProps) -> Html {
+    // Settings
+    let settings =
+        use_context::<global_settings::Settings>().expect("Could not find settings context");
+
+    // History entries and their thumbnails
+    let entries_state = use_state_eq(Vec::<payloads::HistoryEntry>::new);
+    let thumbnails_ref: ThumbnailCache = use_mut_ref(HashMap::new);
+
+    // Why the last image couldn't be added, until one is
+    let add_error_state = use_state_eq(|| None::<String>);
+    {
+        let add_error_state = add_error_state.clone();
+        use_tauri_event("history-error", move |error: JsValue| {
+            add_error_state.set(Some(backend_error(error).to_string()))
+        });
+    }
+    {
+        let add_error_state = add_error_state.clone();
+        use_effect_with_deps(
+            move |_| {
+                add_error_state.set(None);
+                || ()
+            },
+            entries_state.last().map(|entry| entry.id),
+        )
+    }
+
+    // Loading
+    {
+        let entries_state = entries_state.clone();
+        let thumbnails_ref = thumbnails_ref.clone();
+        use_effect_with_deps(
+            move |_| {
+                load_history(entries_state, thumbnails_ref);
+                || ()
+            },
+            props.refresh,
+        )
+    }
+
+    let thumbnails = thumbnails_ref.borrow();
+    let entry_list = entries_state
+        .iter()
+        .rev()
+        .map(|entry| {
+            // History IDs are small, and JavaScript can't hold a u64 anyways
+            let id = entry.id as u32;
+            let on_restore = {
+                let on_restore = props.on_restore.clone();
+                Callback::from(move |_| restore_history_entry(id, on_restore.clone()))
+            };
+            let on_save = {
+                let path = settings.save_path.clone();
+                let format = settings.save_format.to_string();
+                Callback::from(move |_| save_history_entry(id, path.clone(), format.clone(), None))
+            };
+            let on_copy = {
+                let format = settings.save_format.to_string();
+                Callback::from(move |_| copy_to_clipboard(Some(id), format.clone()))
+            };
+            let on_delete = {
+                let entries_state = entries_state.clone();
+                let thumbnails_ref = thumbnails_ref.clone();
+                Callback::from(move |_| {
+                    delete_history_entry(id, entries_state.clone(), thumbnails_ref.clone())
+                })
+            };
+            let captured_at = js_sys::Date::new(&JsValue::from_f64(entry.timestamp as f64 * 1000.0))
+                .to_locale_string("default", &JsValue::UNDEFINED);
+
+            html! {
+                <div key={ entry.id } class="flex flex-col gap-1">
+                    <img
+                        onclick={ on_restore }
+                        alt="History entry"
+                        title="Restore this image"
+                        draggable="false"
+                        class="border-2 border-white border-opacity-20 cursor-pointer checkerboard"
+                        src={
+                            thumbnails
+                                .get(&entry.id)
+                                .map(|thumbnail| format!("data:image/png;base64,{}", thumbnail))
+                                .unwrap_or_default()
+                        }
+                    />
+                    <p class="text-xs">
+                        { String::from(captured_at) }
+                        <br />
+                        { format!("{}x{}", entry.width, entry.height) }
+                    </p>
+                    <div class="flex gap-1">
+                        <button
+                            onclick={ on_save }
+                            class="px-2 py-1 w-max text-xs text-black bg-gray-300 rounded-md hover:bg-gray-200"
+                        >
+                            { "Save" }
+                        </button>
+                        <button
+                            onclick={ on_copy }
+                            class="px-2 py-1 w-max text-xs text-black bg-gray-300 rounded-md hover:bg-gray-200"
+                        >
+                            { "Copy" }
+                        </button>
+                        <button
+                            onclick={ on_delete }
+                            class="px-2 py-1 w-max text-xs text-black bg-gray-300 rounded-md hover:bg-gray-200"
+                        >
+                            { "Delete" }
+                        </button>
+                    </div>
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <widget::Widget class="flex overflow-y-auto flex-col gap-2 w-40 h-full">
+            <p class="text-2xl">{ "History" }</p>
+            {
+                match &*add_error_state {
+                    Some(add_error) => html! { <p class="text-xs text-red-300">{ add_error.clone() }</p> },
+                    None => html! {},
+                }
+            }
+            { entry_list }
+        </widget::Widget>
+    }
+}
+
+/// Load the history entries and any new thumbnails using JavaScript glue
+fn load_history(
+    entries_state: UseStateHandle<Vec<payloads::HistoryEntry>>,
+    thumbnails_ref: ThumbnailCache,
+) {
+    spawn_local(async move {
+        let entries: Vec<payloads::HistoryEntry> = match list_history_glue().await {
+            Ok(entries) => entries.into_serde().unwrap_or_default(),
+            Err(e) => return alert_error(&backend_error(e)),
+        };
+
+        // Only load thumbnails that haven't been loaded yet
+        for entry in entries.iter() {
+            if thumbnails_ref.borrow().contains_key(&entry.id) {
+                continue;
+            }
+            if let Ok(thumbnail) = preview_history_entry_glue(entry.id as u32).await {
+                thumbnails_ref.borrow_mut().insert(
+                    entry.id,
+                    AttrValue::from(thumbnail.as_string().unwrap_or_default()),
+                );
+            }
+        }
+
+        entries_state.set(entries);
+    })
+}
+
+/// Restore a history entry using JavaScript glue
+fn restore_history_entry(id: u32, on_restore: Callback<u64>) {
+    spawn_local(async move {
+        match restore_history_entry_glue(id).await {
+            Ok(revision) => on_restore.emit(preview_revision(&revision)),
+            Err(e) => alert_error(&backend_error(e)),
+        }
+    })
+}
+
+/// Save a history entry to a file using JavaScript glue
+/// If the file exists and the user should be asked, this will be called again once confirmed
+fn save_history_entry(id: u32, path: String, format: String, confirmed_path: Option<String>) {
+    spawn_local(async move {
+        match save_history_entry_glue(id, path.clone(), format.clone(), confirmed_path).await {
+            Ok(outcome) => match outcome.into_serde::<payloads::SaveOutcome>() {
+                Ok(payloads::SaveOutcome::Saved(saved_path)) => window()
+                    .unwrap()
+                    .alert_with_message(format!("Saved history image to {}", saved_path).as_str())
+                    .unwrap(),
+                Ok(payloads::SaveOutcome::Exists(existing_path)) => {
+                    let overwrite = window()
+                        .unwrap()
+                        .confirm_with_message(
+                            format!("{} already exists. Overwrite it?", existing_path).as_str(),
+                        )
+                        .unwrap_or(false);
+                    if overwrite {
+                        save_history_entry(id, path, format, Some(existing_path));
+                    }
+                }
+                Ok(payloads::SaveOutcome::Skipped(existing_path)) => window()
+                    .unwrap()
+                    .alert_with_message(
+                        format!(
+                            "{} already exists, so the image wasn't saved",
+                            existing_path
+                        )
+                        .as_str(),
+                    )
+                    .unwrap(),
+                Err(_) => alert_error(&error::Error::Other(
+                    "Got an unexpected response when saving the image".to_string(),
+                )),
+            },
+            Err(e) => alert_error(&backend_error(e)),
+        }
+    })
+}
+
+/// Delete a history entry using JavaScript glue, then reload the history
+fn delete_history_entry(
+    id: u32,
+    entries_state: UseStateHandle<Vec<payloads::HistoryEntry>>,
+    thumbnails_ref: ThumbnailCache,
+) {
+    spawn_local(async move {
+        match delete_history_entry_glue(id).await {
+            Ok(_) => (),
+            // It's already gone, so the history just needs to be reloaded
+            Err(e) => match backend_error(e) {
+                error::Error::HistoryEntryNotFound(_) => (),
+                error => return alert_error(&error),
+            },
+        }
+        thumbnails_ref.borrow_mut().remove(&(id as u64));
+        load_history(entries_state, thumbnails_ref);
+    })
+}
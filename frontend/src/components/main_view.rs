@@ -6,16 +6,46 @@
 /***** Setup *****/
 /* Imports */
 #![allow(clippy::duplicate_mod)]
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::window;
 use yew::{prelude::*, ContextProvider};
+#[path = "./cache_stats.rs"]
+mod cache_stats;
 #[path = "./clipboard_image.rs"]
 mod clipboard_image;
 #[path = "./controls.rs"]
 mod controls;
+#[path = "../../../shared/error.rs"]
+pub mod error;
 #[path = "../../../shared/settings.rs"]
 pub mod global_settings;
+#[path = "../../../shared/payloads.rs"]
+pub mod payloads;
 #[path = "./settings.rs"]
 mod settings;
 
+/***** Glue *****/
+#[wasm_bindgen(module = "/src/static/glue.js")]
+extern "C" {
+    #[wasm_bindgen(js_name = invokeSyncSettings, catch)]
+    pub async fn sync_settings_glue(settings_text: String) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeWriteClipboard, catch)]
+    pub async fn write_clipboard_glue(
+        history_id: Option<u32>,
+        format: Option<String>,
+    ) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = listenEvent, catch)]
+    pub async fn listen_event_glue(
+        name: &str,
+        callback: &js_sys::Function,
+    ) -> Result<JsValue, JsValue>;
+}
+
 /***** Main view *****/
 #[function_component(MainView)]
 pub fn main_view() -> Html {
@@ -27,6 +57,14 @@ pub fn main_view() -> Html {
             settings_state.set(new_settings)
         })
     };
+    // The backend needs to know about the settings too (e.g. for the clipboard watcher)
+    use_effect_with_deps(
+        move |settings| {
+            sync_settings(settings.clone());
+            || ()
+        },
+        (*settings_state).clone(),
+    );
 
     // Paste button callback from control widget
     let should_update_clipboard = use_state_eq(|| false);
@@ -38,6 +76,16 @@ pub fn main_view() -> Html {
         })
     };
 
+    // Open button callback from control widget
+    let should_open_image = use_state_eq(|| false);
+    let on_should_open_image = {
+        let should_open_image = should_open_image.clone();
+        Callback::from(move |_| {
+            should_open_image.set(true);
+            should_open_image.set(false);
+        })
+    };
+
     // Right side collapsed
     let right_side_collapsed_state = use_state_eq(|| true);
     let on_collapsed_toggle_click = {
@@ -45,12 +93,26 @@ pub fn main_view() -> Html {
         Callback::from(move |_| right_side_collapsed_state.set(!*right_side_collapsed_state))
     };
 
+    // Only debug builds show how the backend is doing
+    let debug_view = match cfg!(debug_assertions) {
+        true => html! {
+            <div class="flex-initial mt-2">
+                <cache_stats::CacheStats />
+            </div>
+        },
+        false => html! {},
+    };
+
     html! {
         <ContextProvider<global_settings::Settings> context={(*settings_state).clone()}>
             <div class="flex p-2 h-screen">
                 // Image view
                 <div class="grid flex-1 w-full">
-                    <clipboard_image::ClipboardImage should_update_clipboard={ *should_update_clipboard } />
+                    <clipboard_image::ClipboardImage
+                        should_update_clipboard={ *should_update_clipboard }
+                        should_open_image={ *should_open_image }
+                        on_update_settings={ on_update_settings.clone() }
+                    />
                 </div>
                 // Settings and controls
                 <div class={
@@ -66,8 +128,9 @@ pub fn main_view() -> Html {
                         <settings::Settings { on_update_settings } />
                     </div>
                     <div class="flex-initial">
-                        <controls::Controls { on_should_update_clipboard } />
+                        <controls::Controls { on_should_update_clipboard } { on_should_open_image } />
                     </div>
+                    { debug_view }
                 </div>
                 // Collapse
                 <button onclick={ on_collapsed_toggle_click } class="ml-2 text-white">
@@ -82,3 +145,112 @@ pub fn main_view() -> Html {
         </ContextProvider<global_settings::Settings>>
     }
 }
+
+/// Send the settings to the backend using JavaScript glue
+fn sync_settings(settings: global_settings::Settings) {
+    let serialized_data = match serde_json::to_string(&settings) {
+        Ok(serialized_data) => serialized_data,
+        Err(_) => return,
+    };
+
+    spawn_local(async move {
+        if let Err(e) = sync_settings_glue(serialized_data).await {
+            alert_error(&backend_error(e));
+        }
+    })
+}
+
+/// Copy the current image (or a history entry) to the clipboard using JavaScript glue.
+/// It's copied as it'd look once saved in the format, e.g. without transparency for JPEGs
+pub fn copy_to_clipboard(history_id: Option<u32>, format: String) {
+    spawn_local(async move {
+        match write_clipboard_glue(history_id, Some(format)).await {
+            Ok(_) => (),
+            Err(e) => match backend_error(e) {
+                error::Error::NoImageInClipboard => window()
+                    .unwrap()
+                    .alert_with_message("Paste an image before copying it")
+                    .unwrap(),
+                error::Error::ClipboardWriteRefused(_) => window()
+                    .unwrap()
+                    .alert_with_message(
+                        "Another application is holding the clipboard, so the image couldn't be copied. Please try again",
+                    )
+                    .unwrap(),
+                error => alert_error(&error),
+            },
+        }
+    })
+}
+
+/// Get the backend error from an error returned by the JavaScript glue.
+/// Errors that didn't come from the backend (e.g. Tauri failing to call a command) are `Other`
+pub fn backend_error(e: JsValue) -> error::Error {
+    match e.into_serde() {
+        Ok(error) => error,
+        Err(_) => error::Error::Other(
+            e.as_string()
+                .unwrap_or_else(|| "Something went wrong, but no reason was provided".to_string()),
+        ),
+    }
+}
+
+/// Show an error to the user. Canceled dialogs were the user's choice, so they aren't shown
+pub fn alert_error(error: &error::Error) {
+    if *error == error::Error::Canceled {
+        return;
+    }
+    window()
+        .unwrap()
+        .alert_with_message(error.to_string().as_str())
+        .unwrap();
+}
+
+/***** Hooks *****/
+/// Listen to an event from the backend (or Tauri, e.g. `tauri://file-drop`) while the component
+/// is shown, calling the callback with the event's payload. The callback is the one from the
+/// first render, so it should only use handles that stay valid, like state handles
+pub fn use_tauri_event<F>(name: &'static str, callback: F)
+where
+    F: Fn(JsValue) + 'static,
+{
+    use_effect_with_deps(
+        move |_| {
+            let callback = Closure::wrap(Box::new(callback) as Box<dyn Fn(JsValue)>);
+
+            // Listening is async, so the unlisten function is only available later. If the
+            // component is gone by then, it stops listening right away
+            let unlisten_ref: Rc<RefCell<Option<js_sys::Function>>> = Rc::new(RefCell::new(None));
+            let stopped_ref = Rc::new(Cell::new(false));
+            {
+                let unlisten_ref = unlisten_ref.clone();
+                let stopped_ref = stopped_ref.clone();
+                let js_callback: js_sys::Function = callback.as_ref().clone().unchecked_into();
+                spawn_local(async move {
+                    let unlisten: js_sys::Function = match listen_event_glue(name, &js_callback)
+                        .await
+                        .map(|unlisten| unlisten.dyn_into())
+                    {
+                        Ok(Ok(unlisten)) => unlisten,
+                        _ => return,
+                    };
+                    match stopped_ref.get() {
+                        true => {
+                            let _ = unlisten.call0(&JsValue::NULL);
+                        }
+                        false => *unlisten_ref.borrow_mut() = Some(unlisten),
+                    }
+                });
+            }
+
+            move || {
+                stopped_ref.set(true);
+                if let Some(unlisten) = unlisten_ref.borrow_mut().take() {
+                    let _ = unlisten.call0(&JsValue::NULL);
+                }
+                drop(callback);
+            }
+        },
+        name,
+    );
+}
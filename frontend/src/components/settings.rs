@@ -5,8 +5,8 @@
 
 /***** Setup *****/
 /* Imports */
-use super::global_settings;
-use std::{path::PathBuf, str::FromStr};
+use super::{alert_error, backend_error, error, global_settings};
+use std::{ffi::OsStr, fmt::Display, path::PathBuf, str::FromStr};
 use strum::IntoEnumIterator;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
@@ -22,6 +22,10 @@ mod widget;
 extern "C" {
     #[wasm_bindgen(js_name = invokeGetSavePath, catch)]
     pub async fn get_save_path_glue(format: String) -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokeListSaveFormats, catch)]
+    pub async fn list_save_formats_glue() -> Result<JsValue, JsValue>;
+    #[wasm_bindgen(js_name = invokePreviewSavePath, catch)]
+    pub async fn preview_save_path_glue(path: String, format: String) -> Result<JsValue, JsValue>;
     #[wasm_bindgen(js_name = invokeLoadSettings, catch)]
     pub async fn load_settings_glue() -> Result<JsValue, JsValue>;
     #[wasm_bindgen(js_name = invokeSaveSettings, catch)]
@@ -93,6 +97,98 @@ pub fn settings(props: &SettingsProps) -> Html {
         })
     };
 
+    // Watch clipboard
+    let on_watch_clipboard_changed = {
+        let settings = settings.clone();
+        let on_update_settings = on_update_settings.clone();
+        Callback::from(move |event: Event| {
+            let value = match event.target_dyn_into::<HtmlInputElement>() {
+                Some(input) => match input.value().as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => settings.watch_clipboard,
+                },
+                None => !settings.watch_clipboard,
+            };
+            let mut new_settings = settings.clone();
+            new_settings.watch_clipboard = value;
+            on_update_settings.emit(new_settings);
+        })
+    };
+
+    // History limit
+    let on_history_limit_change = {
+        let settings = settings.clone();
+        let on_update_settings = on_update_settings.clone();
+        Callback::from(move |event: Event| {
+            let history_limit = match event.target_dyn_into::<HtmlInputElement>() {
+                Some(input) => input
+                    .value()
+                    .parse::<usize>()
+                    .unwrap_or(settings.history_limit),
+                None => settings.history_limit,
+            };
+            let mut new_settings = settings.clone();
+            new_settings.history_limit = history_limit;
+            on_update_settings.emit(new_settings);
+        })
+    };
+
+    // SVG DPI
+    let on_svg_dpi_change = {
+        let settings = settings.clone();
+        let on_update_settings = on_update_settings.clone();
+        Callback::from(move |event: Event| {
+            let svg_dpi = match event.target_dyn_into::<HtmlInputElement>() {
+                Some(input) => input
+                    .value()
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|svg_dpi| *svg_dpi > 0.0)
+                    .unwrap_or(settings.svg_dpi),
+                None => settings.svg_dpi,
+            };
+            let mut new_settings = settings.clone();
+            new_settings.svg_dpi = svg_dpi;
+            on_update_settings.emit(new_settings);
+        })
+    };
+
+    // Auto trim
+    let on_auto_trim_changed = {
+        let settings = settings.clone();
+        let on_update_settings = on_update_settings.clone();
+        Callback::from(move |event: Event| {
+            let value = match event.target_dyn_into::<HtmlInputElement>() {
+                Some(input) => match input.value().as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => settings.auto_trim,
+                },
+                None => !settings.auto_trim,
+            };
+            let mut new_settings = settings.clone();
+            new_settings.auto_trim = value;
+            on_update_settings.emit(new_settings);
+        })
+    };
+    let on_trim_tolerance_change = {
+        let settings = settings.clone();
+        let on_update_settings = on_update_settings.clone();
+        Callback::from(move |event: Event| {
+            let trim_tolerance = match event.target_dyn_into::<HtmlInputElement>() {
+                Some(input) => input
+                    .value()
+                    .parse::<u8>()
+                    .unwrap_or(settings.trim_tolerance),
+                None => settings.trim_tolerance,
+            };
+            let mut new_settings = settings.clone();
+            new_settings.trim_tolerance = trim_tolerance;
+            on_update_settings.emit(new_settings);
+        })
+    };
+
     // Image save path
     let on_get_save_path = {
         let settings = settings.clone();
@@ -102,10 +198,44 @@ pub fn settings(props: &SettingsProps) -> Html {
             get_image_save_path(settings.clone(), on_update_settings.clone());
         })
     };
+    let on_save_path_change = {
+        let settings = settings.clone();
+        let on_update_settings = on_update_settings.clone();
+        Callback::from(move |event: Event| {
+            let save_path = match event.target_dyn_into::<HtmlInputElement>() {
+                Some(input) => input.value(),
+                None => settings.save_path.clone(),
+            };
+            let mut new_settings = settings.clone();
+            new_settings.save_path = save_path;
+            on_update_settings.emit(new_settings);
+        })
+    };
+
+    // Preview of the file name the save path template expands to
+    let save_path_preview_state = use_state_eq(String::new);
+    {
+        let save_path_preview_state = save_path_preview_state.clone();
+        use_effect_with_deps(
+            move |(save_path, save_format)| {
+                preview_save_path(
+                    save_path.clone(),
+                    save_format.to_string(),
+                    save_path_preview_state,
+                );
+                || ()
+            },
+            (settings.save_path.clone(), settings.save_format.clone()),
+        )
+    }
 
     // Image save format
     let update_file_extension = |save_path: String, extension: String| {
         let mut save_path_buf = PathBuf::from(save_path);
+        // Templates using `{format}` for the extension don't need it to be updated
+        if save_path_buf.extension() == Some(OsStr::new("{format}")) {
+            return save_path_buf.to_string_lossy().to_string();
+        }
         save_path_buf.set_extension(extension);
         save_path_buf.to_string_lossy().to_string()
     };
@@ -116,24 +246,13 @@ pub fn settings(props: &SettingsProps) -> Html {
 
         Callback::from(move |event: Event| {
             let save_format = match event.target_dyn_into::<HtmlSelectElement>() {
-                Some(input) => {
-                    // The save format text that we get will be all uppercase, but we need
-                    // that in "sentence case" (e.g. "ICO" -> "Ico") so we can convert it to
-                    // an enum type
-                    global_settings::SaveFormat::from_str({
-                        let mut input_value: String = input.value();
-                        input_value.get_mut(1..).unwrap().make_ascii_lowercase();
-                        input_value.clone().as_str()
-                    })
-                    .unwrap_or_else(|_| settings.save_format.clone())
-                }
+                Some(input) => global_settings::SaveFormat::from_str(input.value().as_str())
+                    .unwrap_or_else(|_| settings.save_format.clone()),
                 None => settings.save_format.clone(),
             };
             let mut new_settings = settings.clone();
-            new_settings.save_path = update_file_extension(
-                new_settings.save_path,
-                save_format.to_string().to_lowercase(),
-            );
+            new_settings.save_path =
+                update_file_extension(new_settings.save_path, save_format.extension().to_string());
             new_settings.save_format = save_format;
             on_update_settings.emit(new_settings);
         })
@@ -148,21 +267,16 @@ pub fn settings(props: &SettingsProps) -> Html {
         let settings = settings.clone();
         let on_update_settings = on_update_settings.clone();
         use_effect_with_deps(
-            move |selection| {
-                let save_format_string;
-                match save_format_select_ref.cast::<HtmlSelectElement>() {
-                    Some(element) => {
-                        save_format_string = selection.to_string().to_uppercase();
-                        element.set_value(save_format_string.as_str())
-                    }
-                    None => save_format_string = "".to_string(),
-                };
+            move |selection: &global_settings::SaveFormat| {
+                if let Some(element) = save_format_select_ref.cast::<HtmlSelectElement>() {
+                    element.set_value(selection.to_string().as_str())
+                }
 
                 // Update save to extension
                 let mut new_settings = settings.clone();
                 new_settings.save_path = update_file_extension(
                     new_settings.save_path,
-                    save_format_string.to_lowercase(),
+                    selection.extension().to_string(),
                 );
                 on_update_settings.emit(new_settings);
                 || {}
@@ -170,6 +284,204 @@ pub fn settings(props: &SettingsProps) -> Html {
             save_format_dependent,
         )
     };
+    // Only the formats the backend can encode are shown
+    let save_formats_state = use_state_eq(Vec::<global_settings::SaveFormat>::new);
+    {
+        let save_formats_state = save_formats_state.clone();
+        use_effect_with_deps(
+            move |_| {
+                list_save_formats(save_formats_state);
+                || ()
+            },
+            (),
+        )
+    }
+
+    // Encoder options
+    let on_jpeg_quality_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(jpeg_quality) = value.parse() {
+                options.jpeg_quality = jpeg_quality;
+            }
+        },
+    );
+    let on_png_compression_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(png_compression) = global_settings::PngCompression::from_str(&value) {
+                options.png_compression = png_compression;
+            }
+        },
+    );
+    let on_png_filter_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(png_filter) = global_settings::PngFilter::from_str(&value) {
+                options.png_filter = png_filter;
+            }
+        },
+    );
+    let on_tiff_compression_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(tiff_compression) = global_settings::TiffCompression::from_str(&value) {
+                options.tiff_compression = tiff_compression;
+            }
+        },
+    );
+    let on_gif_speed_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(gif_speed) = value.parse() {
+                options.gif_speed = gif_speed;
+            }
+        },
+    );
+    let on_ico_shrink_to_fit_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| options.ico_shrink_to_fit = value == "true",
+    );
+    let on_ico_multi_size_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| options.ico_multi_size = value == "true",
+    );
+    let on_icon_filter_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(icon_filter) = global_settings::ResizeFilter::from_str(&value) {
+                options.icon_filter = icon_filter;
+            }
+        },
+    );
+    let icon_options = html! {
+        <>
+            <p class="text-sm">{ "Sizes" }</p>
+            <div class="flex flex-wrap gap-2">
+                { icon_size_checkboxes(&settings, &on_update_settings) }
+            </div>
+            <p class="text-sm">{ "Resampling filter" }</p>
+            <select class="text-sm text-black" onchange={ on_icon_filter_change }>
+                { enum_options(&settings.encoder_options.icon_filter) }
+            </select>
+        </>
+    };
+    let on_transparency_background_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(transparency_background) =
+                global_settings::TransparencyBackground::from_str(&value)
+            {
+                options.transparency_background = transparency_background;
+            }
+        },
+    );
+    let on_transparency_color_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| options.transparency_color = value,
+    );
+    let on_resize_mode_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(mode) = global_settings::ResizeMode::from_str(&value) {
+                options.resize.mode = mode;
+            }
+        },
+    );
+    let on_resize_width_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(width) = value.parse() {
+                options.resize.width = width;
+            }
+        },
+    );
+    let on_resize_height_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(height) = value.parse() {
+                options.resize.height = height;
+            }
+        },
+    );
+    let on_resize_percentage_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(percentage) = value.parse() {
+                options.resize.percentage = percentage;
+            }
+        },
+    );
+    let on_resize_max_edge_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(max_edge) = value.parse() {
+                options.resize.max_edge = max_edge;
+            }
+        },
+    );
+    let on_resize_filter_change = encoder_option_callback(
+        settings.clone(),
+        on_update_settings.clone(),
+        |options, value| {
+            if let Ok(filter) = global_settings::ResizeFilter::from_str(&value) {
+                options.resize.filter = filter;
+            }
+        },
+    );
+    let encoder_options = &settings.encoder_options;
+    let resize = &encoder_options.resize;
+    let resize_box_inputs = html! {
+        <div class="flex gap-1 items-center text-sm">
+            <input
+                onchange={ on_resize_width_change }
+                class="w-20 text-black"
+                type="number"
+                min=0
+                value={ resize.width.to_string() }
+            />
+            { "x" }
+            <input
+                onchange={ on_resize_height_change }
+                class="w-20 text-black"
+                type="number"
+                min=0
+                value={ resize.height.to_string() }
+            />
+        </div>
+    };
+
+    // Collision policy
+    let on_collision_policy_change = {
+        let settings = settings.clone();
+        let on_update_settings = on_update_settings.clone();
+        Callback::from(move |event: Event| {
+            let collision_policy = match event.target_dyn_into::<HtmlSelectElement>() {
+                Some(input) => global_settings::CollisionPolicy::from_str(input.value().as_str())
+                    .unwrap_or_else(|_| settings.collision_policy.clone()),
+                None => settings.collision_policy.clone(),
+            };
+            let mut new_settings = settings.clone();
+            new_settings.collision_policy = collision_policy;
+            on_update_settings.emit(new_settings);
+        })
+    };
+
     // Same thing with the zoom by slider
     let zoom_by_slider_ref = use_node_ref();
     {
@@ -219,11 +531,23 @@ pub fn settings(props: &SettingsProps) -> Html {
         )
     }
 
-    // Save
+    // Save. Settings the backend can't use are shown next to them until they change
+    let setting_errors_state = use_state(Vec::<error::SettingError>::new);
+    {
+        let setting_errors_state = setting_errors_state.clone();
+        use_effect_with_deps(
+            move |_| {
+                setting_errors_state.set(Vec::new());
+                || ()
+            },
+            settings.clone(),
+        )
+    }
     let on_save = {
         let settings = settings.clone();
+        let setting_errors_state = setting_errors_state.clone();
         Callback::from(move |_| {
-            save_settings(settings.clone()).unwrap();
+            save_settings(settings.clone(), setting_errors_state.clone()).unwrap();
         })
     };
 
@@ -278,9 +602,89 @@ pub fn settings(props: &SettingsProps) -> Html {
                     />
                     { " Disabled" }
                 </label>
+                // Watch clipboard
+                <UnderlineText>{ "Watch clipboard" }</UnderlineText>
+                <label>
+                    <input
+                        onchange={ on_watch_clipboard_changed.clone() }
+                        type="radio"
+                        checked={ settings.watch_clipboard }
+                        value="true"
+                    />
+                    { " Enabled" }
+                    <br />
+                    <input
+                        onchange={ on_watch_clipboard_changed.clone() }
+                        type="radio"
+                        checked={ !settings.watch_clipboard }
+                        value="false"
+                    />
+                    { " Disabled" }
+                </label>
+                // History limit
+                <UnderlineText>{ "History size" }</UnderlineText>
+                <input
+                    onchange={ on_history_limit_change }
+                    class="w-20 text-sm text-black"
+                    type="number"
+                    min=0
+                    max=1000
+                    value={ settings.history_limit.to_string() }
+                />
+                <p class="text-xs">{ "0 turns the history off. Images are kept as captured, before any redactions" }</p>
+                // SVG DPI
+                <UnderlineText>{ "SVG DPI" }</UnderlineText>
+                <input
+                    onchange={ on_svg_dpi_change }
+                    class="w-20 text-sm text-black"
+                    type="number"
+                    min=1
+                    max=2400
+                    value={ settings.svg_dpi.to_string() }
+                />
+                <p class="text-xs">{ "Copied SVGs are drawn at this DPI, where 96 is their actual size" }</p>
+                // Auto trim
+                <UnderlineText>{ "Trim borders when pasting" }</UnderlineText>
+                <label>
+                    <input
+                        onchange={ on_auto_trim_changed.clone() }
+                        type="radio"
+                        checked={ settings.auto_trim }
+                        value="true"
+                    />
+                    { " Enabled" }
+                    <br />
+                    <input
+                        onchange={ on_auto_trim_changed.clone() }
+                        type="radio"
+                        checked={ !settings.auto_trim }
+                        value="false"
+                    />
+                    { " Disabled" }
+                </label>
+                <p class="text-sm">{ "Trim tolerance" }</p>
+                <input
+                    onchange={ on_trim_tolerance_change }
+                    class="w-20 text-sm text-black"
+                    type="number"
+                    min=0
+                    max=255
+                    value={ settings.trim_tolerance.to_string() }
+                />
+                <p class="text-xs">{ "How far from the border's color a pixel can be and still be trimmed" }</p>
                 // Save path
                 <UnderlineText>{ "Save to" }</UnderlineText>
-                <p>{ settings.save_path }</p>
+                <input
+                    onchange={ on_save_path_change }
+                    class="w-full text-sm text-black"
+                    type="text"
+                    value={ settings.save_path.clone() }
+                />
+                <p class="text-xs">
+                    { "Placeholders: {date} {time} {counter} {width} {height} {hash8} {hash} {format}" }
+                </p>
+                <p class="text-sm">{ format!("Next file: {}", *save_path_preview_state) }</p>
+                { setting_errors(&setting_errors_state, "save_path") }
                 <button
                     onclick={ on_get_save_path }
                     class="px-2 py-1 w-max text-sm text-black bg-gray-300 rounded-md hover:bg-gray-200"
@@ -295,15 +699,195 @@ pub fn settings(props: &SettingsProps) -> Html {
                     ref={ save_format_select_ref }
                 >
                     {
-                        global_settings::SaveFormat::iter().map(|format| {
-                            let string_format = format.to_string().to_uppercase();
+                        save_formats_state.iter().map(|format| {
+                            let string_format = format.to_string();
                             html! {
                                 <option
                                     key={ string_format.clone() }
                                     value={ string_format.clone() }
-                                    selected={ format == settings.save_format }
+                                    selected={ format == &settings.save_format }
                                 >
-                                    { string_format }
+                                    { string_format.to_uppercase() }
+                                </option>
+                            }
+                        }).collect::<Html>()
+                    }
+                </select>
+                { setting_errors(&setting_errors_state, "save_format") }
+                // Encoder options, which only show for the chosen format
+                {
+                    match settings.save_format {
+                        global_settings::SaveFormat::Jpg => html! {
+                            <>
+                                <p class="text-sm">
+                                    { format!("Quality: {}", encoder_options.jpeg_quality) }
+                                </p>
+                                <input
+                                    onchange={ on_jpeg_quality_change }
+                                    type="range"
+                                    min=1
+                                    max=100
+                                    value={ encoder_options.jpeg_quality.to_string() }
+                                />
+                            </>
+                        },
+                        global_settings::SaveFormat::Png => html! {
+                            <>
+                                <p class="text-sm">{ "Compression" }</p>
+                                <select class="text-sm text-black" onchange={ on_png_compression_change }>
+                                    { enum_options(&encoder_options.png_compression) }
+                                </select>
+                                <p class="text-sm">{ "Filter" }</p>
+                                <select class="text-sm text-black" onchange={ on_png_filter_change }>
+                                    { enum_options(&encoder_options.png_filter) }
+                                </select>
+                            </>
+                        },
+                        global_settings::SaveFormat::Tiff => html! {
+                            <>
+                                <p class="text-sm">{ "Compression" }</p>
+                                <select class="text-sm text-black" onchange={ on_tiff_compression_change }>
+                                    { enum_options(&encoder_options.tiff_compression) }
+                                </select>
+                            </>
+                        },
+                        global_settings::SaveFormat::Gif => html! {
+                            <>
+                                <p class="text-sm">
+                                    { format!("Speed: {} (lower is better quality)", encoder_options.gif_speed) }
+                                </p>
+                                <input
+                                    onchange={ on_gif_speed_change }
+                                    type="range"
+                                    min=1
+                                    max=30
+                                    value={ encoder_options.gif_speed.to_string() }
+                                />
+                            </>
+                        },
+                        global_settings::SaveFormat::Ico => html! {
+                            <>
+                                <label class="text-sm">
+                                    <input
+                                        onchange={ on_ico_multi_size_change }
+                                        type="checkbox"
+                                        checked={ encoder_options.ico_multi_size }
+                                    />
+                                    { " Pack multiple sizes" }
+                                </label>
+                                {
+                                    match encoder_options.ico_multi_size {
+                                        true => icon_options,
+                                        false => html! {
+                                            <label class="text-sm">
+                                                <input
+                                                    onchange={ on_ico_shrink_to_fit_change }
+                                                    type="checkbox"
+                                                    checked={ encoder_options.ico_shrink_to_fit }
+                                                />
+                                                { " Shrink to fit 256x256" }
+                                            </label>
+                                        },
+                                    }
+                                }
+                            </>
+                        },
+                        // ICNSs can't hold 24x24 or 48x48 icons, so those sizes are skipped
+                        global_settings::SaveFormat::Icns => icon_options,
+                        _ => html! {},
+                    }
+                }
+                // Transparent pixels are flattened for formats without transparency
+                {
+                    if settings.save_format.supports_alpha() {
+                        html! {}
+                    } else {
+                        html! {
+                            <>
+                                <p class="text-sm">{ "Background for transparency" }</p>
+                                <select class="text-sm text-black" onchange={ on_transparency_background_change }>
+                                    { enum_options(&encoder_options.transparency_background) }
+                                </select>
+                                {
+                                    match encoder_options.transparency_background {
+                                        global_settings::TransparencyBackground::Color => html! {
+                                            <input
+                                                onchange={ on_transparency_color_change }
+                                                type="color"
+                                                value={ encoder_options.transparency_color.clone() }
+                                            />
+                                        },
+                                        _ => html! {},
+                                    }
+                                }
+                            </>
+                        }
+                    }
+                }
+                // Resize
+                <UnderlineText>{ "Resize" }</UnderlineText>
+                <select class="text-sm text-black" onchange={ on_resize_mode_change }>
+                    { enum_options(&resize.mode) }
+                </select>
+                {
+                    match resize.mode {
+                        global_settings::ResizeMode::None => html! {},
+                        global_settings::ResizeMode::Exact => html! {
+                            <>
+                                { resize_box_inputs }
+                                <p class="text-xs">{ "A size of 0 keeps the aspect ratio" }</p>
+                            </>
+                        },
+                        global_settings::ResizeMode::Fit => resize_box_inputs,
+                        global_settings::ResizeMode::Percentage => html! {
+                            <div class="flex gap-1 items-center text-sm">
+                                <input
+                                    onchange={ on_resize_percentage_change }
+                                    class="w-20 text-black"
+                                    type="number"
+                                    min=1
+                                    value={ resize.percentage.to_string() }
+                                />
+                                { "%" }
+                            </div>
+                        },
+                        global_settings::ResizeMode::MaxEdge => html! {
+                            <input
+                                onchange={ on_resize_max_edge_change }
+                                class="w-20 text-sm text-black"
+                                type="number"
+                                min=1
+                                value={ resize.max_edge.to_string() }
+                            />
+                        },
+                    }
+                }
+                {
+                    match resize.mode {
+                        global_settings::ResizeMode::None => html! {},
+                        _ => html! {
+                            <>
+                                <p class="text-sm">{ "Resampling filter" }</p>
+                                <select class="text-sm text-black" onchange={ on_resize_filter_change }>
+                                    { enum_options(&resize.filter) }
+                                </select>
+                            </>
+                        },
+                    }
+                }
+                // Collision policy
+                <UnderlineText>{ "If the file exists" }</UnderlineText>
+                <select class="text-sm text-black" onchange={ on_collision_policy_change }>
+                    {
+                        global_settings::CollisionPolicy::iter().map(|policy| {
+                            let string_policy = policy.to_string();
+                            html! {
+                                <option
+                                    key={ string_policy.clone() }
+                                    value={ string_policy.clone() }
+                                    selected={ policy == settings.collision_policy }
+                                >
+                                    { string_policy }
                                 </option>
                             }
                         }).collect::<Html>()
@@ -312,7 +896,14 @@ pub fn settings(props: &SettingsProps) -> Html {
                 // Zoom by
                 <UnderlineText>{ "Zoom by" }</UnderlineText>
                 <p>{ format!("{}%", settings.zoom_by) }</p>
-                <input oninput={ zoom_by_range } ref={ zoom_by_slider_ref } type="range" min=1 max=100 />
+                <input
+                    oninput={ zoom_by_range }
+                    ref={ zoom_by_slider_ref }
+                    type="range"
+                    min={ global_settings::MIN_ZOOM_BY.to_string() }
+                    max={ global_settings::MAX_ZOOM_BY.to_string() }
+                />
+                { setting_errors(&setting_errors_state, "zoom_by") }
             </div>
 
             // Controls
@@ -334,6 +925,107 @@ pub fn settings(props: &SettingsProps) -> Html {
     }
 }
 
+/// Show why the backend couldn't use a setting when the settings were saved
+fn setting_errors(errors: &[error::SettingError], field: &str) -> Html {
+    errors
+        .iter()
+        .filter(|error| error.field == field)
+        .map(|error| html! { <p class="text-xs text-red-300">{ error.message.clone() }</p> })
+        .collect::<Html>()
+}
+
+/// Make the options of a select tag from every variant of an enum
+fn enum_options<T: IntoEnumIterator + Display + PartialEq>(selected: &T) -> Html {
+    T::iter()
+        .map(|variant| {
+            let string_variant = variant.to_string();
+            html! {
+                <option
+                    key={ string_variant.clone() }
+                    value={ string_variant.clone() }
+                    selected={ &variant == selected }
+                >
+                    { string_variant }
+                </option>
+            }
+        })
+        .collect::<Html>()
+}
+
+/// Make a callback that updates an encoder option with the value of the input or select tag
+/// that changed. Checkboxes give "true" or "false"
+fn encoder_option_callback(
+    settings: global_settings::Settings,
+    on_update_settings: Callback<global_settings::Settings>,
+    update_option: fn(&mut global_settings::EncoderOptions, String),
+) -> Callback<Event> {
+    Callback::from(move |event: Event| {
+        let value = match event.target_dyn_into::<HtmlInputElement>() {
+            Some(input) if input.type_() == "checkbox" => input.checked().to_string(),
+            Some(input) => input.value(),
+            None => match event.target_dyn_into::<HtmlSelectElement>() {
+                Some(select) => select.value(),
+                None => return,
+            },
+        };
+        let mut new_settings = settings.clone();
+        update_option(&mut new_settings.encoder_options, value);
+        on_update_settings.emit(new_settings);
+    })
+}
+
+/// Make a checkbox for each icon size, which adds or removes it from the icon sizes
+fn icon_size_checkboxes(
+    settings: &global_settings::Settings,
+    on_update_settings: &Callback<global_settings::Settings>,
+) -> Html {
+    global_settings::ICON_SIZES
+        .iter()
+        .map(|&size| {
+            let on_change = {
+                let settings = settings.clone();
+                let on_update_settings = on_update_settings.clone();
+                Callback::from(move |event: Event| {
+                    let checked = match event.target_dyn_into::<HtmlInputElement>() {
+                        Some(input) => input.checked(),
+                        None => return,
+                    };
+                    let mut new_settings = settings.clone();
+                    let icon_sizes = &mut new_settings.encoder_options.icon_sizes;
+                    icon_sizes.retain(|&icon_size| icon_size != size);
+                    if checked {
+                        icon_sizes.push(size);
+                        icon_sizes.sort_unstable();
+                    }
+                    on_update_settings.emit(new_settings);
+                })
+            };
+            html! {
+                <label key={ size } class="text-sm">
+                    <input
+                        onchange={ on_change }
+                        type="checkbox"
+                        checked={ settings.encoder_options.icon_sizes.contains(&size) }
+                    />
+                    { format!(" {}", size) }
+                </label>
+            }
+        })
+        .collect::<Html>()
+}
+
+/// Get the save formats the backend can encode using JavaScript glue
+fn list_save_formats(save_formats_state: UseStateHandle<Vec<global_settings::SaveFormat>>) {
+    spawn_local(async move {
+        match list_save_formats_glue().await {
+            Ok(save_formats) => {
+                save_formats_state.set(save_formats.into_serde().unwrap_or_default())
+            }
+            Err(e) => alert_error(&backend_error(e)),
+        }
+    })
+}
+
 /// Get the save image location using JavaScript glue
 fn get_image_save_path(
     settings: global_settings::Settings,
@@ -349,45 +1041,62 @@ fn get_image_save_path(
                 new_settings.save_path = save_path.as_string().unwrap_or(new_settings.save_path);
                 on_update_settings.emit(new_settings);
             }
-            Err(e) => {
-                window()
-                    .unwrap()
-                    .alert_with_message(&e.as_string().unwrap_or_else(|| {
-                        "Failed to get the save path, but no reason was provided".to_string()
-                    }))
-                    .unwrap();
-            }
+            // Canceling the dialog isn't shown
+            Err(e) => alert_error(&backend_error(e)),
         }
     })
 }
 
+/// Preview the expanded save path template using JavaScript glue
+fn preview_save_path(
+    save_path: String,
+    save_format: String,
+    save_path_preview_state: UseStateHandle<String>,
+) {
+    spawn_local(async move {
+        // Failing to preview isn't worth an alert, so just show nothing
+        let preview = match preview_save_path_glue(save_path, save_format).await {
+            Ok(preview) => preview.as_string().unwrap_or_default(),
+            Err(_) => "".to_string(),
+        };
+        save_path_preview_state.set(preview);
+    })
+}
+
 /// Load settings from JavaScript glue
 fn load_settings(on_update_settings: Callback<global_settings::Settings>) {
     spawn_local(async move {
+        // There's no settings file until the settings are saved, so the defaults are kept
         let settings_text = match load_settings_glue().await {
-            Ok(settings_text) => settings_text.as_string().unwrap(),
-            Err(e) => {
-                let error_message = &e.as_string().unwrap();
-                let error_message = error_message.as_str();
-
-                // If it was a FileNotFound error, it'll return "NotFound"
-                // Don't display those
-                match error_message {
-                    "NotFound" => (),
-                    _ => window().unwrap().alert_with_message(error_message).unwrap(),
-                };
-                return;
-            }
+            Ok(settings_text) => match settings_text.as_string() {
+                Some(settings_text) => settings_text,
+                None => return,
+            },
+            Err(e) => return alert_error(&backend_error(e)),
         };
         match global_settings::Settings::parse(settings_text) {
             Ok(new_settings) => on_update_settings.emit(new_settings),
-            Err(e) => window().unwrap().alert_with_message(e.as_str()).unwrap(),
+            Err(error::Error::SettingsParse(message)) => window()
+                .unwrap()
+                .alert_with_message(
+                    format!(
+                        "The settings file is broken, so the default settings are used: {}",
+                        message
+                    )
+                    .as_str(),
+                )
+                .unwrap(),
+            Err(error) => alert_error(&error),
         }
     })
 }
 
-/// Save settings using JavaScript glue
-fn save_settings(settings: global_settings::Settings) -> Result<(), ()> {
+/// Save settings using JavaScript glue. If the backend can't use some of them, why is put in the
+/// errors state instead of alerted
+fn save_settings(
+    settings: global_settings::Settings,
+    setting_errors_state: UseStateHandle<Vec<error::SettingError>>,
+) -> Result<(), ()> {
     // Serialize
     let serialized_data = match serde_json::to_string(&settings) {
         Ok(serialized_data) => serialized_data,
@@ -402,16 +1111,23 @@ fn save_settings(settings: global_settings::Settings) -> Result<(), ()> {
 
     spawn_local(async move {
         match save_settings_glue(serialized_data).await {
-            Ok(settings_path) => window()
-                .unwrap()
-                .alert_with_message(
-                    format!("Saved settings to {}", settings_path.as_string().unwrap()).as_str(),
-                )
-                .unwrap(),
-            Err(e) => window()
-                .unwrap()
-                .alert_with_message(&e.as_string().unwrap())
-                .unwrap(),
+            Ok(settings_path) => {
+                setting_errors_state.set(Vec::new());
+                window()
+                    .unwrap()
+                    .alert_with_message(
+                        format!(
+                            "Saved settings to {}",
+                            settings_path.as_string().unwrap_or_default()
+                        )
+                        .as_str(),
+                    )
+                    .unwrap()
+            }
+            Err(e) => match backend_error(e) {
+                error::Error::InvalidSettings(errors) => setting_errors_state.set(errors),
+                error => alert_error(&error),
+            },
         }
     });
 
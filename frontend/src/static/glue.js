@@ -6,20 +6,70 @@
 /***** Setup *****/
 /* Imports */
 const invoke = window.__TAURI__.invoke;
+const listen = window.__TAURI__.event.listen;
+const convertFileSrc = window.__TAURI__.tauri.convertFileSrc;
 
 /***** Bridge functions *****/
 export async function invokeReadClipboard() {
 	return invoke("read_clipboard", {});
 }
 
-export async function invokeSaveImage(path, format) {
-	return invoke("save_image", {path: path, format: format});
+export async function invokeSaveImage(path, format, confirmedPath) {
+	return invoke("save_image", {path: path, format: format, confirmedPath: confirmedPath ?? null});
+}
+
+export async function invokeOpenImage(path) {
+	return invoke("open_image", {path: path ?? null});
+}
+
+export async function invokeGetImageInfo() {
+	return invoke("get_image_info", {});
+}
+
+export async function invokeEditImage(edit) {
+	return invoke("edit_image", {edit: edit});
+}
+
+export async function invokeUndoEdit() {
+	return invoke("undo_edit", {});
+}
+
+export async function invokeRedoEdit() {
+	return invoke("redo_edit", {});
+}
+
+export async function invokeResetEdits() {
+	return invoke("reset_edits", {});
+}
+
+export async function invokeGetAdjustments() {
+	return invoke("get_adjustments", {});
+}
+
+export async function invokeSetAdjustments(adjustments) {
+	return invoke("set_adjustments", {adjustments: adjustments});
+}
+
+export async function invokeSetAnnotations(annotations) {
+	return invoke("set_annotations", {annotations: annotations});
+}
+
+export async function invokeGetCacheStats() {
+	return invoke("get_cache_stats", {});
+}
+
+export async function invokeListSaveFormats() {
+	return invoke("list_save_formats", {});
 }
 
 export async function invokeGetSavePath(format) {
 	return invoke("get_save_path", {format: format});
 }
 
+export async function invokePreviewSavePath(path, format) {
+	return invoke("preview_save_path", {path: path, format: format});
+}
+
 export async function invokeLoadSettings() {
 	return invoke("load_settings", {});
 }
@@ -27,3 +77,54 @@ export async function invokeLoadSettings() {
 export async function invokeSaveSettings(settings) {
 	return invoke("save_settings", {settings: settings});
 }
+
+export async function invokeSyncSettings(settings) {
+	return invoke("sync_settings", {settings: settings});
+}
+
+export async function invokeListHistory() {
+	return invoke("list_history", {});
+}
+
+export async function invokePreviewHistoryEntry(id) {
+	return invoke("preview_history_entry", {id: id});
+}
+
+export async function invokeRestoreHistoryEntry(id) {
+	return invoke("restore_history_entry", {id: id});
+}
+
+export async function invokeDeleteHistoryEntry(id) {
+	return invoke("delete_history_entry", {id: id});
+}
+
+export async function invokeSaveHistoryEntry(id, path, format, confirmedPath) {
+	return invoke("save_history_entry", {
+		id: id,
+		path: path,
+		format: format,
+		confirmedPath: confirmedPath ?? null,
+	});
+}
+
+export async function invokeCancelSaves() {
+	return invoke("cancel_saves", {});
+}
+
+export async function invokeWriteClipboard(historyId, format) {
+	return invoke("write_clipboard", {historyId: historyId ?? null, format: format ?? null});
+}
+
+/***** Preview protocol *****/
+// The URL of the current image's preview, which differs between platforms (e.g. Windows uses
+// https://clip.localhost/current), with the revision so the webview doesn't reuse an old one
+export function previewUrl(revision) {
+	return `${convertFileSrc("current", "clip")}?rev=${revision}`;
+}
+
+/***** Event listeners *****/
+// Listen to an event, calling the callback with its payload. Returns a function that stops
+// listening when called
+export async function listenEvent(name, callback) {
+	return listen(name, (event) => callback(event.payload));
+}
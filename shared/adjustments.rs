@@ -0,0 +1,82 @@
+/*
+ * Color adjustments (brightness, contrast, gamma...) applied to images
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{error::Error, settings::Adjustments};
+use image::{imageops, DynamicImage, RgbaImage};
+
+/***** Constants *****/
+/// The most brightness can be added or removed, which turns every pixel white or black
+pub const MAX_BRIGHTNESS: i32 = 255;
+/// The most contrast can be added or removed, as a percentage
+pub const MAX_CONTRAST: f32 = 100.0;
+/// The lowest and highest gamma, past which the image is almost a solid color
+pub const MIN_GAMMA: f32 = 0.1;
+pub const MAX_GAMMA: f32 = 10.0;
+
+/***** Validating *****/
+/// Check that adjustments are in range, e.g. that the gamma isn't 0
+pub fn validate_adjustments(adjustments: &Adjustments) -> Result<(), Error> {
+    if !(-MAX_BRIGHTNESS..=MAX_BRIGHTNESS).contains(&adjustments.brightness) {
+        return Err(Error::InvalidEdit(format!(
+            "The brightness must be from -{} to {}",
+            MAX_BRIGHTNESS, MAX_BRIGHTNESS
+        )));
+    }
+    if !(-MAX_CONTRAST..=MAX_CONTRAST).contains(&adjustments.contrast) {
+        return Err(Error::InvalidEdit(format!(
+            "The contrast must be from -{} to {}",
+            MAX_CONTRAST, MAX_CONTRAST
+        )));
+    }
+    if !(MIN_GAMMA..=MAX_GAMMA).contains(&adjustments.gamma) {
+        return Err(Error::InvalidEdit(format!(
+            "The gamma must be from {} to {}",
+            MIN_GAMMA, MAX_GAMMA
+        )));
+    }
+    Ok(())
+}
+
+/***** Adjusting *****/
+/// Apply color adjustments to a copy of an image. Transparency is kept as it is, and the copy is
+/// 8 bit RGBA like clipboard images
+pub fn apply_adjustments(image: &DynamicImage, adjustments: &Adjustments) -> DynamicImage {
+    let mut image_buf = match adjustments.grayscale {
+        true => DynamicImage::ImageLumaA8(imageops::grayscale_alpha(image)).to_rgba8(),
+        false => image.to_rgba8(),
+    };
+    if adjustments.hue_rotate % 360 != 0 {
+        image_buf = imageops::huerotate(&image_buf, adjustments.hue_rotate);
+    }
+    if adjustments.brightness != 0 {
+        image_buf = imageops::brighten(&image_buf, adjustments.brightness);
+    }
+    if adjustments.contrast != 0.0 {
+        image_buf = imageops::contrast(&image_buf, adjustments.contrast);
+    }
+    if adjustments.gamma != 1.0 {
+        apply_gamma(&mut image_buf, adjustments.gamma);
+    }
+    if adjustments.invert {
+        imageops::invert(&mut image_buf);
+    }
+    DynamicImage::ImageRgba8(image_buf)
+}
+
+/// Apply a gamma curve to the color channels of an image, where gammas above 1 brighten it
+fn apply_gamma(image_buf: &mut RgbaImage, gamma: f32) {
+    // There are only 256 values a channel can have, so they're looked up instead of calculated
+    let mut curve = [0_u8; 256];
+    for (value, curved) in curve.iter_mut().enumerate() {
+        *curved = ((value as f32 / 255.0).powf(1.0 / gamma) * 255.0).round() as u8;
+    }
+    for pixel in image_buf.pixels_mut() {
+        for channel in pixel.0.iter_mut().take(3) {
+            *channel = curve[*channel as usize];
+        }
+    }
+}
@@ -0,0 +1,296 @@
+/*
+ * Drawing annotations (arrows, boxes, text...) onto images
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{
+    edits::parse_hex_rgba,
+    error::Error,
+    payloads::{Annotation, Shape, HIGHLIGHT_OPACITY},
+};
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use image::{DynamicImage, RgbaImage};
+use tiny_skia::{
+    ColorU8, FillRule, LineCap, LineJoin, Paint, PathBuilder, Pixmap, PremultipliedColorU8, Rect,
+    Stroke, Transform,
+};
+
+/***** Constants *****/
+/// The font text annotations are drawn in, which the frontend shows them in too
+pub const ANNOTATION_FONT: &[u8] = include_bytes!("fonts/DejaVuSans.ttf");
+/// The largest line width or text size, so a typo can't cover the whole image
+pub const MAX_ANNOTATION_SIZE: f32 = 500.0;
+
+/***** Validating *****/
+/// Check that an annotation can be drawn, e.g. that its color is valid
+pub fn validate_annotation(annotation: &Annotation) -> Result<(), Error> {
+    if parse_hex_rgba(&annotation.color).is_none() {
+        return Err(Error::InvalidAnnotation(format!(
+            "{} isn't a hex color",
+            annotation.color
+        )));
+    }
+    if !(annotation.size > 0.0 && annotation.size <= MAX_ANNOTATION_SIZE) {
+        return Err(Error::InvalidAnnotation(format!(
+            "The size must be more than 0 and at most {}",
+            MAX_ANNOTATION_SIZE
+        )));
+    }
+
+    let coordinates = match &annotation.shape {
+        Shape::Arrow { from, to } => vec![from[0], from[1], to[0], to[1]],
+        Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+        }
+        | Shape::Ellipse {
+            x,
+            y,
+            width,
+            height,
+        }
+        | Shape::Highlight {
+            x,
+            y,
+            width,
+            height,
+        } => vec![*x, *y, *width, *height],
+        Shape::Freehand { points } => match points.is_empty() {
+            true => return Err(Error::InvalidAnnotation("The line is empty".to_string())),
+            false => points.iter().flatten().copied().collect(),
+        },
+        Shape::Text { x, y, text } => match text.trim().is_empty() {
+            true => return Err(Error::InvalidAnnotation("The text is empty".to_string())),
+            false => vec![*x, *y],
+        },
+    };
+    match coordinates.iter().all(|coordinate| coordinate.is_finite()) {
+        true => Ok(()),
+        false => Err(Error::InvalidAnnotation(
+            "The shape has a position that isn't a number".to_string(),
+        )),
+    }
+}
+
+/***** Drawing *****/
+/// Draw annotations onto an image in order. Annotations are validated before they're kept,
+/// so ones that can't be drawn (e.g. a zero sized box) are skipped
+pub fn draw_annotations(
+    image: &DynamicImage,
+    annotations: &[Annotation],
+) -> Result<DynamicImage, Error> {
+    let image_buf = image.to_rgba8();
+    let mut pixmap = match Pixmap::new(image_buf.width(), image_buf.height()) {
+        Some(pixmap) => pixmap,
+        None => return Err(Error::NoImageInClipboard),
+    };
+    // Pixmaps have premultiplied alpha, unlike images
+    for (pixel, image_pixel) in pixmap.pixels_mut().iter_mut().zip(image_buf.pixels()) {
+        let [red, green, blue, alpha] = image_pixel.0;
+        *pixel = ColorU8::from_rgba(red, green, blue, alpha).premultiply();
+    }
+    let font = match FontRef::try_from_slice(ANNOTATION_FONT) {
+        Ok(font) => font,
+        Err(e) => {
+            return Err(Error::Other(format!(
+                "The annotation font is broken: {}",
+                e
+            )))
+        }
+    };
+
+    for annotation in annotations {
+        let color = parse_hex_rgba(&annotation.color).unwrap_or([0, 0, 0, u8::MAX]);
+        draw_annotation(&mut pixmap, &font, annotation, color);
+    }
+
+    let raw = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    match RgbaImage::from_raw(image_buf.width(), image_buf.height(), raw) {
+        Some(annotated) => Ok(DynamicImage::ImageRgba8(annotated)),
+        None => Err(Error::EncodeFailed(
+            "The annotated pixels don't match the image's size".to_string(),
+        )),
+    }
+}
+
+/// Draw an annotation onto a pixmap in a color
+fn draw_annotation(pixmap: &mut Pixmap, font: &FontRef, annotation: &Annotation, color: [u8; 4]) {
+    let paint = |opacity: f32| {
+        let mut paint = Paint::default();
+        let alpha = (color[3] as f32 * opacity).round() as u8;
+        paint.set_color_rgba8(color[0], color[1], color[2], alpha);
+        paint.anti_alias = true;
+        paint
+    };
+    let stroke = Stroke {
+        width: annotation.size,
+        line_cap: LineCap::Round,
+        line_join: LineJoin::Round,
+        ..Stroke::default()
+    };
+    let identity = Transform::identity();
+
+    match &annotation.shape {
+        Shape::Arrow { from, to } => {
+            let (line_end, head) = match Shape::arrow_geometry(*from, *to, annotation.size) {
+                Some(geometry) => geometry,
+                None => return,
+            };
+            let mut line = PathBuilder::new();
+            line.move_to(from[0], from[1]);
+            line.line_to(line_end[0], line_end[1]);
+            if let Some(path) = line.finish() {
+                pixmap.stroke_path(&path, &paint(1.0), &stroke, identity, None);
+            }
+            let mut head_path = PathBuilder::new();
+            head_path.move_to(head[0][0], head[0][1]);
+            head_path.line_to(head[1][0], head[1][1]);
+            head_path.line_to(head[2][0], head[2][1]);
+            head_path.close();
+            if let Some(path) = head_path.finish() {
+                pixmap.fill_path(&path, &paint(1.0), FillRule::Winding, identity, None);
+            }
+        }
+        Shape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            if let Some(rect) = normalized_rect(*x, *y, *width, *height) {
+                let path = PathBuilder::from_rect(rect);
+                pixmap.stroke_path(&path, &paint(1.0), &stroke, identity, None);
+            }
+        }
+        Shape::Ellipse {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            if let Some(path) =
+                normalized_rect(*x, *y, *width, *height).and_then(PathBuilder::from_oval)
+            {
+                pixmap.stroke_path(&path, &paint(1.0), &stroke, identity, None);
+            }
+        }
+        Shape::Freehand { points } => {
+            let mut line = PathBuilder::new();
+            for (index, [point_x, point_y]) in points.iter().enumerate() {
+                match index {
+                    0 => line.move_to(*point_x, *point_y),
+                    _ => line.line_to(*point_x, *point_y),
+                }
+            }
+            match (points.len(), line.finish()) {
+                // A click without moving is a dot
+                (1, _) => {
+                    let [point_x, point_y] = points[0];
+                    if let Some(path) =
+                        PathBuilder::from_circle(point_x, point_y, annotation.size / 2.0)
+                    {
+                        pixmap.fill_path(&path, &paint(1.0), FillRule::Winding, identity, None);
+                    }
+                }
+                (_, Some(path)) => {
+                    pixmap.stroke_path(&path, &paint(1.0), &stroke, identity, None);
+                }
+                (_, None) => (),
+            }
+        }
+        Shape::Text { x, y, text } => {
+            draw_text(pixmap, font, [*x, *y], text, annotation.size, color)
+        }
+        Shape::Highlight {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            if let Some(rect) = normalized_rect(*x, *y, *width, *height) {
+                pixmap.fill_rect(rect, &paint(HIGHLIGHT_OPACITY), identity, None);
+            }
+        }
+    }
+}
+
+/// Draw a line of text starting on its baseline at a position, where the size is the em size
+/// in pixels like CSS's `font-size`
+fn draw_text(
+    pixmap: &mut Pixmap,
+    font: &FontRef,
+    position: [f32; 2],
+    text: &str,
+    size: f32,
+    color: [u8; 4],
+) {
+    // ab_glyph scales by the height from descent to ascent, not by the em size
+    let units_per_em = font.units_per_em().unwrap_or(1000.0);
+    let scale = PxScale::from(size * font.height_unscaled() / units_per_em);
+    let scaled_font = font.as_scaled(scale);
+    let (width, height) = (pixmap.width() as i32, pixmap.height() as i32);
+
+    let mut caret = position[0];
+    let mut previous_glyph = None;
+    for character in text.chars() {
+        let glyph_id = scaled_font.glyph_id(character);
+        if let Some(previous_glyph) = previous_glyph {
+            caret += scaled_font.kern(previous_glyph, glyph_id);
+        }
+        let glyph = glyph_id.with_scale_and_position(scale, point(caret, position[1]));
+        caret += scaled_font.h_advance(glyph_id);
+        previous_glyph = Some(glyph_id);
+
+        let outlined = match font.outline_glyph(glyph) {
+            Some(outlined) => outlined,
+            // e.g. spaces
+            None => continue,
+        };
+        let bounds = outlined.px_bounds();
+        let pixels = pixmap.pixels_mut();
+        outlined.draw(|glyph_x, glyph_y, coverage| {
+            let pixel_x = bounds.min.x as i32 + glyph_x as i32;
+            let pixel_y = bounds.min.y as i32 + glyph_y as i32;
+            if (0..width).contains(&pixel_x) && (0..height).contains(&pixel_y) {
+                let pixel = &mut pixels[(pixel_y * width + pixel_x) as usize];
+                *pixel = blend_pixel(*pixel, color, coverage);
+            }
+        });
+    }
+}
+
+/// Draw a color over a premultiplied pixel, where the coverage is how much of the pixel it covers
+fn blend_pixel(pixel: PremultipliedColorU8, color: [u8; 4], coverage: f32) -> PremultipliedColorU8 {
+    let alpha = color[3] as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+    let blend = |source: u8, destination: u8| {
+        (source as f32 * alpha + destination as f32 * (1.0 - alpha)).round() as u8
+    };
+    PremultipliedColorU8::from_rgba(
+        blend(color[0], pixel.red()),
+        blend(color[1], pixel.green()),
+        blend(color[2], pixel.blue()),
+        blend(u8::MAX, pixel.alpha()),
+    )
+    .unwrap_or(pixel)
+}
+
+/// Make a rectangle that can have a negative size (e.g. from dragging up), or `None` if it's empty
+fn normalized_rect(x: f32, y: f32, width: f32, height: f32) -> Option<Rect> {
+    Rect::from_ltrb(
+        x.min(x + width),
+        y.min(y + height),
+        x.max(x + width),
+        y.max(y + height),
+    )
+}
@@ -0,0 +1,130 @@
+/*
+ * Benchmark of the two ways of getting the preview to the frontend, encoded or cached
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{Rgba, RgbaImage};
+use shared::image_data::ImageData;
+use std::{
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+/// How many times each path is timed, where the average is reported
+const ITERATIONS: u32 = 5;
+
+/// Make image data with a 4K image that looks like a screenshot: flat areas with some detail
+fn screenshot_image_data() -> ImageData {
+    let image = RgbaImage::from_fn(3840, 2160, |x, y| match (x / 240 + y / 135) % 3 {
+        0 => Rgba([0xf0, 0xf0, 0xf0, 0xff]),
+        1 => Rgba([(x % 256) as u8, (y % 256) as u8, 0x80, 0xff]),
+        _ => Rgba([((x * y) % 251) as u8, 0x30, ((x + y) % 241) as u8, 0xff]),
+    });
+    let mut image_data = ImageData::default();
+    image_data.set_clipboard_image(image);
+    image_data
+}
+
+/// Run a path a few times, returning the average time and the bytes it moved. The path returns
+/// those along with what the `<img>` ends up with, which is checked to be the same every time.
+/// This way, copies can't be optimized out for never being read
+fn time(mut path: impl FnMut() -> (usize, Vec<u8>)) -> (Duration, usize) {
+    let mut runs = Vec::new();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        runs.push(path());
+    }
+    let elapsed = start.elapsed();
+    assert!(runs.windows(2).all(|pair| pair[0] == pair[1]));
+    (elapsed / ITERATIONS, runs[0].0)
+}
+
+/// Get the preview to the frontend the way commands used to: it's encoded while the state is
+/// locked, turned into base64, serialized for IPC, parsed by JavaScript and put into a data URL
+/// for the `<img>`
+fn base64_path(state: &RwLock<ImageData>) -> (usize, Vec<u8>) {
+    let png = state.read().unwrap().preview_png().unwrap();
+    let encoded = base64::encode(&*png);
+    let message = serde_json::to_string(&encoded).unwrap();
+    let parsed: String = serde_json::from_str(&message).unwrap();
+    let data_url = format!("data:image/png;base64,{}", parsed);
+    (message.len() + data_url.len(), data_url.into_bytes())
+}
+
+/// Get the preview to the frontend the way it's served now: only the revision goes through IPC,
+/// then the `<img>` fetches the PNG from the protocol, which looks in the outputs while the state
+/// is locked and encodes from a snapshot on a miss
+fn protocol_path(state: &RwLock<ImageData>) -> (usize, Vec<u8>) {
+    let revision = state.read().unwrap().revision;
+    let message = serde_json::to_string(&revision).unwrap();
+    let png = ImageData::preview_png_of(state, Some(revision)).unwrap();
+    let body = response_body(&png);
+    (message.len() + body.len(), body)
+}
+
+/// Make the protocol response's body, which is a copy of the encoded bytes since responses own
+/// their body. It's timed on its own too, as it happens on every request even when cached
+fn response_body(png: &[u8]) -> Vec<u8> {
+    png.to_vec()
+}
+
+/***** Benchmark *****/
+fn main() {
+    let state = RwLock::new(screenshot_image_data());
+    let png_size = state.read().unwrap().preview_png().unwrap().len();
+    let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    println!(
+        "4K preview ({} byte PNG), average of {} runs:",
+        png_size, ITERATIONS
+    );
+
+    // Both paths start from the same state: either the outputs are cleared so the PNG is encoded
+    // every time, like after an edit, or it's cached, like when the preview is shown again
+    for cached in [false, true] {
+        let clear = || {
+            if !cached {
+                state.read().unwrap().outputs.clear();
+            }
+        };
+        let (base64_time, base64_bytes) = time(|| {
+            clear();
+            base64_path(&state)
+        });
+        let (protocol_time, protocol_bytes) = time(|| {
+            clear();
+            protocol_path(&state)
+        });
+
+        println!(
+            "  {}:",
+            if cached {
+                "cached"
+            } else {
+                "encoded on a miss"
+            }
+        );
+        println!(
+            "    base64 data URL:  {:>8.3} ms, {:>10} bytes copied",
+            milliseconds(base64_time),
+            base64_bytes
+        );
+        println!(
+            "    preview protocol: {:>8.3} ms, {:>10} bytes copied",
+            milliseconds(protocol_time),
+            protocol_bytes
+        );
+    }
+
+    let png = state.read().unwrap().preview_png().unwrap();
+    let (copy_time, copy_bytes) = time(|| {
+        let body = response_body(&png);
+        (body.len(), body)
+    });
+    println!(
+        "    body copy alone:  {:>8.3} ms, {:>10} bytes copied",
+        milliseconds(copy_time),
+        copy_bytes
+    );
+}
@@ -0,0 +1,124 @@
+/*
+ * Clipboard sources
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::error::Error;
+use arboard::Clipboard;
+use image::{ImageBuffer, RgbaImage};
+use std::borrow::Cow;
+
+/***** Clipboard source *****/
+/// Somewhere images can be read from and written to like a clipboard
+pub trait ClipboardSource {
+    /// Get the image in the clipboard as an RGBA image buffer
+    fn get_image(&mut self) -> Result<RgbaImage, Error>;
+    /// Get the text in the clipboard, which might point to or hold an image
+    fn get_text(&mut self) -> Result<String, Error>;
+    /// Put an RGBA image buffer in the clipboard
+    fn set_image(&mut self, image_buf: &RgbaImage) -> Result<(), Error>;
+}
+
+/***** Arboard *****/
+/// The system clipboard
+pub struct ArboardClipboard {
+    clipboard: Clipboard,
+}
+impl ArboardClipboard {
+    pub fn new() -> Result<ArboardClipboard, Error> {
+        match Clipboard::new() {
+            Ok(clipboard) => Ok(ArboardClipboard { clipboard }),
+            Err(e) => Err(Error::ClipboardUnavailable(e.to_string())),
+        }
+    }
+}
+impl ClipboardSource for ArboardClipboard {
+    fn get_image(&mut self) -> Result<RgbaImage, Error> {
+        let image_data = match self.clipboard.get_image() {
+            Ok(image_data) => image_data,
+            Err(arboard::Error::ContentNotAvailable) => return Err(Error::NoImageInClipboard),
+            Err(e) => return Err(Error::ClipboardUnavailable(e.to_string())),
+        };
+
+        // Convert the raw image data (bytes is image::ImageBuffer.into_raw())
+        match ImageBuffer::from_raw(
+            image_data.width as u32,
+            image_data.height as u32,
+            image_data.bytes.into_owned(),
+        ) {
+            Some(buf) => Ok(buf),
+            None => Err(Error::EncodeFailed(
+                "The clipboard image's raw bytes don't match its size".to_string(),
+            )),
+        }
+    }
+
+    fn get_text(&mut self) -> Result<String, Error> {
+        match self.clipboard.get_text() {
+            Ok(text) => Ok(text),
+            Err(arboard::Error::ContentNotAvailable) => Err(Error::NoImageInClipboard),
+            Err(e) => Err(Error::ClipboardUnavailable(e.to_string())),
+        }
+    }
+
+    fn set_image(&mut self, image_buf: &RgbaImage) -> Result<(), Error> {
+        // The bytes are in the same RGBA layout `get_image` gives
+        let image_data = arboard::ImageData {
+            width: image_buf.width() as usize,
+            height: image_buf.height() as usize,
+            bytes: Cow::Borrowed(image_buf.as_raw()),
+        };
+        match self.clipboard.set_image(image_data) {
+            Ok(_) => Ok(()),
+            Err(arboard::Error::ClipboardNotSupported) => Err(Error::ClipboardUnavailable(
+                arboard::Error::ClipboardNotSupported.to_string(),
+            )),
+            Err(e) => Err(Error::ClipboardWriteRefused(e.to_string())),
+        }
+    }
+}
+
+/***** Memory *****/
+/// A clipboard that only exists in memory, for testing without a display
+#[derive(Default)]
+pub struct MemoryClipboard {
+    pub image: Option<RgbaImage>,
+    pub text: Option<String>,
+}
+impl MemoryClipboard {
+    pub fn with_image(image: RgbaImage) -> MemoryClipboard {
+        MemoryClipboard {
+            image: Some(image),
+            text: None,
+        }
+    }
+
+    pub fn with_text(text: &str) -> MemoryClipboard {
+        MemoryClipboard {
+            image: None,
+            text: Some(text.to_string()),
+        }
+    }
+}
+impl ClipboardSource for MemoryClipboard {
+    fn get_image(&mut self) -> Result<RgbaImage, Error> {
+        match &self.image {
+            Some(image) => Ok(image.clone()),
+            None => Err(Error::NoImageInClipboard),
+        }
+    }
+
+    fn get_text(&mut self) -> Result<String, Error> {
+        match &self.text {
+            Some(text) => Ok(text.clone()),
+            None => Err(Error::NoImageInClipboard),
+        }
+    }
+
+    fn set_image(&mut self, image_buf: &RgbaImage) -> Result<(), Error> {
+        self.image = Some(image_buf.clone());
+        Ok(())
+    }
+}
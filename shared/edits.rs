@@ -0,0 +1,410 @@
+/*
+ * Non-destructive edits, applied to the image when it's encoded
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{
+    error::Error,
+    payloads::{Edit, RedactStyle},
+};
+use image::{imageops, DynamicImage, GenericImageView, Rgba, RgbaImage};
+use std::borrow::Cow;
+
+/***** Constants *****/
+/// The most padding that can be added at once, so a typo can't make a gigantic image
+pub const MAX_PADDING: u32 = 4096;
+/// The smallest blur used for redacting, as smaller ones leave text readable
+const REDACT_MIN_BLUR_SIGMA: f32 = 6.0;
+/// About how many blocks fit along the longest edge of a pixelated redaction
+const REDACT_PIXELATE_BLOCKS: u32 = 8;
+/// The smallest block used for pixelating, as smaller ones leave text readable
+const REDACT_PIXELATE_MIN_BLOCK_SIZE: u32 = 8;
+
+/***** Edit stack *****/
+/// The edits made to the current image in order, and the ones undone so they can be redone
+#[derive(Default, Clone)]
+pub struct EditStack {
+    pub edits: Vec<Edit>,
+    pub undone: Vec<Edit>,
+}
+impl EditStack {
+    /// Add an edit, which forgets the undone edits
+    pub fn push(&mut self, edit: Edit) {
+        self.edits.push(edit);
+        self.undone.clear();
+    }
+
+    /// Undo the last edit, returning whether there was one
+    pub fn undo(&mut self) -> bool {
+        match self.edits.pop() {
+            Some(edit) => {
+                self.undone.push(edit);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the last undone edit, returning whether there was one
+    pub fn redo(&mut self) -> bool {
+        match self.undone.pop() {
+            Some(edit) => {
+                self.edits.push(edit);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget every edit, including the undone ones
+    pub fn reset(&mut self) {
+        self.edits.clear();
+        self.undone.clear();
+    }
+
+    /// Apply the edits to an image in order. Without edits, the image isn't copied
+    pub fn apply<'a>(&self, image: &'a DynamicImage) -> Cow<'a, DynamicImage> {
+        self.edits.iter().fold(Cow::Borrowed(image), |image, edit| {
+            Cow::Owned(apply_edit(&image, edit))
+        })
+    }
+
+    /// Get the size of an image once the edits are applied to it. Only trims depend on the
+    /// pixels, so the image is only edited up to the last trim, if there is one
+    pub fn edited_size(&self, image: &DynamicImage) -> (u32, u32) {
+        let last_trim = self
+            .edits
+            .iter()
+            .rposition(|edit| matches!(edit, Edit::Trim { .. }));
+        let (size, rest) = match last_trim {
+            Some(index) => {
+                let trimmed = EditStack {
+                    edits: self.edits[..=index].to_vec(),
+                    undone: Vec::new(),
+                }
+                .apply(image)
+                .dimensions();
+                (trimmed, &self.edits[index + 1..])
+            }
+            None => (image.dimensions(), &self.edits[..]),
+        };
+        rest.iter().fold(size, |(width, height), edit| {
+            size_after_edit(edit, width, height)
+        })
+    }
+}
+
+/***** Edits *****/
+/// Check that an edit can be applied to an image of a size, e.g. that a crop isn't empty
+pub fn validate_edit(edit: &Edit, width: u32, height: u32) -> Result<(), Error> {
+    match edit {
+        Edit::Crop {
+            x,
+            y,
+            width: crop_width,
+            height: crop_height,
+        } => validate_rectangle("crop", [*x, *y, *crop_width, *crop_height], width, height),
+        Edit::Redact {
+            x,
+            y,
+            width: redact_width,
+            height: redact_height,
+            ..
+        } => validate_rectangle(
+            "redaction",
+            [*x, *y, *redact_width, *redact_height],
+            width,
+            height,
+        ),
+        Edit::Rotate { degrees } if !degrees.is_finite() => Err(Error::InvalidEdit(format!(
+            "Can't rotate by {} degrees",
+            degrees
+        ))),
+        Edit::Pad { size, color } => {
+            if *size == 0 {
+                return Err(Error::InvalidEdit("The padding is empty".to_string()));
+            }
+            if *size > MAX_PADDING {
+                return Err(Error::InvalidEdit(format!(
+                    "The padding can be at most {} pixels",
+                    MAX_PADDING
+                )));
+            }
+            match parse_hex_rgba(color) {
+                Some(_) => Ok(()),
+                None => Err(Error::InvalidEdit(format!("{} isn't a hex color", color))),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that a rectangle given as [x, y, width, height] isn't empty and starts in the image
+fn validate_rectangle(
+    name: &str,
+    rectangle: [u32; 4],
+    width: u32,
+    height: u32,
+) -> Result<(), Error> {
+    let [x, y, rectangle_width, rectangle_height] = rectangle;
+    if rectangle_width == 0 || rectangle_height == 0 {
+        return Err(Error::InvalidEdit(format!("The {} is empty", name)));
+    }
+    if x >= width || y >= height {
+        return Err(Error::InvalidEdit(format!(
+            "The {} is outside of the image",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Apply an edit to an image
+pub fn apply_edit(image: &DynamicImage, edit: &Edit) -> DynamicImage {
+    match edit {
+        // Crops past the edges are shrunk to fit
+        Edit::Crop {
+            x,
+            y,
+            width,
+            height,
+        } => image.crop_imm(*x, *y, *width, *height),
+        Edit::Rotate90 => image.rotate90(),
+        Edit::Rotate180 => image.rotate180(),
+        Edit::Rotate270 => image.rotate270(),
+        Edit::FlipHorizontal => image.fliph(),
+        Edit::FlipVertical => image.flipv(),
+        Edit::Rotate { degrees } => DynamicImage::ImageRgba8(rotate_free(image, *degrees)),
+        Edit::Trim { tolerance } => match trim_bounds(&image.to_rgba8(), *tolerance) {
+            Some((x, y, width, height)) => image.crop_imm(x, y, width, height),
+            None => image.clone(),
+        },
+        // Colors are validated before being added, so an invalid one is left transparent
+        Edit::Pad { size, color } => DynamicImage::ImageRgba8(pad(
+            image,
+            *size,
+            Rgba(parse_hex_rgba(color).unwrap_or([0; 4])),
+        )),
+        Edit::Redact {
+            x,
+            y,
+            width,
+            height,
+            style,
+        } => DynamicImage::ImageRgba8(redact(image, [*x, *y, *width, *height], *style)),
+    }
+}
+
+/// Get the size of an image once an edit that doesn't depend on its pixels is applied to it.
+/// Trims keep the size, as they can only be measured on the image
+fn size_after_edit(edit: &Edit, width: u32, height: u32) -> (u32, u32) {
+    match edit {
+        // The same as `crop_imm`, which shrinks crops past the edges
+        Edit::Crop {
+            x,
+            y,
+            width: crop_width,
+            height: crop_height,
+        } => (
+            (*crop_width).min(width - (*x).min(width)),
+            (*crop_height).min(height - (*y).min(height)),
+        ),
+        Edit::Rotate90 | Edit::Rotate270 => (height, width),
+        Edit::Rotate { degrees } => rotated_size(width, height, *degrees),
+        Edit::Pad { size, .. } => (width + size * 2, height + size * 2),
+        Edit::Rotate180
+        | Edit::FlipHorizontal
+        | Edit::FlipVertical
+        | Edit::Trim { .. }
+        | Edit::Redact { .. } => (width, height),
+    }
+}
+
+/// Replace the pixels in a rectangle given as [x, y, width, height], so they can't be recovered.
+/// Like crops, rectangles past the edges are shrunk to fit
+fn redact(image: &DynamicImage, rectangle: [u32; 4], style: RedactStyle) -> RgbaImage {
+    let mut image_buf = image.to_rgba8();
+    let [x, y, width, height] = rectangle;
+    let width = width.min(image_buf.width().saturating_sub(x));
+    let height = height.min(image_buf.height().saturating_sub(y));
+    if width == 0 || height == 0 {
+        return image_buf;
+    }
+
+    let region = imageops::crop_imm(&image_buf, x, y, width, height).to_image();
+    let redacted = match style {
+        RedactStyle::Fill => RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, u8::MAX])),
+        RedactStyle::Blur => {
+            let sigma = (width.min(height) as f32 / 4.0).max(REDACT_MIN_BLUR_SIGMA);
+            imageops::blur(&region, sigma)
+        }
+        RedactStyle::Pixelate => pixelate(&region),
+    };
+    imageops::replace(&mut image_buf, &redacted, x as i64, y as i64);
+    image_buf
+}
+
+/// Average an image into blocks
+fn pixelate(image: &RgbaImage) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let block_size =
+        (width.max(height) / REDACT_PIXELATE_BLOCKS).max(REDACT_PIXELATE_MIN_BLOCK_SIZE);
+    let columns = ((width + block_size - 1) / block_size).max(1);
+    let rows = ((height + block_size - 1) / block_size).max(1);
+    // Blocks are spread evenly, so there's no single pixel wide block left at the edges
+    let edge =
+        |size: u32, count: u32, index: u32| (index as u64 * size as u64 / count as u64) as u32;
+
+    let mut pixelated = RgbaImage::new(width, height);
+    for row in 0..rows {
+        let (top, bottom) = (edge(height, rows, row), edge(height, rows, row + 1));
+        for column in 0..columns {
+            let (left, right) = (
+                edge(width, columns, column),
+                edge(width, columns, column + 1),
+            );
+            let mut sum = [0_u64; 4];
+            for y in top..bottom {
+                for x in left..right {
+                    for (channel_sum, channel) in sum.iter_mut().zip(image.get_pixel(x, y).0) {
+                        *channel_sum += channel as u64;
+                    }
+                }
+            }
+            let count = ((right - left) * (bottom - top)).max(1) as u64;
+            let average = Rgba(sum.map(|channel_sum| ((channel_sum + count / 2) / count) as u8));
+            for y in top..bottom {
+                for x in left..right {
+                    pixelated.put_pixel(x, y, average);
+                }
+            }
+        }
+    }
+    pixelated
+}
+
+/// Get the rectangle (x, y, width, height) left once the border is trimmed off an image, where
+/// the border is the rows and columns matching the top left pixel. If there's no border, or
+/// the whole image is border, there's nothing to trim and `None` is returned
+pub fn trim_bounds(image: &RgbaImage, tolerance: u8) -> Option<(u32, u32, u32, u32)> {
+    let (width, height) = image.dimensions();
+    let border = *image.get_pixel_checked(0, 0)?;
+    let is_border = |x: u32, y: u32| {
+        let pixel = image.get_pixel(x, y);
+        // Transparent pixels have no color worth keeping
+        (pixel[3] == 0 && border[3] == 0)
+            || pixel
+                .0
+                .iter()
+                .zip(border.0.iter())
+                .all(|(channel, border_channel)| {
+                    (*channel as i16 - *border_channel as i16).unsigned_abs() <= tolerance as u16
+                })
+    };
+    let is_border_row = |y: u32| (0..width).all(|x| is_border(x, y));
+
+    let top = (0..height).find(|y| !is_border_row(*y))?;
+    let bottom = (top..height).rev().find(|y| !is_border_row(*y))? + 1;
+    // Only the rows left need checking for the columns
+    let is_border_column = |x: u32| (top..bottom).all(|y| is_border(x, y));
+    let left = (0..width).find(|x| !is_border_column(*x))?;
+    let right = (left..width).rev().find(|x| !is_border_column(*x))? + 1;
+
+    match (left, top, right, bottom) == (0, 0, width, height) {
+        true => None,
+        false => Some((left, top, right - left, bottom - top)),
+    }
+}
+
+/// Add a border of a color around an image
+fn pad(image: &DynamicImage, size: u32, color: Rgba<u8>) -> RgbaImage {
+    let mut padded =
+        RgbaImage::from_pixel(image.width() + size * 2, image.height() + size * 2, color);
+    imageops::replace(&mut padded, &image.to_rgba8(), size as i64, size as i64);
+    padded
+}
+
+/// Parse a hex color like "#ff00ff", or "#ff00ff80" with alpha
+pub(crate) fn parse_hex_rgba(color: &str) -> Option<[u8; 4]> {
+    let hex = color.trim().trim_start_matches('#');
+    let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+    match hex.len() {
+        6 => Some([channel(0)?, channel(2)?, channel(4)?, u8::MAX]),
+        8 => Some([channel(0)?, channel(2)?, channel(4)?, channel(6)?]),
+        _ => None,
+    }
+}
+
+/// Get the size of an image rotated by any angle, grown to fit the corners
+fn rotated_size(width: u32, height: u32, degrees: f32) -> (u32, u32) {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (width, height) = (width as f32, height as f32);
+    // Rounding avoids a 1 pixel border from float error, e.g. at 90 degrees
+    let new_width = (width * cos.abs() + height * sin.abs() - 0.001)
+        .ceil()
+        .max(1.0);
+    let new_height = (width * sin.abs() + height * cos.abs() - 0.001)
+        .ceil()
+        .max(1.0);
+    (new_width as u32, new_height as u32)
+}
+
+/// Rotate an image clockwise by any angle, growing it to fit the corners.
+/// The new area is transparent, and pixels are sampled bilinearly
+fn rotate_free(image: &DynamicImage, degrees: f32) -> RgbaImage {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    let (new_width, new_height) = rotated_size(image.width(), image.height(), degrees);
+    let source = image.to_rgba8();
+
+    RgbaImage::from_fn(new_width, new_height, |x, y| {
+        // Rotate the pixel's center back to where it is in the source image
+        let dx = x as f32 + 0.5 - new_width as f32 / 2.0;
+        let dy = y as f32 + 0.5 - new_height as f32 / 2.0;
+        let source_x = dx * cos + dy * sin + width / 2.0 - 0.5;
+        let source_y = -dx * sin + dy * cos + height / 2.0 - 0.5;
+        sample_bilinear(&source, source_x, source_y)
+    })
+}
+
+/// Sample an image between pixels, where outside of the image is transparent
+fn sample_bilinear(image: &RgbaImage, x: f32, y: f32) -> Rgba<u8> {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let pixel = |px: f32, py: f32| -> [f32; 4] {
+        match px >= 0.0 && py >= 0.0 && image.in_bounds(px as u32, py as u32) {
+            true => image.get_pixel(px as u32, py as u32).0.map(f32::from),
+            false => [0.0; 4],
+        }
+    };
+    let corners = [
+        (pixel(x0, y0), (1.0 - fx) * (1.0 - fy)),
+        (pixel(x0 + 1.0, y0), fx * (1.0 - fy)),
+        (pixel(x0, y0 + 1.0), (1.0 - fx) * fy),
+        (pixel(x0 + 1.0, y0 + 1.0), fx * fy),
+    ];
+
+    // Colors are weighted by alpha, so transparent pixels don't darken the edges
+    let alpha: f32 = corners
+        .iter()
+        .map(|(color, weight)| color[3] * weight)
+        .sum();
+    if alpha <= 0.0 {
+        return Rgba([0, 0, 0, 0]);
+    }
+    let channel = |index: usize| {
+        let sum: f32 = corners
+            .iter()
+            .map(|(color, weight)| color[index] * color[3] * weight)
+            .sum();
+        (sum / alpha).round().clamp(0.0, 255.0) as u8
+    };
+    Rgba([
+        channel(0),
+        channel(1),
+        channel(2),
+        alpha.round().clamp(0.0, 255.0) as u8,
+    ])
+}
@@ -0,0 +1,267 @@
+/*
+ * Image encoding with per-format options
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{
+    error::Error,
+    icon,
+    settings::{
+        EncoderOptions, PngCompression, PngFilter, ResizeOptions, SaveFormat, TiffCompression,
+        TransparencyBackground,
+    },
+};
+use image::{
+    codecs::{
+        gif::GifEncoder,
+        png::{CompressionType, FilterType, PngEncoder},
+        pnm::{PnmSubtype, SampleEncoding},
+    },
+    DynamicImage, Frame, ImageEncoder, ImageOutputFormat, Rgb, RgbImage,
+};
+use std::io::{Seek, Write};
+use tiff::encoder::{
+    colortype::RGBA8,
+    compression::{Deflate, Lzw, Packbits, Uncompressed},
+    TiffEncoder,
+};
+
+/***** Constants *****/
+/// The biggest width or height an ICO image can have
+const ICO_MAX_SIZE: u32 = 256;
+/// The size of each square of the checkerboard transparency background
+const CHECKERBOARD_SQUARE_SIZE: u32 = 8;
+/// The colors of the checkerboard transparency background squares
+const CHECKERBOARD_COLORS: [[u8; 3]; 2] = [[0xff, 0xff, 0xff], [0xcc, 0xcc, 0xcc]];
+
+/***** Formats *****/
+/// Whether this build is able to encode a format
+pub fn can_encode(format: &SaveFormat) -> bool {
+    cfg!(feature = "avif") || *format != SaveFormat::Avif
+}
+
+/// Get the `image` crate output format for formats that don't need special encoding
+fn output_format(
+    format: &SaveFormat,
+    options: &EncoderOptions,
+) -> Result<ImageOutputFormat, Error> {
+    Ok(match format {
+        SaveFormat::Png => ImageOutputFormat::Png,
+        SaveFormat::Jpg => ImageOutputFormat::Jpeg(options.jpeg_quality.clamp(1, 100)),
+        SaveFormat::Bmp => ImageOutputFormat::Bmp,
+        SaveFormat::Ico => ImageOutputFormat::Ico,
+        SaveFormat::Tiff => ImageOutputFormat::Tiff,
+        SaveFormat::Gif => ImageOutputFormat::Gif,
+        SaveFormat::Tga => ImageOutputFormat::Tga,
+        // The `image` crate's WebP encoder is lossless
+        SaveFormat::Webp => ImageOutputFormat::WebP,
+        SaveFormat::Qoi => ImageOutputFormat::Qoi,
+        SaveFormat::Pbm => ImageOutputFormat::Pnm(PnmSubtype::Bitmap(SampleEncoding::Binary)),
+        SaveFormat::Pgm => ImageOutputFormat::Pnm(PnmSubtype::Graymap(SampleEncoding::Binary)),
+        SaveFormat::Ppm => ImageOutputFormat::Pnm(PnmSubtype::Pixmap(SampleEncoding::Binary)),
+        SaveFormat::Pam => ImageOutputFormat::Pnm(PnmSubtype::ArbitraryMap),
+        SaveFormat::Farbfeld => ImageOutputFormat::Farbfeld,
+        SaveFormat::OpenExr => ImageOutputFormat::OpenExr,
+        SaveFormat::Icns => return Err(Error::InvalidFormat(format.to_string())),
+        #[cfg(feature = "avif")]
+        SaveFormat::Avif => ImageOutputFormat::Avif,
+        #[cfg(not(feature = "avif"))]
+        SaveFormat::Avif => {
+            // This build was made without the `avif` feature
+            return Err(Error::InvalidFormat(format.to_string()));
+        }
+    })
+}
+
+/// Convert an image to a color type that the format's encoder supports, if it needs to be
+fn convert_color_for_format(image: &DynamicImage, format: &SaveFormat) -> Option<DynamicImage> {
+    match format {
+        SaveFormat::Pbm | SaveFormat::Pgm => Some(DynamicImage::ImageLuma8(image.to_luma8())),
+        SaveFormat::Ppm => Some(DynamicImage::ImageRgb8(image.to_rgb8())),
+        SaveFormat::Farbfeld => Some(DynamicImage::ImageRgba16(image.to_rgba16())),
+        SaveFormat::OpenExr => Some(DynamicImage::ImageRgba32F(image.to_rgba32f())),
+        _ => None,
+    }
+}
+
+/***** Transparency *****/
+/// Whether any pixel of an image is at least partly transparent
+pub fn has_transparency(image: &DynamicImage) -> bool {
+    image.color().has_alpha() && image.to_rgba8().pixels().any(|pixel| pixel[3] != u8::MAX)
+}
+
+/// Parse a hex color like "#ff00ff"
+fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Composite an image onto the transparency background, for formats without transparency
+pub fn flatten_transparency(image: &DynamicImage, options: &EncoderOptions) -> DynamicImage {
+    let solid_color = match options.transparency_background {
+        TransparencyBackground::White => Some([0xff, 0xff, 0xff]),
+        TransparencyBackground::Black => Some([0x00, 0x00, 0x00]),
+        TransparencyBackground::Checkerboard => None,
+        // An invalid color falls back to white
+        TransparencyBackground::Color => {
+            Some(parse_hex_color(&options.transparency_color).unwrap_or([0xff, 0xff, 0xff]))
+        }
+    };
+
+    let image_buf = image.to_rgba8();
+    let flattened = RgbImage::from_fn(image_buf.width(), image_buf.height(), |x, y| {
+        let pixel = image_buf.get_pixel(x, y);
+        let background = solid_color.unwrap_or_else(|| {
+            let square = (x / CHECKERBOARD_SQUARE_SIZE + y / CHECKERBOARD_SQUARE_SIZE) % 2;
+            CHECKERBOARD_COLORS[square as usize]
+        });
+        let alpha = pixel[3] as u32;
+        let blend = |channel: usize| {
+            ((pixel[channel] as u32 * alpha + background[channel] as u32 * (255 - alpha) + 127)
+                / 255) as u8
+        };
+        Rgb([blend(0), blend(1), blend(2)])
+    });
+    DynamicImage::ImageRgb8(flattened)
+}
+
+/***** Resizing *****/
+/// Resample an image with the resize options, or `None` if its size doesn't change
+pub fn resize_image(image: &DynamicImage, options: &ResizeOptions) -> Option<DynamicImage> {
+    let (width, height) = options.output_size(image.width(), image.height());
+    match (width, height) == (image.width(), image.height()) {
+        true => None,
+        false => Some(image.resize_exact(width, height, icon::filter_type(&options.filter))),
+    }
+}
+
+/***** Encoding *****/
+/// Encode an image into a writer with the format and encoder options specified
+pub fn encode_image<W: Write + Seek>(
+    image: &DynamicImage,
+    format: &SaveFormat,
+    options: &EncoderOptions,
+    writer: &mut W,
+) -> Result<(), Error> {
+    let resized;
+    let image = match resize_image(image, &options.resize) {
+        Some(resized_image) => {
+            resized = resized_image;
+            &resized
+        }
+        None => image,
+    };
+
+    // Formats without transparency would otherwise drop the alpha channel, which leaves
+    // whatever color the transparent pixels happen to have
+    let flattened;
+    let image = if !format.supports_alpha() && image.color().has_alpha() {
+        flattened = flatten_transparency(image, options);
+        &flattened
+    } else {
+        image
+    };
+
+    let result = match format {
+        SaveFormat::Png => PngEncoder::new_with_quality(
+            writer,
+            match options.png_compression {
+                PngCompression::Default => CompressionType::Default,
+                PngCompression::Fast => CompressionType::Fast,
+                PngCompression::Best => CompressionType::Best,
+            },
+            match options.png_filter {
+                PngFilter::NoFilter => FilterType::NoFilter,
+                PngFilter::Sub => FilterType::Sub,
+                PngFilter::Up => FilterType::Up,
+                PngFilter::Avg => FilterType::Avg,
+                PngFilter::Paeth => FilterType::Paeth,
+                PngFilter::Adaptive => FilterType::Adaptive,
+            },
+        )
+        .write_image(
+            image.as_bytes(),
+            image.width(),
+            image.height(),
+            image.color(),
+        ),
+        SaveFormat::Gif => GifEncoder::new_with_speed(writer, options.gif_speed.clamp(1, 30))
+            .encode_frame(Frame::new(image.to_rgba8())),
+        // The `image` crate's TIFF encoder can't compress, so the `tiff` crate is used directly
+        SaveFormat::Tiff => return encode_tiff(image, &options.tiff_compression, writer),
+        // Multi-size icons have their own encoders
+        SaveFormat::Ico if options.ico_multi_size => {
+            return icon::encode_ico(image, &options.icon_sizes, &options.icon_filter, writer)
+        }
+        SaveFormat::Icns => {
+            return icon::encode_icns(image, &options.icon_sizes, &options.icon_filter, writer)
+        }
+        SaveFormat::Ico
+            if options.ico_shrink_to_fit
+                && (image.width() > ICO_MAX_SIZE || image.height() > ICO_MAX_SIZE) =>
+        {
+            image
+                .thumbnail(ICO_MAX_SIZE, ICO_MAX_SIZE)
+                .write_to(writer, ImageOutputFormat::Ico)
+        }
+        _ => {
+            let output_format = match output_format(format, options) {
+                Ok(output_format) => output_format,
+                Err(e) => return Err(e),
+            };
+            convert_color_for_format(image, format)
+                .as_ref()
+                .unwrap_or(image)
+                .write_to(writer, output_format)
+        }
+    };
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::EncodeFailed(e.to_string())),
+    }
+}
+
+/// Encode an image as a TIFF with the compression specified
+fn encode_tiff<W: Write + Seek>(
+    image: &DynamicImage,
+    compression: &TiffCompression,
+    writer: &mut W,
+) -> Result<(), Error> {
+    let mut encoder = match TiffEncoder::new(writer) {
+        Ok(encoder) => encoder,
+        Err(e) => return Err(Error::EncodeFailed(e.to_string())),
+    };
+    let image_buf = image.to_rgba8();
+    let (width, height) = image_buf.dimensions();
+    let data = image_buf.as_raw().as_slice();
+
+    let result = match compression {
+        TiffCompression::Uncompressed => {
+            encoder.write_image_with_compression::<RGBA8, _>(width, height, Uncompressed, data)
+        }
+        TiffCompression::Lzw => {
+            encoder.write_image_with_compression::<RGBA8, _>(width, height, Lzw, data)
+        }
+        TiffCompression::Deflate => encoder.write_image_with_compression::<RGBA8, _>(
+            width,
+            height,
+            Deflate::default(),
+            data,
+        ),
+        TiffCompression::Packbits => {
+            encoder.write_image_with_compression::<RGBA8, _>(width, height, Packbits, data)
+        }
+    };
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::EncodeFailed(e.to_string())),
+    }
+}
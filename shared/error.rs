@@ -0,0 +1,133 @@
+/*
+ * A shared error type, sent from the backend to the frontend
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+#![allow(dead_code, unused_imports, clippy::derive_partial_eq_without_eq)]
+/* Imports */
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/***** Error *****/
+/// Everything that can go wrong in the backend, so the frontend can react to each kind
+/// Serialized like `{ "kind": "IoError", "details": { "path": ..., "message": ... } }`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "details")]
+pub enum Error {
+    /// The clipboard couldn't be accessed
+    ClipboardUnavailable(String),
+    /// The clipboard (or current image) doesn't have an image in it
+    NoImageInClipboard,
+    /// The clipboard refused an image, e.g. because another application is holding it
+    ClipboardWriteRefused(String),
+    /// An image couldn't be encoded or decoded
+    EncodeFailed(String),
+    /// A file couldn't be opened as an image, e.g. because its format is unknown
+    DecodeFailed { path: String, message: String },
+    /// Something copied that isn't a file (e.g. a data URL or SVG markup) couldn't be decoded
+    ClipboardDecodeFailed(String),
+    /// A file couldn't be read or written
+    IoError { path: String, message: String },
+    /// A lock is poisoned, because a thread panicked while holding it
+    LockPoisoned(String),
+    /// A save format is unknown, or this build can't encode it
+    InvalidFormat(String),
+    /// The settings couldn't be parsed
+    SettingsParse(String),
+    /// The settings parsed, but some of them can't be used, e.g. a save path in a folder that
+    /// can't be created
+    InvalidSettings(Vec<SettingError>),
+    /// An edit can't be applied to the image, e.g. an empty crop
+    InvalidEdit(String),
+    /// An annotation can't be drawn, e.g. because its color isn't valid
+    InvalidAnnotation(String),
+    /// There is no history entry with the ID
+    HistoryEntryNotFound(u64),
+    /// Something was asked for a revision of the image it isn't at anymore, e.g. a late preview
+    OutdatedRevision(u64),
+    /// The user canceled a dialog
+    Canceled,
+    /// Anything else, e.g. Tauri failing to call a command
+    Other(String),
+}
+impl Error {
+    /// Make an I/O error for a path
+    pub fn io(path: impl AsRef<std::path::Path>, e: impl Display) -> Error {
+        Error::IoError {
+            path: path.as_ref().to_string_lossy().to_string(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Error::ClipboardUnavailable(message) => {
+                write!(f, "Failed to access the clipboard: {}", message)
+            }
+            Error::NoImageInClipboard => write!(f, "The clipboard doesn't have an image"),
+            Error::ClipboardWriteRefused(message) => {
+                write!(f, "The clipboard refused the image: {}", message)
+            }
+            Error::EncodeFailed(message) => write!(f, "Failed to encode the image: {}", message),
+            Error::DecodeFailed { path, message } => {
+                write!(f, "Failed to open {} as an image: {}", path, message)
+            }
+            Error::ClipboardDecodeFailed(message) => {
+                write!(f, "Failed to decode the copied image: {}", message)
+            }
+            Error::IoError { path, message } => write!(f, "Failed to access {}: {}", path, message),
+            Error::LockPoisoned(name) => write!(
+                f,
+                "The {} lock is poisoned, so please restart the application",
+                name
+            ),
+            Error::InvalidFormat(format) => write!(
+                f,
+                "Can't save as {}, as it's unknown or this build can't encode it",
+                format
+            ),
+            Error::SettingsParse(message) => write!(f, "Failed to parse the settings: {}", message),
+            Error::InvalidSettings(errors) => {
+                write!(f, "Some settings can't be used:")?;
+                for error in errors {
+                    write!(f, "\n{}: {}", error.field, error.message)?;
+                }
+                Ok(())
+            }
+            Error::InvalidEdit(message) => write!(f, "Can't edit the image: {}", message),
+            Error::InvalidAnnotation(message) => {
+                write!(f, "Can't annotate the image: {}", message)
+            }
+            Error::HistoryEntryNotFound(id) => {
+                write!(f, "There is no history entry with ID {}", id)
+            }
+            Error::OutdatedRevision(revision) => {
+                write!(f, "The image changed since revision {}", revision)
+            }
+            Error::Canceled => write!(f, "Canceled"),
+            Error::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+impl std::error::Error for Error {}
+
+/***** Setting errors *****/
+/// Why a setting can't be used, so the frontend can show it next to the setting
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SettingError {
+    /// The name of the setting's field, e.g. `save_path`
+    pub field: String,
+    pub message: String,
+}
+impl SettingError {
+    /// Make an error for a field
+    pub fn new(field: &str, message: impl Display) -> SettingError {
+        SettingError {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
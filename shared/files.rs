@@ -0,0 +1,57 @@
+/*
+ * File writing helpers
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use std::{
+    fs::{remove_file, rename, File},
+    io::{Result as IoResult, Write},
+    path::{Path, PathBuf},
+};
+
+/***** Writing *****/
+/// Write bytes to a file through a temporary file in the same directory, which is then
+/// renamed over the destination. This way the file is either fully written or untouched
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> IoResult<()> {
+    let file_name = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = path.with_file_name(format!(".{}.tmp", file_name));
+
+    let write_result = File::create(&temp_path).and_then(|mut temp_file| {
+        temp_file.write_all(bytes)?;
+        temp_file.sync_all()
+    });
+    match write_result.and_then(|_| rename(&temp_path, path)) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let _ = remove_file(&temp_path);
+            Err(e)
+        }
+    }
+}
+
+/// Find a path that doesn't exist yet by adding a number to the end of the file name,
+/// e.g. `image.png` -> `image-1.png` -> `image-2.png`
+pub fn find_free_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut suffix = 1;
+    loop {
+        let free_path = path.with_file_name(format!("{}-{}{}", stem, suffix, extension));
+        if !free_path.exists() {
+            return free_path;
+        }
+        suffix += 1;
+    }
+}
@@ -0,0 +1,182 @@
+/*
+ * Clipboard image history stored on disk
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{error::Error, files::write_atomic, payloads::HistoryEntry};
+use image::{DynamicImage, ImageOutputFormat};
+use std::{
+    fs::{create_dir_all, read, read_to_string, remove_file},
+    io::Cursor,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/***** Constants *****/
+/// The biggest width or height of a history thumbnail
+const THUMBNAIL_SIZE: u32 = 128;
+
+/***** History *****/
+pub struct History {
+    /// The directory holding the history index and images (see `get_history_dir`)
+    dir: PathBuf,
+    /// Entries from oldest to newest
+    pub entries: Vec<HistoryEntry>,
+}
+impl History {
+    /// Get the path of the history index file
+    fn get_index_path(&self) -> PathBuf {
+        self.dir.join("index.json")
+    }
+
+    /// Get the path of an entry's full size image
+    fn get_image_path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.png", id))
+    }
+
+    /// Get the path of an entry's thumbnail
+    fn get_thumbnail_path(&self, id: u64) -> PathBuf {
+        self.dir.join(format!("{}.thumb.png", id))
+    }
+
+    /// Load the history kept in a directory. A missing or broken index means an empty history,
+    /// and the directory is only created once an image is added
+    pub fn load(dir: &Path) -> History {
+        let mut history = History {
+            dir: dir.to_path_buf(),
+            entries: Vec::new(),
+        };
+        if let Ok(index_text) = read_to_string(history.get_index_path()) {
+            history.entries = serde_json::from_str(index_text.as_str()).unwrap_or_default();
+        }
+        history
+    }
+
+    /// Write the history index to disk
+    fn save_index(&self) -> Result<(), Error> {
+        let index_text = match serde_json::to_string(&self.entries) {
+            Ok(index_text) => index_text,
+            Err(e) => {
+                return Err(Error::Other(format!(
+                    "Failed to serialize the history index: {}",
+                    e
+                )))
+            }
+        };
+        match write_atomic(&self.get_index_path(), index_text.as_bytes()) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::io(self.get_index_path(), e)),
+        }
+    }
+
+    /// Get an entry by its ID
+    pub fn get(&self, id: u64) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Write an image to disk as a PNG, atomically so a crash can't leave half an image behind
+    fn write_png(path: &Path, image: &DynamicImage) -> Result<(), Error> {
+        // The vector must be wrapped in a cursor so it satisfies the `Seek` trait
+        let mut cursor = Cursor::new(Vec::new());
+        match image.write_to(&mut cursor, ImageOutputFormat::Png) {
+            Ok(_) => (),
+            Err(e) => return Err(Error::io(path, e)),
+        };
+        match write_atomic(path, cursor.get_ref()) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::io(path, e)),
+        }
+    }
+
+    /// Add an image to the history, removing the oldest entries past the limit.
+    /// Nothing will be added if the newest entry is the same image.
+    /// Images are kept as they were captured, so later edits and redactions don't apply to them
+    pub fn add(&mut self, image: &DynamicImage, hash: u64, limit: usize) -> Result<(), Error> {
+        let hash = format!("{:016x}", hash);
+        if limit == 0 || self.entries.last().map(|entry| &entry.hash) == Some(&hash) {
+            return Ok(());
+        }
+
+        match create_dir_all(&self.dir) {
+            Ok(_) => (),
+            Err(e) => return Err(Error::io(&self.dir, e)),
+        };
+
+        // Write the image and its thumbnail
+        let id = self
+            .entries
+            .iter()
+            .map(|entry| entry.id + 1)
+            .max()
+            .unwrap_or(0);
+        match History::write_png(&self.get_image_path(id), image) {
+            Ok(_) => (),
+            Err(e) => return Err(e),
+        };
+        let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+        match History::write_png(&self.get_thumbnail_path(id), &thumbnail) {
+            Ok(_) => (),
+            Err(e) => return Err(e),
+        };
+
+        self.entries.push(HistoryEntry {
+            id,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+            width: image.width(),
+            height: image.height(),
+            hash,
+        });
+
+        // Forget the oldest entries
+        while self.entries.len() > limit {
+            let oldest_id = self.entries[0].id;
+            match self.remove(oldest_id) {
+                Ok(_) => (),
+                Err(e) => return Err(e),
+            };
+        }
+
+        self.save_index()
+    }
+
+    /// Remove an entry and its files
+    pub fn remove(&mut self, id: u64) -> Result<(), Error> {
+        if self.get(id).is_none() {
+            return Err(Error::HistoryEntryNotFound(id));
+        }
+        self.entries.retain(|entry| entry.id != id);
+
+        // The files might already be gone, which is fine
+        let _ = remove_file(self.get_image_path(id));
+        let _ = remove_file(self.get_thumbnail_path(id));
+
+        self.save_index()
+    }
+
+    /// Load the full size image of an entry
+    pub fn load_image(&self, id: u64) -> Result<DynamicImage, Error> {
+        if self.get(id).is_none() {
+            return Err(Error::HistoryEntryNotFound(id));
+        }
+        match image::open(self.get_image_path(id)) {
+            Ok(image) => Ok(image),
+            Err(e) => Err(Error::io(self.get_image_path(id), e)),
+        }
+    }
+
+    /// Load the thumbnail of an entry as PNG bytes
+    pub fn load_thumbnail(&self, id: u64) -> Result<Vec<u8>, Error> {
+        if self.get(id).is_none() {
+            return Err(Error::HistoryEntryNotFound(id));
+        }
+        match read(self.get_thumbnail_path(id)) {
+            Ok(thumbnail) => Ok(thumbnail),
+            Err(e) => Err(Error::io(self.get_thumbnail_path(id), e)),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+/*
+ * Multi-size icon encoding
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{
+    error::Error,
+    settings::{ResizeFilter, ICON_SIZES},
+};
+use image::{
+    codecs::{
+        ico::{IcoEncoder, IcoFrame},
+        png::PngEncoder,
+    },
+    imageops::{overlay, FilterType},
+    DynamicImage, ImageEncoder, RgbaImage,
+};
+use std::io::Write;
+
+/***** Constants *****/
+/// The ICNS element types that hold a PNG of each size. Sizes without one (e.g. 24 and 48)
+/// can only be stored in legacy formats, so they're left out of ICNSs
+const ICNS_PNG_TYPES: [(u32, &[u8; 4]); 5] = [
+    (16, b"icp4"),
+    (32, b"icp5"),
+    (64, b"icp6"),
+    (128, b"ic07"),
+    (256, b"ic08"),
+];
+
+/***** Resampling *****/
+/// Get the `image` crate filter for a resize filter
+pub fn filter_type(filter: &ResizeFilter) -> FilterType {
+    match filter {
+        ResizeFilter::Nearest => FilterType::Nearest,
+        ResizeFilter::Triangle => FilterType::Triangle,
+        ResizeFilter::CatmullRom => FilterType::CatmullRom,
+        ResizeFilter::Gaussian => FilterType::Gaussian,
+        ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+    }
+}
+
+/// Get the valid icon sizes from a list of sizes, smallest first and without duplicates
+fn valid_sizes(sizes: &[u32]) -> Result<Vec<u32>, Error> {
+    let mut sizes: Vec<u32> = sizes
+        .iter()
+        .copied()
+        .filter(|size| ICON_SIZES.contains(size))
+        .collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+    match sizes.is_empty() {
+        true => Err(Error::EncodeFailed("No icon sizes were chosen".to_string())),
+        false => Ok(sizes),
+    }
+}
+
+/// Center an image on a transparent square, so it isn't stretched when resampled to an icon
+fn pad_to_square(image: &DynamicImage) -> RgbaImage {
+    let side = image.width().max(image.height());
+    let mut square = RgbaImage::new(side, side);
+    overlay(
+        &mut square,
+        &image.to_rgba8(),
+        ((side - image.width()) / 2) as i64,
+        ((side - image.height()) / 2) as i64,
+    );
+    square
+}
+
+/// Resample an image to a square icon of each size and encode them as PNGs
+fn encode_png_sizes(
+    image: &DynamicImage,
+    sizes: &[u32],
+    filter: &ResizeFilter,
+) -> Result<Vec<(u32, Vec<u8>)>, Error> {
+    let square = DynamicImage::ImageRgba8(pad_to_square(image));
+    let mut pngs = Vec::new();
+    for &size in sizes {
+        let resized = square.resize_exact(size, size, filter_type(filter));
+        let mut png = Vec::new();
+        match PngEncoder::new(&mut png).write_image(resized.as_bytes(), size, size, resized.color())
+        {
+            Ok(_) => (),
+            Err(e) => return Err(Error::EncodeFailed(e.to_string())),
+        };
+        pngs.push((size, png));
+    }
+    Ok(pngs)
+}
+
+/***** Encoding *****/
+/// Encode an image as an ICO holding a copy resampled to each size
+pub fn encode_ico<W: Write>(
+    image: &DynamicImage,
+    sizes: &[u32],
+    filter: &ResizeFilter,
+    writer: W,
+) -> Result<(), Error> {
+    let sizes = match valid_sizes(sizes) {
+        Ok(sizes) => sizes,
+        Err(e) => return Err(e),
+    };
+    let pngs = match encode_png_sizes(image, &sizes, filter) {
+        Ok(pngs) => pngs,
+        Err(e) => return Err(e),
+    };
+
+    let mut frames = Vec::new();
+    for (size, png) in pngs.iter() {
+        match IcoFrame::with_encoded(png.as_slice(), *size, *size, image::ColorType::Rgba8) {
+            Ok(frame) => frames.push(frame),
+            Err(e) => return Err(Error::EncodeFailed(e.to_string())),
+        };
+    }
+    match IcoEncoder::new(writer).encode_images(&frames) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::EncodeFailed(e.to_string())),
+    }
+}
+
+/// Encode an image as an ICNS holding a copy resampled to each size that ICNS supports
+pub fn encode_icns<W: Write>(
+    image: &DynamicImage,
+    sizes: &[u32],
+    filter: &ResizeFilter,
+    mut writer: W,
+) -> Result<(), Error> {
+    let sizes: Vec<u32> = match valid_sizes(sizes) {
+        Ok(sizes) => sizes
+            .into_iter()
+            .filter(|size| {
+                ICNS_PNG_TYPES
+                    .iter()
+                    .any(|(icns_size, _)| icns_size == size)
+            })
+            .collect(),
+        Err(e) => return Err(e),
+    };
+    if sizes.is_empty() {
+        return Err(Error::EncodeFailed(
+            "None of the chosen icon sizes can be stored in an ICNS".to_string(),
+        ));
+    }
+    let pngs = match encode_png_sizes(image, &sizes, filter) {
+        Ok(pngs) => pngs,
+        Err(e) => return Err(e),
+    };
+
+    // Each element is its type, its length including the 8 byte header, then the data
+    let mut elements = Vec::new();
+    for (size, png) in pngs.iter() {
+        let (_, element_type) = ICNS_PNG_TYPES
+            .iter()
+            .find(|(icns_size, _)| icns_size == size)
+            .unwrap();
+        elements.extend_from_slice(*element_type);
+        elements.extend_from_slice(&(png.len() as u32 + 8).to_be_bytes());
+        elements.extend_from_slice(png);
+    }
+
+    let mut icns = Vec::with_capacity(elements.len() + 8);
+    icns.extend_from_slice(b"icns");
+    icns.extend_from_slice(&(elements.len() as u32 + 8).to_be_bytes());
+    icns.extend_from_slice(&elements);
+    match writer.write_all(&icns) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::EncodeFailed(e.to_string())),
+    }
+}
@@ -0,0 +1,593 @@
+/*
+ * The current image and how it's encoded and saved
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{
+    adjustments, annotations,
+    clipboard::ClipboardSource,
+    edits::{self, EditStack},
+    encoder,
+    error::Error,
+    files,
+    output_cache::{OutputCache, OutputKey},
+    payloads,
+    progress::{Progress, ProgressWriter},
+    resolver, settings, template,
+};
+use image::{DynamicImage, ImageError, ImageFormat, RgbaImage};
+use std::{
+    borrow::Cow,
+    collections::hash_map::DefaultHasher,
+    fs::create_dir_all,
+    hash::{Hash, Hasher},
+    io::Cursor,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+/***** Image data *****/
+#[derive(Default)]
+pub struct ImageData {
+    /// The clipboard image encoded as a dynamic image, without the edits
+    pub clipboard_dynamic_image: DynamicImage,
+    /// The edits made to the image, which are applied when it's encoded
+    pub edits: EditStack,
+    /// Color adjustments applied after the edits, which the preview has too
+    pub adjustments: settings::Adjustments,
+    /// Shapes drawn over the edited image when it's saved or copied. The preview doesn't have
+    /// them, as the frontend draws them over it
+    pub annotations: Vec<payloads::Annotation>,
+    /// The image encoded in the formats it was saved, copied or previewed in lately
+    pub outputs: OutputCache,
+    /// A hash of the raw clipboard image, used to tell if the clipboard has a new image
+    pub clipboard_image_hash: u64,
+    /// Where the image came from, which is a bitmap unless set otherwise
+    pub clipboard_image_source: payloads::ImageSource,
+    /// Incremented whenever the image, its edits, adjustments or annotations change, so the
+    /// frontend can tell previews apart and outputs can be told apart from older ones
+    pub revision: u64,
+}
+impl ImageData {
+    /// Copy the image and everything done to it, sharing the encoded outputs. This way it can be
+    /// encoded (e.g. saved) without keeping the state locked
+    pub fn snapshot(&self) -> ImageData {
+        ImageData {
+            clipboard_dynamic_image: self.clipboard_dynamic_image.clone(),
+            edits: self.edits.clone(),
+            adjustments: self.adjustments.clone(),
+            annotations: self.annotations.clone(),
+            outputs: self.outputs.clone(),
+            clipboard_image_hash: self.clipboard_image_hash,
+            clipboard_image_source: self.clipboard_image_source.clone(),
+            revision: self.revision,
+        }
+    }
+
+    /// Replace the current image with a bitmap read from the clipboard
+    pub fn set_clipboard_image(&mut self, image_buf: RgbaImage) {
+        self.clipboard_image_hash = hash_image(&image_buf);
+        self.clipboard_dynamic_image = DynamicImage::ImageRgba8(image_buf);
+        self.clipboard_image_source = payloads::ImageSource::Bitmap;
+        self.edits.reset();
+        self.adjustments = settings::Adjustments::default();
+        self.annotations.clear();
+        self.changed();
+    }
+
+    /// Move to a new revision, as the image or something done to it changed. The outputs encoded
+    /// before are out of date
+    fn changed(&mut self) {
+        self.revision += 1;
+        self.outputs.invalidate(self.revision);
+    }
+
+    /// Get the image with the edits and color adjustments applied
+    pub fn edited_image(&self) -> Cow<'_, DynamicImage> {
+        let edited_image = self.edits.apply(&self.clipboard_dynamic_image);
+        match self.adjustments.is_identity() {
+            true => edited_image,
+            false => Cow::Owned(adjustments::apply_adjustments(
+                &edited_image,
+                &self.adjustments,
+            )),
+        }
+    }
+
+    /// Get the image with the edits applied and the annotations drawn on, as it's saved
+    pub fn output_image(&self) -> Result<Cow<'_, DynamicImage>, Error> {
+        let edited_image = self.edited_image();
+        if self.annotations.is_empty() {
+            return Ok(edited_image);
+        }
+        match annotations::draw_annotations(&edited_image, &self.annotations) {
+            Ok(annotated) => Ok(Cow::Owned(annotated)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Replace the annotations, if they can all be drawn
+    pub fn set_annotations(
+        &mut self,
+        new_annotations: Vec<payloads::Annotation>,
+    ) -> Result<(), Error> {
+        // Nothing was pasted yet
+        if self.clipboard_dynamic_image.width() == 0 || self.clipboard_dynamic_image.height() == 0 {
+            return Err(Error::NoImageInClipboard);
+        }
+
+        for annotation in &new_annotations {
+            match annotations::validate_annotation(annotation) {
+                Ok(_) => (),
+                Err(e) => return Err(e),
+            };
+        }
+        self.annotations = new_annotations;
+        self.changed();
+        Ok(())
+    }
+
+    /// Replace the color adjustments, if they're in range
+    pub fn set_adjustments(&mut self, new_adjustments: settings::Adjustments) -> Result<(), Error> {
+        // Nothing was pasted yet
+        if self.clipboard_dynamic_image.width() == 0 || self.clipboard_dynamic_image.height() == 0 {
+            return Err(Error::NoImageInClipboard);
+        }
+
+        match adjustments::validate_adjustments(&new_adjustments) {
+            Ok(_) => (),
+            Err(e) => return Err(e),
+        };
+        self.adjustments = new_adjustments;
+        self.changed();
+        Ok(())
+    }
+
+    /// Add an edit to the image, if it can be applied
+    pub fn edit(&mut self, edit: payloads::Edit) -> Result<(), Error> {
+        // Nothing was pasted yet
+        if self.clipboard_dynamic_image.width() == 0 || self.clipboard_dynamic_image.height() == 0 {
+            return Err(Error::NoImageInClipboard);
+        }
+
+        let (width, height) = self.edits.edited_size(&self.clipboard_dynamic_image);
+        match edits::validate_edit(&edit, width, height) {
+            Ok(_) => (),
+            Err(e) => return Err(e),
+        };
+        self.edits.push(edit);
+        self.changed();
+        Ok(())
+    }
+
+    /// Undo the last edit. Returns whether there was one
+    pub fn undo_edit(&mut self) -> bool {
+        let changed = self.edits.undo();
+        if changed {
+            self.changed();
+        }
+        changed
+    }
+
+    /// Redo the last undone edit. Returns whether there was one
+    pub fn redo_edit(&mut self) -> bool {
+        let changed = self.edits.redo();
+        if changed {
+            self.changed();
+        }
+        changed
+    }
+
+    /// Forget every edit, including the undone ones
+    pub fn reset_edits(&mut self) {
+        self.edits.reset();
+        self.changed();
+    }
+
+    /// Replace the current image with the one in a clipboard source. Content that isn't a bitmap
+    /// (e.g. a copied file) is resolved into an image, with SVGs rasterized at the DPI given
+    pub fn read_clipboard(
+        &mut self,
+        clipboard: &mut dyn ClipboardSource,
+        svg_dpi: f32,
+    ) -> Result<(), Error> {
+        match resolver::resolve_image(clipboard, svg_dpi) {
+            Ok((image_buf, source)) => {
+                self.set_clipboard_image(image_buf);
+                self.clipboard_image_source = source;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Trim the border off the image (e.g. a screenshot's margin) by adding a trim edit, if it has
+    /// one. Returns whether it did, so reading the clipboard can trim automatically
+    pub fn auto_trim(&mut self, tolerance: u8) -> bool {
+        let has_border = edits::trim_bounds(&self.edited_image().to_rgba8(), tolerance).is_some();
+        if has_border {
+            self.edits.push(payloads::Edit::Trim { tolerance });
+            self.changed();
+        }
+        has_border
+    }
+
+    /// Replace the current image with an image file in any format the `image` crate can decode
+    pub fn open_file(&mut self, path: &str) -> Result<(), Error> {
+        let image = match image::open(path) {
+            Ok(image) => image,
+            Err(ImageError::IoError(e)) => return Err(Error::io(path, e)),
+            Err(e) => {
+                return Err(Error::DecodeFailed {
+                    path: path.to_string(),
+                    message: e.to_string(),
+                })
+            }
+        };
+        // Everything else works with RGBA images, like the ones from the clipboard
+        self.set_clipboard_image(image.into_rgba8());
+        self.clipboard_image_source = payloads::ImageSource::File;
+        Ok(())
+    }
+
+    /// Put the current image in a clipboard source. If a format is given, the image is copied as
+    /// it'd look once saved in it, e.g. with its transparency flattened for JPEGs.
+    /// The hash of the image written is returned
+    pub fn write_clipboard(
+        &self,
+        clipboard: &mut dyn ClipboardSource,
+        format: Option<settings::SaveFormat>,
+        options: &settings::EncoderOptions,
+    ) -> Result<u64, Error> {
+        let image_buf = match self.clipboard_image(format, options) {
+            Ok(image_buf) => image_buf,
+            Err(e) => return Err(e),
+        };
+        match clipboard.set_image(&image_buf) {
+            Ok(_) => Ok(hash_image(&image_buf)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the current image as `write_clipboard` puts it in a clipboard. This is the slow part,
+    /// so it can be done (e.g. from a snapshot) without keeping the clipboard locked
+    pub fn clipboard_image(
+        &self,
+        format: Option<settings::SaveFormat>,
+        options: &settings::EncoderOptions,
+    ) -> Result<RgbaImage, Error> {
+        // Nothing was pasted yet
+        if self.clipboard_dynamic_image.width() == 0 || self.clipboard_dynamic_image.height() == 0 {
+            return Err(Error::NoImageInClipboard);
+        }
+
+        let image = match format {
+            Some(format) => match self.converted_image(format, options) {
+                Ok(image) => image,
+                Err(e) => return Err(e),
+            },
+            None => match self.output_image() {
+                Ok(image) => image.into_owned(),
+                Err(e) => return Err(e),
+            },
+        };
+        // Clipboards only take 8 bit RGBA, while decoded images can be e.g. RGB or 16 bit
+        Ok(image.into_rgba8())
+    }
+
+    /// Get the current image as it'd look once saved in a format.
+    /// Formats the `image` crate can't decode again (e.g. ICNS) only have their transparency flattened
+    pub fn converted_image(
+        &self,
+        format: settings::SaveFormat,
+        options: &settings::EncoderOptions,
+    ) -> Result<DynamicImage, Error> {
+        let encoded = match self.encoded(format.clone(), options) {
+            Ok(encoded) => encoded,
+            Err(e) => return Err(e),
+        };
+
+        let decoded = match ImageFormat::from_extension(format.extension()) {
+            Some(image_format) => image::load_from_memory_with_format(&encoded, image_format).ok(),
+            None => None,
+        };
+        let output_image = match decoded {
+            Some(decoded) => return Ok(decoded),
+            None => match self.output_image() {
+                Ok(output_image) => output_image,
+                Err(e) => return Err(e),
+            },
+        };
+        match format.supports_alpha() {
+            true => Ok(output_image.into_owned()),
+            false => Ok(encoder::flatten_transparency(&output_image, options)),
+        }
+    }
+
+    /// Encode the current image as a PNG for displaying, which the frontend loads by revision.
+    /// The annotations are left out, as the frontend draws them over it. It's compressed quickly,
+    /// as it's never written to a file
+    pub fn preview_png(&self) -> Result<Arc<Vec<u8>>, Error> {
+        self.encode(
+            settings::SaveFormat::Png,
+            &preview_options(),
+            false,
+            &Progress::default(),
+        )
+    }
+
+    /// Get the preview PNG of the image in a state like `preview_png`, without keeping the state
+    /// locked while it's encoded, so saves and edits aren't blocked by it. A cached preview is
+    /// returned straight from the state, and only otherwise is the image snapshotted to encode.
+    /// If a revision is given and the image moved on from it, `Error::OutdatedRevision` is
+    /// returned, as its preview is gone
+    pub fn preview_png_of(
+        state: &RwLock<ImageData>,
+        revision: Option<u64>,
+    ) -> Result<Arc<Vec<u8>>, Error> {
+        let (key, snapshot) = {
+            let image_data = match state.read() {
+                Ok(image_data) => image_data,
+                Err(_) => return Err(Error::LockPoisoned("image".to_string())),
+            };
+            match revision {
+                Some(revision) if revision != image_data.revision => {
+                    return Err(Error::OutdatedRevision(revision))
+                }
+                _ => (),
+            };
+            let key = image_data.output_key(settings::SaveFormat::Png, &preview_options(), false);
+            if let Some(png) = image_data.outputs.get(&key) {
+                return Ok(png);
+            }
+            (key, image_data.snapshot())
+        };
+        snapshot.encode_uncached(key, &Progress::default())
+    }
+
+    /// Encode the image with the format and options specified and write it to a file
+    pub fn save_to_file(
+        &self,
+        path: &str,
+        format: settings::SaveFormat,
+        options: &settings::EncoderOptions,
+    ) -> Result<(), Error> {
+        self.save_to_file_with_progress(path, format, options, &Progress::default())
+    }
+
+    /// Save the image to a file like `save_to_file`, reporting the progress. If it's canceled, the
+    /// file is left untouched and `Error::Canceled` is returned
+    pub fn save_to_file_with_progress(
+        &self,
+        path: &str,
+        format: settings::SaveFormat,
+        options: &settings::EncoderOptions,
+        progress: &Progress,
+    ) -> Result<(), Error> {
+        // Convert the buffer to the save format
+        let encoded = match self.encode(format, options, true, progress) {
+            Ok(encoded) => encoded,
+            Err(e) => return Err(e),
+        };
+
+        // Write to file, unless it was canceled while encoding finished
+        match progress.check() {
+            Ok(_) => (),
+            Err(e) => return Err(e),
+        };
+        progress.report(payloads::SaveStage::Writing, encoded.len() as u64);
+        // The folder might not exist yet, e.g. one named by a placeholder
+        if let Some(dir_path) = Path::new(path).parent() {
+            match create_dir_all(dir_path) {
+                Ok(_) => (),
+                Err(e) => return Err(Error::io(dir_path, e)),
+            };
+        }
+        match files::write_atomic(Path::new(path), &encoded) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Error::io(path, e)),
+        }
+    }
+
+    /// Get the values to expand a save path template with for this image, whose size is the one
+    /// it's saved at with the encoder options
+    pub fn template_values<'a>(
+        &self,
+        counter: u64,
+        format: &'a settings::SaveFormat,
+        options: &settings::EncoderOptions,
+    ) -> template::TemplateValues<'a> {
+        let edited_image = self.edited_image();
+        let (width, height) = options
+            .resize
+            .output_size(edited_image.width(), edited_image.height());
+        template::TemplateValues {
+            now: chrono::Local::now(),
+            counter,
+            width,
+            height,
+            hash: self.clipboard_image_hash,
+            format,
+        }
+    }
+
+    /// Expand a save path template for this image and save it there, following the collision
+    /// policy and encoder options in the settings. A confirmed path is one the user agreed to
+    /// overwrite. The `{counter}` is kept in the counter file (see `get_counter_path`), and only
+    /// incremented if it was used and the image was saved
+    pub fn save_with_template(
+        &self,
+        path_template: &str,
+        format: settings::SaveFormat,
+        current_settings: &settings::Settings,
+        confirmed_path: Option<String>,
+        counter_path: &Path,
+    ) -> Result<payloads::SaveOutcome, Error> {
+        self.save_with_progress(
+            path_template,
+            format,
+            current_settings,
+            confirmed_path,
+            counter_path,
+            &Progress::default(),
+        )
+    }
+
+    /// Save the image like `save_with_template`, reporting the progress and stopping if it's
+    /// canceled
+    pub fn save_with_progress(
+        &self,
+        path_template: &str,
+        format: settings::SaveFormat,
+        current_settings: &settings::Settings,
+        confirmed_path: Option<String>,
+        counter_path: &Path,
+        progress: &Progress,
+    ) -> Result<payloads::SaveOutcome, Error> {
+        // Nothing was pasted yet
+        if self.clipboard_dynamic_image.width() == 0 || self.clipboard_dynamic_image.height() == 0 {
+            return Err(Error::NoImageInClipboard);
+        }
+
+        let counter = template::load_counter(counter_path);
+        let (path, collision_policy) = match confirmed_path {
+            Some(confirmed_path) => (confirmed_path, settings::CollisionPolicy::Overwrite),
+            None => (
+                template::expand_template(
+                    path_template,
+                    &self.template_values(counter, &format, &current_settings.encoder_options),
+                ),
+                current_settings.collision_policy.clone(),
+            ),
+        };
+
+        // Handle the file already existing
+        let path = match Path::new(&path).exists() {
+            true => match collision_policy {
+                settings::CollisionPolicy::Ask => return Ok(payloads::SaveOutcome::Exists(path)),
+                settings::CollisionPolicy::Skip => return Ok(payloads::SaveOutcome::Skipped(path)),
+                settings::CollisionPolicy::Overwrite => path,
+                settings::CollisionPolicy::Rename => files::find_free_path(Path::new(&path))
+                    .to_string_lossy()
+                    .to_string(),
+            },
+            false => path,
+        };
+
+        match self.save_to_file_with_progress(
+            &path,
+            format,
+            &current_settings.encoder_options,
+            progress,
+        ) {
+            Ok(_) => (),
+            Err(e) => return Err(e),
+        };
+        if template::uses_counter(path_template) {
+            match template::save_counter(counter_path, counter + 1) {
+                Ok(_) => (),
+                Err(e) => return Err(e),
+            };
+        }
+
+        Ok(payloads::SaveOutcome::Saved(path))
+    }
+
+    /// Encode the image in the format specified, applying the edits and drawing the annotations
+    pub fn encoded(
+        &self,
+        format: settings::SaveFormat,
+        options: &settings::EncoderOptions,
+    ) -> Result<Arc<Vec<u8>>, Error> {
+        self.encode(format, options, true, &Progress::default())
+    }
+
+    /// Encode the edited image, with or without the annotations. It's only encoded if it isn't
+    /// in the outputs already, in which case no progress is reported
+    fn encode(
+        &self,
+        format: settings::SaveFormat,
+        options: &settings::EncoderOptions,
+        with_annotations: bool,
+        progress: &Progress,
+    ) -> Result<Arc<Vec<u8>>, Error> {
+        let key = self.output_key(format, options, with_annotations);
+        match self.outputs.get(&key) {
+            Some(output) => Ok(output),
+            None => self.encode_uncached(key, progress),
+        }
+    }
+
+    /// Get the key the image's output in a format is cached with
+    fn output_key(
+        &self,
+        format: settings::SaveFormat,
+        options: &settings::EncoderOptions,
+        with_annotations: bool,
+    ) -> OutputKey {
+        OutputKey {
+            revision: self.revision,
+            format,
+            options: options.clone(),
+            with_annotations,
+        }
+    }
+
+    /// Encode the edited image without looking in the outputs first, then keep it in them
+    fn encode_uncached(&self, key: OutputKey, progress: &Progress) -> Result<Arc<Vec<u8>>, Error> {
+        // The vector must be wrapped in a cursor so it satisfies the `Seek` trait
+        let mut cursor = Cursor::new(Vec::new());
+        progress.report(payloads::SaveStage::Rendering, 0);
+        let image = match key.with_annotations {
+            true => self.output_image(),
+            false => Ok(self.edited_image()),
+        };
+        let result = match (image, progress.check()) {
+            (Ok(image), Ok(_)) => {
+                progress.report(payloads::SaveStage::Encoding, 0);
+                let mut writer = ProgressWriter::new(&mut cursor, progress);
+                encoder::encode_image(&image, &key.format, &key.options, &mut writer)
+            }
+            (Err(e), _) | (_, Err(e)) => Err(e),
+        };
+        match result {
+            Ok(_) => {
+                let output = Arc::new(cursor.into_inner());
+                self.outputs.insert(key, output.clone());
+                Ok(output)
+            }
+            // Canceling makes writing fail, which isn't the encoder's fault
+            Err(_) if progress.is_canceled() => Err(Error::Canceled),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// The encoder options previews are encoded with
+fn preview_options() -> settings::EncoderOptions {
+    settings::EncoderOptions {
+        png_compression: settings::PngCompression::Fast,
+        ..settings::EncoderOptions::default()
+    }
+}
+
+/***** Opening *****/
+/// The file extensions of every format that can be opened
+pub fn openable_extensions() -> Vec<&'static str> {
+    ImageFormat::all()
+        .filter(ImageFormat::reading_enabled)
+        .flat_map(ImageFormat::extensions_str)
+        .copied()
+        .collect()
+}
+
+/***** Hashing *****/
+/// Hash the dimensions and pixels of an image
+pub fn hash_image(image_buf: &RgbaImage) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    image_buf.dimensions().hash(&mut hasher);
+    image_buf.as_raw().hash(&mut hasher);
+    hasher.finish()
+}
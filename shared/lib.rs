@@ -0,0 +1,52 @@
+/*
+ * The image pipeline shared by the backend and the command line
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+// Errors are matched and returned explicitly, like in the backend
+#![allow(clippy::question_mark)]
+/* Modules */
+pub mod adjustments;
+pub mod annotations;
+pub mod clipboard;
+pub mod edits;
+pub mod encoder;
+pub mod error;
+pub mod files;
+pub mod history;
+pub mod icon;
+pub mod image_data;
+pub mod output_cache;
+pub mod payloads;
+pub mod progress;
+pub mod resolver;
+pub mod settings;
+pub mod settings_file;
+pub mod template;
+/* Imports */
+use std::path::PathBuf;
+
+/***** Paths *****/
+/// Get the directory where the settings and history are kept
+pub fn get_data_dir() -> PathBuf {
+    match dirs_next::data_local_dir() {
+        Some(local_data_dir) => local_data_dir.join("clipboard-image-saver"),
+        None => PathBuf::from("."),
+    }
+}
+
+/// Get the settings path
+pub fn get_settings_path() -> PathBuf {
+    get_data_dir().join("settings.json")
+}
+
+/// Get the directory holding the history index and images
+pub fn get_history_dir() -> PathBuf {
+    get_data_dir().join("history")
+}
+
+/// Get the path of the file keeping the `{counter}` save path placeholder's value
+pub fn get_counter_path() -> PathBuf {
+    get_data_dir().join("save_counter")
+}
@@ -0,0 +1,132 @@
+/*
+ * A small cache of encoded outputs, so an image isn't encoded again in the same format
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{payloads::CacheStats, settings};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/***** Constants *****/
+/// How many encoded outputs are kept
+const MAX_ENTRIES: usize = 8;
+/// How many bytes of encoded outputs are kept, as big images in uncompressed formats (e.g. BMP)
+/// add up quickly. The most recent output is kept even if it's bigger
+const MAX_BYTES: usize = 256 * 1024 * 1024;
+
+/***** Keys *****/
+/// What an output was encoded from and how
+#[derive(PartialEq, Clone)]
+pub struct OutputKey {
+    pub revision: u64,
+    pub format: settings::SaveFormat,
+    pub options: settings::EncoderOptions,
+    /// Whether the annotations were drawn, which they aren't on the preview
+    pub with_annotations: bool,
+}
+
+/***** Cache *****/
+#[derive(Default)]
+struct Outputs {
+    /// The most recently used output first
+    entries: Vec<(OutputKey, Arc<Vec<u8>>)>,
+    /// The revision the outputs are of. Outputs of older ones are never used again
+    revision: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// A least recently used cache of encoded outputs, keyed by the image revision, the format and
+/// the encoder options. Clones share the same outputs, so saving a snapshot of the image fills
+/// the cache of the state it was taken from
+#[derive(Clone, Default)]
+pub struct OutputCache(Arc<Mutex<Outputs>>);
+impl OutputCache {
+    /// Lock the outputs. A panic while they were locked can't leave them half changed, so a
+    /// poisoned lock is used anyway
+    fn outputs(&self) -> MutexGuard<'_, Outputs> {
+        match self.0.lock() {
+            Ok(outputs) => outputs,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// Get an encoded output if it's cached, counting it as a hit or a miss
+    pub fn get(&self, key: &OutputKey) -> Option<Arc<Vec<u8>>> {
+        let mut outputs = self.outputs();
+        match outputs
+            .entries
+            .iter()
+            .position(|(entry_key, _)| entry_key == key)
+        {
+            Some(index) => {
+                let entry = outputs.entries.remove(index);
+                let output = entry.1.clone();
+                outputs.entries.insert(0, entry);
+                outputs.hits += 1;
+                Some(output)
+            }
+            None => {
+                outputs.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Keep an encoded output, dropping the least recently used ones once the cache is full.
+    /// Outputs of older revisions (e.g. a save that finished after an edit) aren't kept
+    pub fn insert(&self, key: OutputKey, output: Arc<Vec<u8>>) {
+        let mut outputs = self.outputs();
+        if key.revision < outputs.revision {
+            return;
+        }
+        if key.revision > outputs.revision {
+            outputs.entries.clear();
+            outputs.revision = key.revision;
+        }
+        outputs.entries.retain(|(entry_key, _)| *entry_key != key);
+        outputs.entries.insert(0, (key, output));
+
+        let mut bytes = 0;
+        let kept = outputs
+            .entries
+            .iter()
+            .take(MAX_ENTRIES)
+            .take_while(|(_, output)| {
+                bytes += output.len();
+                bytes <= MAX_BYTES
+            })
+            .count();
+        outputs.entries.truncate(kept.max(1));
+    }
+
+    /// Forget the outputs if the image changed since they were encoded
+    pub fn invalidate(&self, revision: u64) {
+        let mut outputs = self.outputs();
+        if revision > outputs.revision {
+            outputs.entries.clear();
+            outputs.revision = revision;
+        }
+    }
+
+    /// Forget every output, but not the hits and misses
+    pub fn clear(&self) {
+        self.outputs().entries.clear();
+    }
+
+    /// Get how well the cache is doing, e.g. for a debug view
+    pub fn stats(&self) -> CacheStats {
+        let outputs = self.outputs();
+        CacheStats {
+            hits: outputs.hits,
+            misses: outputs.misses,
+            entries: outputs.entries.len() as u64,
+            bytes: outputs
+                .entries
+                .iter()
+                .map(|(_, output)| output.len() as u64)
+                .sum(),
+        }
+    }
+}
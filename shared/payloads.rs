@@ -0,0 +1,248 @@
+/*
+ * Shared types sent between the backend and the frontend
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+#![allow(dead_code, unused_imports, clippy::derive_partial_eq_without_eq)]
+/* Imports */
+use serde::{Deserialize, Serialize};
+
+/***** History *****/
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Unique ID of the entry, also used for its file names
+    pub id: u64,
+    /// When the image was captured, in seconds since the Unix epoch
+    pub timestamp: u64,
+    pub width: u32,
+    pub height: u32,
+    /// Hash of the image pixels as hex (JavaScript can't hold a u64)
+    pub hash: String,
+}
+
+/***** Image *****/
+/// Where the current image came from
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub enum ImageSource {
+    /// A bitmap in the clipboard
+    Bitmap,
+    /// A data URL in the clipboard
+    DataUrl,
+    /// A file copied in a file manager
+    FileList,
+    /// SVG markup in the clipboard
+    Svg,
+    /// An `<img>` tag in HTML copied from a browser
+    Html,
+    /// A file opened from disk or dropped onto the window
+    File,
+    /// An image restored from the history
+    History,
+}
+impl Default for ImageSource {
+    fn default() -> ImageSource {
+        ImageSource::Bitmap
+    }
+}
+impl ImageSource {
+    /// Describe the source for showing to the user, e.g. "Pasted from a copied file"
+    pub fn description(&self) -> &'static str {
+        match self {
+            ImageSource::Bitmap => "a copied image",
+            ImageSource::DataUrl => "a copied data URL",
+            ImageSource::FileList => "a copied file",
+            ImageSource::Svg => "copied SVG markup",
+            ImageSource::Html => "an image in copied HTML",
+            ImageSource::File => "an opened file",
+            ImageSource::History => "the history",
+        }
+    }
+}
+
+/// An edit made to the current image, which is only applied when it's encoded
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub enum Edit {
+    /// Crop to a rectangle, in pixels of the image as edited so far
+    Crop {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Rotate clockwise by 90 degrees
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    /// Rotate clockwise by any angle, growing the image to fit
+    Rotate {
+        degrees: f32,
+    },
+    /// Crop off the rows and columns around the edges that match the top left pixel's color, or
+    /// are fully transparent. Channels can differ from the color by up to the tolerance
+    Trim {
+        tolerance: u8,
+    },
+    /// Add a border of a hex color like "#ff00ff", or "#ff00ff00" with alpha, around the image
+    Pad {
+        size: u32,
+        color: String,
+    },
+    /// Hide a rectangle (e.g. a token in a screenshot), in pixels of the image as edited so far.
+    /// This replaces the pixels, so nothing under it is left in the saved image
+    Redact {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        style: RedactStyle,
+    },
+}
+
+/// How a redacted rectangle is hidden
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum RedactStyle {
+    /// Filled with black, which is the only style that leaves nothing to guess from
+    Fill,
+    /// Blurred so it's unreadable
+    Blur,
+    /// Averaged into big blocks
+    Pixelate,
+}
+
+/***** Annotations *****/
+/// How opaque highlights are, so what's under them can still be read
+pub const HIGHLIGHT_OPACITY: f32 = 0.4;
+
+/// A shape drawn over the image, in pixels of the image as edited
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub enum Shape {
+    /// A line with an arrowhead at `to`
+    Arrow { from: [f32; 2], to: [f32; 2] },
+    /// The outline of a rectangle
+    Rectangle {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    /// The outline of the ellipse inside of a rectangle
+    Ellipse {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    /// A line through the points drawn with the mouse
+    Freehand { points: Vec<[f32; 2]> },
+    /// A line of text, starting on its baseline at `x` and `y`
+    Text { x: f32, y: f32, text: String },
+    /// A translucent rectangle, like a highlighter pen
+    Highlight {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+}
+
+impl Shape {
+    /// Get where an arrow's line ends and the corners of its head, so the frontend and backend
+    /// draw it the same. The line stops inside of the head, so its round end doesn't poke out of
+    /// the tip. Arrows shorter than a pixel have nothing to draw
+    pub fn arrow_geometry(
+        from: [f32; 2],
+        to: [f32; 2],
+        size: f32,
+    ) -> Option<([f32; 2], [[f32; 2]; 3])> {
+        let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length < 1.0 {
+            return None;
+        }
+        let head_length = (size * 4.0).max(10.0).min(length);
+        let (unit_x, unit_y) = (dx / length, dy / length);
+        let (base_x, base_y) = (to[0] - unit_x * head_length, to[1] - unit_y * head_length);
+        let half_width = head_length / 2.0;
+
+        Some((
+            [base_x + unit_x * half_width, base_y + unit_y * half_width],
+            [
+                to,
+                [base_x - unit_y * half_width, base_y + unit_x * half_width],
+                [base_x + unit_y * half_width, base_y - unit_x * half_width],
+            ],
+        ))
+    }
+}
+
+/// A shape in a color, which is drawn onto the image when it's saved or copied
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub shape: Shape,
+    /// A hex color like "#ff0000"
+    pub color: String,
+    /// The width of lines, or the size of text, in pixels
+    pub size: f32,
+}
+
+/// Information about the current image
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    /// Whether any pixel is at least partly transparent
+    pub has_transparency: bool,
+    pub source: ImageSource,
+    /// Whether there are edits to undo or redo
+    pub can_undo: bool,
+    pub can_redo: bool,
+    /// The annotations drawn over the image
+    pub annotations: Vec<Annotation>,
+}
+
+/***** Saving *****/
+/// What happened when saving an image, with the path it was (or would have been) saved to
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub enum SaveOutcome {
+    Saved(String),
+    /// The file already exists and the user should be asked whether to overwrite it
+    Exists(String),
+    /// The file already exists and the collision policy is to skip it
+    Skipped(String),
+}
+
+/// What a running save is doing
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum SaveStage {
+    /// Applying the edits, adjustments and annotations
+    Rendering,
+    /// Encoding into the save format
+    Encoding,
+    /// Writing the encoded image to the file
+    Writing,
+    /// Done, whether it was saved, failed or was canceled
+    Finished,
+}
+
+/// How far along a save is, sent to the frontend while it runs
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct SaveProgress {
+    /// Which save this is, as more than one can run at once
+    pub job_id: u64,
+    pub stage: SaveStage,
+    /// How many bytes were encoded so far
+    pub bytes_encoded: u64,
+}
+
+/// How often encoded outputs were reused instead of encoded again, for the debug view
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// How many outputs are cached, and their size in bytes
+    pub entries: u64,
+    pub bytes: u64,
+}
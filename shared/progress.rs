@@ -0,0 +1,110 @@
+/*
+ * Reporting the progress of saves and canceling them
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{error::Error, payloads::SaveStage};
+use std::{
+    io::{Error as IoError, ErrorKind, Result as IoResult, Seek, SeekFrom, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/***** Constants *****/
+/// How many bytes are encoded between progress reports, so the frontend isn't flooded
+const REPORT_INTERVAL: u64 = 256 * 1024;
+
+/***** Progress *****/
+/// Reports how far along a save is, and lets it be canceled from another thread.
+/// Clones share the same cancellation
+#[derive(Clone, Default)]
+pub struct Progress {
+    canceled: Arc<AtomicBool>,
+    /// Called with the stage and the bytes encoded so far
+    on_progress: Option<Arc<dyn Fn(SaveStage, u64) + Send + Sync>>,
+}
+impl Progress {
+    /// Make a progress that's reported to a function
+    pub fn new(on_progress: impl Fn(SaveStage, u64) + Send + Sync + 'static) -> Progress {
+        Progress {
+            canceled: Arc::default(),
+            on_progress: Some(Arc::new(on_progress)),
+        }
+    }
+
+    /// Stop the save at the next chance it gets. The file isn't touched once it's canceled
+    pub fn cancel(&self) {
+        self.canceled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.canceled.load(Ordering::Relaxed)
+    }
+
+    /// Report the stage the save is at
+    pub fn report(&self, stage: SaveStage, bytes_encoded: u64) {
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(stage, bytes_encoded);
+        }
+    }
+
+    /// Get `Error::Canceled` if the save was canceled, to stop between stages
+    pub fn check(&self) -> Result<(), Error> {
+        match self.is_canceled() {
+            true => Err(Error::Canceled),
+            false => Ok(()),
+        }
+    }
+}
+
+/***** Writer *****/
+/// A writer that reports how many bytes were encoded into it, and fails once the save is
+/// canceled so the encoder stops
+pub struct ProgressWriter<'a, W> {
+    writer: W,
+    progress: &'a Progress,
+    bytes_written: u64,
+    last_reported: u64,
+}
+impl<'a, W> ProgressWriter<'a, W> {
+    pub fn new(writer: W, progress: &'a Progress) -> ProgressWriter<'a, W> {
+        ProgressWriter {
+            writer,
+            progress,
+            bytes_written: 0,
+            last_reported: 0,
+        }
+    }
+}
+impl<'a, W: Write> Write for ProgressWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        if self.progress.is_canceled() {
+            // Not `Interrupted`, as writers retry those
+            return Err(IoError::new(ErrorKind::Other, "Canceled"));
+        }
+        let written = match self.writer.write(buf) {
+            Ok(written) => written,
+            Err(e) => return Err(e),
+        };
+        self.bytes_written += written as u64;
+        if self.bytes_written - self.last_reported >= REPORT_INTERVAL {
+            self.last_reported = self.bytes_written;
+            self.progress
+                .report(SaveStage::Encoding, self.bytes_written);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        self.writer.flush()
+    }
+}
+impl<'a, W: Seek> Seek for ProgressWriter<'a, W> {
+    fn seek(&mut self, position: SeekFrom) -> IoResult<u64> {
+        self.writer.seek(position)
+    }
+}
@@ -0,0 +1,316 @@
+/*
+ * Resolving clipboard content that isn't a bitmap into an image
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{clipboard::ClipboardSource, error::Error, payloads::ImageSource};
+use image::RgbaImage;
+use std::path::{Path, PathBuf};
+
+/***** Constants *****/
+/// The DPI SVGs are drawn at when their size isn't scaled
+pub const SVG_BASE_DPI: f32 = 96.0;
+
+/***** Resolver chain *****/
+/// Resolves text into an image, giving `None` if the text isn't the kind it resolves
+type Resolver = fn(&str, f32) -> Option<Result<RgbaImage, Error>>;
+
+/// Get an image from a clipboard source, trying each representation of its content in turn:
+/// a bitmap, then text holding a data URL, a list of files, SVG markup or HTML `<img>` tags.
+/// `arboard` only gives bitmaps and plain text, so the other representations are read from the text.
+/// SVGs are rasterized at the DPI given
+pub fn resolve_image(
+    clipboard: &mut dyn ClipboardSource,
+    svg_dpi: f32,
+) -> Result<(RgbaImage, ImageSource), Error> {
+    match clipboard.get_image() {
+        Ok(image_buf) => return Ok((image_buf, ImageSource::Bitmap)),
+        Err(Error::NoImageInClipboard) => (),
+        Err(e) => return Err(e),
+    };
+    let text = match clipboard.get_text() {
+        Ok(text) => text,
+        Err(e) => return Err(e),
+    };
+    resolve_text(&text, svg_dpi)
+}
+
+/// Get an image from clipboard text, trying each kind of text that can point to or hold an image.
+/// If text was recognized but couldn't be decoded (e.g. a broken data URL), that error is returned
+pub fn resolve_text(text: &str, svg_dpi: f32) -> Result<(RgbaImage, ImageSource), Error> {
+    let resolvers: [(ImageSource, Resolver); 4] = [
+        (ImageSource::DataUrl, resolve_data_url),
+        (ImageSource::FileList, resolve_file_list),
+        (ImageSource::Svg, resolve_svg),
+        (ImageSource::Html, resolve_html),
+    ];
+
+    let mut first_error = None;
+    for (source, resolver) in resolvers {
+        match resolver(text.trim(), svg_dpi) {
+            Some(Ok(image_buf)) => return Ok((image_buf, source)),
+            Some(Err(e)) => {
+                first_error.get_or_insert(e);
+            }
+            None => (),
+        }
+    }
+    Err(first_error.unwrap_or(Error::NoImageInClipboard))
+}
+
+/***** Resolvers *****/
+/// Resolve a `data:image/...` URL, either base64 or percent encoded
+fn resolve_data_url(text: &str, svg_dpi: f32) -> Option<Result<RgbaImage, Error>> {
+    let (header, data) = text.strip_prefix("data:image/")?.split_once(',')?;
+    let data = match header.ends_with(";base64") {
+        true => match base64::decode(data.trim()) {
+            Ok(data) => data,
+            Err(e) => return Some(Err(Error::ClipboardDecodeFailed(e.to_string()))),
+        },
+        false => percent_decode(data),
+    };
+
+    let decoded = match header.starts_with("svg+xml") {
+        true => rasterize_svg(&data, svg_dpi),
+        false => decode(&data),
+    };
+    Some(decoded.map_err(Error::ClipboardDecodeFailed))
+}
+
+/// Resolve a `text/uri-list` of `file://` URIs, or plain paths as some file managers copy them.
+/// The first file that's an image is used
+fn resolve_file_list(text: &str, svg_dpi: f32) -> Option<Result<RgbaImage, Error>> {
+    let paths = text
+        .lines()
+        .map(str::trim)
+        // Comments in URI lists, and whether files were copied or cut in GNOME's list
+        .filter(|line| !(line.is_empty() || line.starts_with('#')))
+        .filter(|line| !matches!(*line, "copy" | "cut"))
+        .map(file_uri_path)
+        .collect::<Option<Vec<PathBuf>>>()?;
+    if paths.is_empty() {
+        return None;
+    }
+
+    let mut first_error = None;
+    for path in paths {
+        match open_path(&path, svg_dpi) {
+            Ok(image_buf) => return Some(Ok(image_buf)),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    first_error.map(Err)
+}
+
+/// Resolve SVG markup
+fn resolve_svg(text: &str, svg_dpi: f32) -> Option<Result<RgbaImage, Error>> {
+    let is_svg = text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg"));
+    match is_svg {
+        true => Some(rasterize_svg(text.as_bytes(), svg_dpi).map_err(Error::ClipboardDecodeFailed)),
+        false => None,
+    }
+}
+
+/// Resolve HTML with `<img>` tags, e.g. copied from a browser. The first image whose source is a
+/// data URL or a local file is used, as remote images would need to be downloaded
+fn resolve_html(text: &str, svg_dpi: f32) -> Option<Result<RgbaImage, Error>> {
+    // Tag names aren't case sensitive
+    let sources = text
+        .to_ascii_lowercase()
+        .match_indices("<img")
+        .filter_map(|(index, _)| {
+            let tag = &text[index + "<img".len()..];
+            attribute_value(tag.split('>').next().unwrap_or_default(), "src")
+        })
+        .collect::<Vec<String>>();
+    if sources.is_empty() {
+        return None;
+    }
+
+    let mut first_error = None;
+    for source in sources {
+        let result = match file_uri_path(&source) {
+            Some(path) => Some(open_path(&path, svg_dpi)),
+            None => resolve_data_url(&source, svg_dpi),
+        };
+        match result {
+            Some(Ok(image_buf)) => return Some(Ok(image_buf)),
+            Some(Err(e)) => {
+                first_error.get_or_insert(e);
+            }
+            None => (),
+        }
+    }
+    Some(Err(first_error.unwrap_or_else(|| {
+        Error::Other(
+            "The copied HTML only has remote images, which can't be downloaded".to_string(),
+        )
+    })))
+}
+
+/***** Decoding *****/
+/// Decode an image in any format the `image` crate can decode. Failing gives the reason, which
+/// the caller makes an error for where the image came from
+fn decode(data: &[u8]) -> Result<RgbaImage, String> {
+    match image::load_from_memory(data) {
+        Ok(image) => Ok(image.into_rgba8()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Open an image file, rasterizing it if it's an SVG
+fn open_path(path: &Path, svg_dpi: f32) -> Result<RgbaImage, Error> {
+    let is_svg = path
+        .extension()
+        .map(|extension| extension.eq_ignore_ascii_case("svg"))
+        .unwrap_or(false);
+    if !is_svg {
+        return match image::open(path) {
+            Ok(image) => Ok(image.into_rgba8()),
+            Err(e) => Err(Error::DecodeFailed {
+                path: path.to_string_lossy().to_string(),
+                message: e.to_string(),
+            }),
+        };
+    }
+
+    match std::fs::read(path) {
+        Ok(data) => match rasterize_svg(&data, svg_dpi) {
+            Ok(image_buf) => Ok(image_buf),
+            Err(message) => Err(Error::DecodeFailed {
+                path: path.to_string_lossy().to_string(),
+                message,
+            }),
+        },
+        Err(e) => Err(Error::io(path, e)),
+    }
+}
+
+/// Draw an SVG into an image, scaled so it's the size it'd be at the DPI given. Like `decode`,
+/// failing gives the reason
+#[cfg(feature = "svg")]
+fn rasterize_svg(data: &[u8], dpi: f32) -> Result<RgbaImage, String> {
+    let options = usvg::Options {
+        dpi: dpi as f64,
+        ..usvg::Options::default()
+    };
+    let tree = match usvg::Tree::from_data(data, &options.to_ref()) {
+        Ok(tree) => tree,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let fit_to = usvg::FitTo::Zoom(dpi / SVG_BASE_DPI);
+    let size = match fit_to.fit_to(tree.svg_node().size.to_screen_size()) {
+        Some(size) => size,
+        None => return Err("The SVG has no size".to_string()),
+    };
+    let mut pixmap = match tiny_skia::Pixmap::new(size.width(), size.height()) {
+        Some(pixmap) => pixmap,
+        None => return Err("The SVG is too big".to_string()),
+    };
+    if resvg::render(
+        &tree,
+        fit_to,
+        tiny_skia::Transform::default(),
+        pixmap.as_mut(),
+    )
+    .is_none()
+    {
+        return Err("Failed to draw the SVG".to_string());
+    }
+
+    // Pixmaps have premultiplied alpha, unlike images
+    let raw = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+    match RgbaImage::from_raw(size.width(), size.height(), raw) {
+        Some(image_buf) => Ok(image_buf),
+        None => Err("The SVG's pixels don't match its size".to_string()),
+    }
+}
+
+/// Draw an SVG into an image, which this build can't do
+#[cfg(not(feature = "svg"))]
+fn rasterize_svg(_data: &[u8], _dpi: f32) -> Result<RgbaImage, String> {
+    Err("This build can't open SVGs, as it wasn't built with the svg feature".to_string())
+}
+
+/***** Parsing *****/
+/// Get the path of a `file://` URI, or an absolute path as-is
+fn file_uri_path(text: &str) -> Option<PathBuf> {
+    let path = match text.strip_prefix("file://") {
+        // The host is usually empty, but can be localhost
+        Some(uri) => {
+            let path = uri.strip_prefix("localhost").unwrap_or(uri);
+            let path = String::from_utf8_lossy(&percent_decode(path)).to_string();
+            // Windows URIs look like file:///C:/...
+            match path.get(2..3) == Some(":") {
+                true => PathBuf::from(&path[1..]),
+                false => PathBuf::from(path),
+            }
+        }
+        None => PathBuf::from(text),
+    };
+    match path.is_absolute() {
+        true => Some(path),
+        false => None,
+    }
+}
+
+/// Get the value of an HTML attribute from the inside of a tag, quoted or not
+fn attribute_value(tag: &str, name: &str) -> Option<String> {
+    let lowercase_tag = tag.to_ascii_lowercase();
+    let mut search_from = 0;
+    loop {
+        let index = search_from + lowercase_tag[search_from..].find(name)?;
+        search_from = index + name.len();
+        // Only whole attribute names count, e.g. not `data-src`
+        let preceded_by_space = tag[..index].ends_with(char::is_whitespace);
+        let rest = tag[search_from..].trim_start();
+        if !preceded_by_space || !rest.starts_with('=') {
+            continue;
+        }
+
+        let rest = rest[1..].trim_start();
+        let value = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].split(quote).next()?,
+            _ => rest.split(char::is_whitespace).next()?,
+        };
+        return Some(value.replace("&amp;", "&"));
+    }
+}
+
+/// Decode `%XX` escapes, leaving anything invalid as-is
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = match bytes[index] {
+            b'%' => text
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    decoded
+}
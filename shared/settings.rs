@@ -6,7 +6,9 @@
 /***** Setup *****/
 #![allow(dead_code, unused_imports, clippy::derive_partial_eq_without_eq)]
 /* Imports */
+use super::error::Error;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::string::ToString;
 use strum_macros::{Display as EnumDisplay, EnumIter, EnumString};
 
@@ -20,6 +22,18 @@ pub enum SaveFormat {
     Tiff,
     Gif,
     Tga,
+    Webp,
+    Qoi,
+    Pbm,
+    Pgm,
+    Ppm,
+    Pam,
+    Farbfeld,
+    OpenExr,
+    /// Apple icon, with the image resampled to each of `EncoderOptions::icon_sizes`
+    Icns,
+    /// Only available if the backend was built with the `avif` feature
+    Avif,
 }
 impl Default for SaveFormat {
     /// Default save format
@@ -27,34 +41,412 @@ impl Default for SaveFormat {
         SaveFormat::Png
     }
 }
+impl SaveFormat {
+    /// The file extension used for the format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SaveFormat::Png => "png",
+            SaveFormat::Jpg => "jpg",
+            SaveFormat::Bmp => "bmp",
+            SaveFormat::Ico => "ico",
+            SaveFormat::Tiff => "tiff",
+            SaveFormat::Gif => "gif",
+            SaveFormat::Tga => "tga",
+            SaveFormat::Webp => "webp",
+            SaveFormat::Qoi => "qoi",
+            SaveFormat::Pbm => "pbm",
+            SaveFormat::Pgm => "pgm",
+            SaveFormat::Ppm => "ppm",
+            SaveFormat::Pam => "pam",
+            SaveFormat::Farbfeld => "ff",
+            SaveFormat::OpenExr => "exr",
+            SaveFormat::Icns => "icns",
+            SaveFormat::Avif => "avif",
+        }
+    }
+
+    /// Whether the format can store transparency
+    pub fn supports_alpha(&self) -> bool {
+        !matches!(
+            self,
+            SaveFormat::Jpg | SaveFormat::Pbm | SaveFormat::Pgm | SaveFormat::Ppm
+        )
+    }
+}
+
+/// How hard the PNG encoder tries to compress
+#[derive(EnumIter, EnumString, EnumDisplay, PartialEq, Clone, Serialize, Deserialize)]
+pub enum PngCompression {
+    Default,
+    Fast,
+    Best,
+}
+
+/// The filter the PNG encoder uses before compressing
+#[derive(EnumIter, EnumString, EnumDisplay, PartialEq, Clone, Serialize, Deserialize)]
+pub enum PngFilter {
+    NoFilter,
+    Sub,
+    Up,
+    Avg,
+    Paeth,
+    Adaptive,
+}
+
+/// The compression the TIFF encoder uses
+#[derive(EnumIter, EnumString, EnumDisplay, PartialEq, Clone, Serialize, Deserialize)]
+pub enum TiffCompression {
+    Uncompressed,
+    Lzw,
+    Deflate,
+    Packbits,
+}
+
+/// The filter used when resampling an image
+#[derive(EnumIter, EnumString, EnumDisplay, PartialEq, Clone, Serialize, Deserialize)]
+pub enum ResizeFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    Lanczos3,
+}
+
+/// How an image is resized when saving it
+#[derive(EnumIter, EnumString, EnumDisplay, PartialEq, Clone, Serialize, Deserialize)]
+pub enum ResizeMode {
+    None,
+    /// To `ResizeOptions::width` by `height`, where 0 keeps the aspect ratio
+    Exact,
+    /// By `ResizeOptions::percentage`
+    Percentage,
+    /// Shrunk to fit within `ResizeOptions::width` by `height`
+    Fit,
+    /// Shrunk so the longest edge is at most `ResizeOptions::max_edge`
+    MaxEdge,
+}
+
+/// Options for resizing an image when saving it
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResizeOptions {
+    pub mode: ResizeMode,
+    pub width: u32,
+    pub height: u32,
+    pub percentage: u32,
+    pub max_edge: u32,
+    pub filter: ResizeFilter,
+}
+impl Default for ResizeOptions {
+    fn default() -> ResizeOptions {
+        ResizeOptions {
+            mode: ResizeMode::None,
+            width: 1920,
+            height: 1080,
+            percentage: 50,
+            max_edge: 1920,
+            filter: ResizeFilter::Lanczos3,
+        }
+    }
+}
+impl ResizeOptions {
+    /// Get the size an image of a size is resized to
+    pub fn output_size(&self, width: u32, height: u32) -> (u32, u32) {
+        if width == 0 || height == 0 {
+            return (width, height);
+        }
+        let scale = |factor: f64| {
+            (
+                (width as f64 * factor).round().max(1.0) as u32,
+                (height as f64 * factor).round().max(1.0) as u32,
+            )
+        };
+        // Shrinking only, where a limit of 0 means there is none
+        let shrink_factor = |limit: u32, size: u32| match limit {
+            0 => 1.0,
+            _ => (limit as f64 / size as f64).min(1.0),
+        };
+
+        match self.mode {
+            ResizeMode::None => (width, height),
+            ResizeMode::Exact => match (self.width, self.height) {
+                (0, 0) => (width, height),
+                (0, new_height) => (scale(new_height as f64 / height as f64).0, new_height),
+                (new_width, 0) => (new_width, scale(new_width as f64 / width as f64).1),
+                (new_width, new_height) => (new_width, new_height),
+            },
+            ResizeMode::Percentage => scale(self.percentage.max(1) as f64 / 100.0),
+            ResizeMode::Fit => {
+                scale(shrink_factor(self.width, width).min(shrink_factor(self.height, height)))
+            }
+            ResizeMode::MaxEdge => scale(shrink_factor(self.max_edge, width.max(height))),
+        }
+    }
+}
+
+/// The sizes that icons can be exported at
+pub const ICON_SIZES: [u32; 7] = [16, 24, 32, 48, 64, 128, 256];
+
+/// What transparent pixels are put on top of when saving to a format without transparency
+#[derive(EnumIter, EnumString, EnumDisplay, PartialEq, Clone, Serialize, Deserialize)]
+pub enum TransparencyBackground {
+    White,
+    Black,
+    Checkerboard,
+    /// The color in `EncoderOptions::transparency_color`
+    Color,
+}
+
+/// Options for the encoders of each save format
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncoderOptions {
+    /// JPEG quality from 1 to 100
+    pub jpeg_quality: u8,
+    pub png_compression: PngCompression,
+    pub png_filter: PngFilter,
+    pub tiff_compression: TiffCompression,
+    /// GIF quantization speed from 1 (best quality) to 30 (fastest)
+    pub gif_speed: i32,
+    /// Shrink images bigger than 256x256 to fit in an ICO, instead of failing to save them
+    pub ico_shrink_to_fit: bool,
+    /// Pack the image resampled to each of `icon_sizes` into one ICO, instead of saving it as is
+    pub ico_multi_size: bool,
+    /// The sizes (from `ICON_SIZES`) that multi-size ICOs and ICNSs contain
+    pub icon_sizes: Vec<u32>,
+    pub icon_filter: ResizeFilter,
+    pub transparency_background: TransparencyBackground,
+    /// A hex color like "#ff00ff"
+    pub transparency_color: String,
+    /// Resizing, done before anything else
+    pub resize: ResizeOptions,
+}
+impl Default for EncoderOptions {
+    /// Default encoder options, which are the same as the `image` crate's defaults
+    fn default() -> EncoderOptions {
+        EncoderOptions {
+            jpeg_quality: 75,
+            png_compression: PngCompression::Default,
+            png_filter: PngFilter::Adaptive,
+            tiff_compression: TiffCompression::Uncompressed,
+            gif_speed: 1,
+            ico_shrink_to_fit: true,
+            ico_multi_size: false,
+            icon_sizes: ICON_SIZES.to_vec(),
+            icon_filter: ResizeFilter::Lanczos3,
+            transparency_background: TransparencyBackground::White,
+            transparency_color: "#ffffff".to_string(),
+            resize: ResizeOptions::default(),
+        }
+    }
+}
+
+/// Color adjustments applied to the edited image, where the defaults change nothing
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Adjustments {
+    /// Added to every channel, from -255 to 255
+    pub brightness: i32,
+    /// From -100 to 100, where negative values reduce the contrast
+    pub contrast: f32,
+    /// From 0.1 to 10, where values above 1 brighten the dark tones
+    pub gamma: f32,
+    pub grayscale: bool,
+    pub invert: bool,
+    /// Degrees to rotate the hue by
+    pub hue_rotate: i32,
+}
+impl Default for Adjustments {
+    /// Adjustments that leave the image as it is
+    fn default() -> Adjustments {
+        Adjustments {
+            brightness: 0,
+            contrast: 0.0,
+            gamma: 1.0,
+            grayscale: false,
+            invert: false,
+            hue_rotate: 0,
+        }
+    }
+}
+impl Adjustments {
+    /// Whether the adjustments leave the image as it is
+    pub fn is_identity(&self) -> bool {
+        self.brightness == 0
+            && self.contrast == 0.0
+            && self.gamma == 1.0
+            && !self.grayscale
+            && !self.invert
+            && self.hue_rotate % 360 == 0
+    }
+}
+
+/// Adjustments saved under a name, to apply them to other images
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct AdjustmentPreset {
+    pub name: String,
+    #[serde(default)]
+    pub adjustments: Adjustments,
+}
+
+/// What to do when saving an image to a file that already exists
+#[derive(EnumIter, EnumString, EnumDisplay, PartialEq, Clone, Serialize, Deserialize)]
+pub enum CollisionPolicy {
+    /// Let the user confirm overwriting the file
+    Ask,
+    /// Add a number to the end of the file name
+    Rename,
+    Overwrite,
+    /// Don't save the image
+    Skip,
+}
+impl Default for CollisionPolicy {
+    /// Default collision policy
+    fn default() -> CollisionPolicy {
+        CollisionPolicy::Ask
+    }
+}
 
+/// The settings the user chose. Fields missing from a settings file (e.g. ones added after it was
+/// saved) get their default values, and fields that changed are migrated (see `migrate_settings`)
 #[derive(PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
+    /// The version of the settings format they were saved in
+    pub version: u64,
     pub anti_aliasing: bool,
     pub save_path: String,
     pub save_format: SaveFormat,
+    pub encoder_options: EncoderOptions,
+    pub collision_policy: CollisionPolicy,
     pub auto_paste: bool,
+    pub watch_clipboard: bool,
+    /// How many captured images to keep in the history, 0 to disable it (the default).
+    /// They're kept as captured, before any redactions
+    pub history_limit: usize,
     pub zoom_by: i32,
+    /// The DPI copied SVGs are drawn at, where 96 is their actual size
+    pub svg_dpi: f32,
+    /// Trim the border off images read from the clipboard, as an edit that can be undone
+    pub auto_trim: bool,
+    /// How much a pixel's channels can differ from the border color and still be trimmed
+    pub trim_tolerance: u8,
+    /// Color adjustments saved by the user
+    pub adjustment_presets: Vec<AdjustmentPreset>,
 }
 impl Default for Settings {
     /// Default settings
     fn default() -> Settings {
         Settings {
+            version: SETTINGS_VERSION,
             anti_aliasing: true,
-            save_path: "./image".to_string(),
+            save_path: "./image-{counter}.png".to_string(),
             save_format: SaveFormat::default(),
+            encoder_options: EncoderOptions::default(),
+            collision_policy: CollisionPolicy::default(),
             auto_paste: false,
+            watch_clipboard: false,
+            history_limit: 0,
             zoom_by: 10,
+            svg_dpi: 96.0,
+            auto_trim: false,
+            trim_tolerance: 8,
+            adjustment_presets: Vec::new(),
         }
     }
 }
 impl Settings {
-    /// Parse a settings JSON file into a Settings struct
-    pub fn parse(settings_text: String) -> Result<Settings, String> {
-        let parsed = match serde_json::from_str(settings_text.as_str()) {
+    /// Parse a settings JSON file into a Settings struct, migrating it if it's from an older
+    /// version
+    pub fn parse(settings_text: String) -> Result<Settings, Error> {
+        let mut settings_json = match serde_json::from_str(settings_text.as_str()) {
+            Ok(settings_json) => settings_json,
+            Err(e) => return Err(Error::SettingsParse(e.to_string())),
+        };
+        match migrate_settings(&mut settings_json) {
+            Ok(_) => (),
+            Err(e) => return Err(e),
+        };
+        let parsed = match serde_json::from_value(settings_json) {
             Ok(parsed) => parsed,
-            Err(e) => return Err(format!("Failed to parse the settings text: {}", e)),
+            Err(e) => return Err(Error::SettingsParse(e.to_string())),
         };
         Ok(parsed)
     }
 }
+
+/// The range of `Settings::zoom_by`, in percent per step
+pub const MIN_ZOOM_BY: i32 = 1;
+pub const MAX_ZOOM_BY: i32 = 100;
+
+/***** Migrations *****/
+/// The version of the settings format. Settings from before it was added are version 0
+pub const SETTINGS_VERSION: u64 = 1;
+
+/// Changes settings from one version to the next, e.g. renaming a field. Fields that were only
+/// added don't need one, as missing fields get their default values
+type Migration = fn(&mut Map<String, Value>);
+/// The migrations from each version, where the first one migrates version 0 to 1
+const MIGRATIONS: [Migration; SETTINGS_VERSION as usize] = [migrate_unversioned];
+
+/// Migrate settings from before versioning. Files from before the collision policy was added
+/// always overwrote existing files, so they keep doing that instead of starting to ask.
+/// Save paths could have no extension (e.g. the old default `./image`), which can't be saved
+/// anymore, so they get the one of their format
+fn migrate_unversioned(settings: &mut Map<String, Value>) {
+    if !settings.contains_key("collision_policy") {
+        settings.insert(
+            "collision_policy".to_string(),
+            Value::String(CollisionPolicy::Overwrite.to_string()),
+        );
+    }
+
+    let save_format = settings
+        .get("save_format")
+        .and_then(|save_format| SaveFormat::deserialize(save_format).ok())
+        .unwrap_or_default();
+    if let Some(Value::String(save_path)) = settings.get_mut("save_path") {
+        let file_name = save_path
+            .rsplit(|c| c == '/' || c == '\\')
+            .next()
+            .unwrap_or_default();
+        if !(file_name.is_empty() || file_name.contains('.')) {
+            save_path.push('.');
+            save_path.push_str(save_format.extension());
+        }
+    }
+}
+
+/// Migrate settings JSON to the current version, returning the version it was in. Settings from
+/// a newer version (e.g. of a newer build) are left as they are, so they're parsed as far as
+/// they can be
+pub fn migrate_settings(settings_json: &mut Value) -> Result<u64, Error> {
+    let settings = match settings_json.as_object_mut() {
+        Some(settings) => settings,
+        None => {
+            return Err(Error::SettingsParse(
+                "The settings aren't a JSON object".to_string(),
+            ))
+        }
+    };
+    let version = match settings.get("version") {
+        Some(version) => match version.as_u64() {
+            Some(version) => version,
+            None => {
+                return Err(Error::SettingsParse(format!(
+                    "The settings version {} isn't a version number",
+                    version
+                )))
+            }
+        },
+        None => 0,
+    };
+
+    if version < SETTINGS_VERSION {
+        for migration in &MIGRATIONS[version as usize..] {
+            migration(settings);
+        }
+        settings.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+    }
+    Ok(version)
+}
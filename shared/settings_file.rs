@@ -0,0 +1,237 @@
+/*
+ * Reading the settings file, which is migrated in place when it's from an older version, and
+ * writing it once the settings are validated
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{
+    encoder,
+    error::{Error, SettingError},
+    files,
+    settings::{self, SaveFormat, Settings},
+    template,
+};
+use image::ImageFormat;
+use std::{
+    fs::{create_dir_all, metadata, read_to_string},
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+};
+
+/***** Loading *****/
+/// Read the settings file, or `None` if there isn't one yet. If it's from an older version, it's
+/// migrated and written back, keeping the old file next to it (see `backup_path`).
+/// Files that can't be parsed are left for the user to fix
+pub fn load_settings_file(path: &Path) -> Result<Option<String>, Error> {
+    let settings_text = match read_to_string(path) {
+        Ok(settings_text) => settings_text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(Error::io(path, e)),
+    };
+    let mut settings_json = match serde_json::from_str(settings_text.as_str()) {
+        Ok(settings_json) => settings_json,
+        Err(e) => return Err(Error::SettingsParse(e.to_string())),
+    };
+    let version = match settings::migrate_settings(&mut settings_json) {
+        Ok(version) => version,
+        Err(e) => return Err(e),
+    };
+    if version >= settings::SETTINGS_VERSION {
+        return Ok(Some(settings_text));
+    }
+
+    // The migrated file has every field, like files saved by the current version
+    let migrated: Settings = match serde_json::from_value(settings_json) {
+        Ok(migrated) => migrated,
+        Err(e) => return Err(Error::SettingsParse(e.to_string())),
+    };
+    let migrated_text = match serde_json::to_string(&migrated) {
+        Ok(migrated_text) => migrated_text,
+        Err(e) => return Err(Error::SettingsParse(e.to_string())),
+    };
+    let backup_path = backup_path(path, version);
+    match files::write_atomic(&backup_path, settings_text.as_bytes()) {
+        Ok(_) => (),
+        Err(e) => return Err(Error::io(&backup_path, e)),
+    };
+    match files::write_atomic(path, migrated_text.as_bytes()) {
+        Ok(_) => Ok(Some(migrated_text)),
+        Err(e) => Err(Error::io(path, e)),
+    }
+}
+
+/// Get the path a settings file of a version is kept at once it's migrated, e.g.
+/// `settings.v0.json`. Backups aren't overwritten, so a number is added if it exists
+pub fn backup_path(path: &Path, version: u64) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let backup_path = path.with_file_name(format!("{}.v{}{}", stem, version, extension));
+    match backup_path.exists() {
+        true => files::find_free_path(&backup_path),
+        false => backup_path,
+    }
+}
+
+/***** Saving *****/
+/// Parse the settings sent by the frontend, validate them (see `validate_settings`) and write
+/// them to the settings file, creating its directory if needed
+pub fn save_settings_file(path: &Path, settings_text: String) -> Result<(), Error> {
+    let new_settings = match Settings::parse(settings_text) {
+        Ok(new_settings) => new_settings,
+        Err(e) => return Err(e),
+    };
+    match validate_settings(&new_settings) {
+        Ok(_) => (),
+        Err(e) => return Err(e),
+    };
+    let settings_text = match serde_json::to_string(&new_settings) {
+        Ok(settings_text) => settings_text,
+        Err(e) => return Err(Error::SettingsParse(e.to_string())),
+    };
+
+    if let Some(parent_dir_path) = path.parent() {
+        match create_dir_all(parent_dir_path) {
+            Ok(_) => (),
+            Err(e) => return Err(Error::io(parent_dir_path, e)),
+        };
+    }
+    match files::write_atomic(path, settings_text.as_bytes()) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::io(path, e)),
+    }
+}
+
+/***** Validating *****/
+/// Check the settings can be used, so mistakes show up when they're saved instead of on the next
+/// save of an image. Every setting that can't be used gets an error in `Error::InvalidSettings`.
+/// Nothing is changed, so the folder images are saved to is only created once one is saved
+pub fn validate_settings(settings: &Settings) -> Result<(), Error> {
+    let mut errors = Vec::new();
+
+    let save_path = PathBuf::from(template::expand_home(&settings.save_path));
+    match save_path.file_name() {
+        Some(file_name) => {
+            // Templates can leave the extension to `{format}`
+            let extension_matches = match Path::new(file_name).extension() {
+                Some(extension) => {
+                    extension == "{format}"
+                        || matches_format(&extension.to_string_lossy(), &settings.save_format)
+                }
+                None => false,
+            };
+            if !extension_matches {
+                errors.push(SettingError::new(
+                    "save_path",
+                    format!(
+                        "The file name should end in .{} (or .{{format}}) to save as {}",
+                        settings.save_format.extension(),
+                        settings.save_format.to_string().to_uppercase()
+                    ),
+                ));
+            }
+            if let Err(message) = check_save_dir(&fixed_dir_path(&save_path)) {
+                errors.push(SettingError::new("save_path", message));
+            }
+        }
+        None => errors.push(SettingError::new(
+            "save_path",
+            "Choose a file name to save images as",
+        )),
+    };
+
+    if !encoder::can_encode(&settings.save_format) {
+        errors.push(SettingError::new(
+            "save_format",
+            format!(
+                "This build can't save as {}",
+                settings.save_format.to_string().to_uppercase()
+            ),
+        ));
+    }
+
+    if settings.zoom_by < settings::MIN_ZOOM_BY || settings.zoom_by > settings::MAX_ZOOM_BY {
+        errors.push(SettingError::new(
+            "zoom_by",
+            format!(
+                "Zoom by should be between {}% and {}%",
+                settings::MIN_ZOOM_BY,
+                settings::MAX_ZOOM_BY
+            ),
+        ));
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(Error::InvalidSettings(errors)),
+    }
+}
+
+/// Check a file extension is one used for a format, e.g. `jpeg` for `Jpg`
+fn matches_format(extension: &str, format: &SaveFormat) -> bool {
+    if extension.eq_ignore_ascii_case(format.extension()) {
+        return true;
+    }
+    match (
+        ImageFormat::from_extension(extension),
+        ImageFormat::from_extension(format.extension()),
+    ) {
+        (Some(image_format), Some(format)) => image_format == format,
+        _ => false,
+    }
+}
+
+/// Check images can be saved in a folder, which is created if it doesn't exist. Its nearest
+/// folder that exists has to be one that can be written to
+fn check_save_dir(save_dir_path: &Path) -> Result<(), String> {
+    // Relative paths that don't exist are in the current folder
+    let (existing_path, metadata) = match save_dir_path
+        .ancestors()
+        .map(|path| match path.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => path,
+        })
+        .find_map(|path| metadata(path).ok().map(|metadata| (path, metadata)))
+    {
+        Some(existing) => existing,
+        None => return Err(format!("Can't find the folder {}", save_dir_path.display())),
+    };
+
+    if !metadata.is_dir() {
+        return Err(format!(
+            "{} is a file, so it can't have the folder {} in it",
+            existing_path.display(),
+            save_dir_path.display()
+        ));
+    }
+    match metadata.permissions().readonly() {
+        true => Err(format!(
+            "The folder {} is read-only",
+            existing_path.display()
+        )),
+        false => Ok(()),
+    }
+}
+
+/// Get the folder of a save path template up to the first placeholder, as the folders named by
+/// placeholders (e.g. `{date}`) aren't known until an image is saved
+fn fixed_dir_path(save_path: &Path) -> PathBuf {
+    match save_path.parent() {
+        Some(parent) => parent
+            .components()
+            .take_while(|component| match component {
+                Component::Normal(name) => !name.to_string_lossy().contains('{'),
+                _ => true,
+            })
+            .collect(),
+        None => PathBuf::new(),
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Save path templates
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{error::Error, files::write_atomic, settings::SaveFormat};
+use chrono::{DateTime, Local};
+use std::{
+    fs::{create_dir_all, read_to_string},
+    path::Path,
+};
+
+/***** Template values *****/
+/// The values that placeholders in a save path template are replaced with
+pub struct TemplateValues<'a> {
+    pub now: DateTime<Local>,
+    pub counter: u64,
+    pub width: u32,
+    pub height: u32,
+    pub hash: u64,
+    pub format: &'a SaveFormat,
+}
+
+/***** Expanding *****/
+/// Replace the placeholders in a save path template. The supported placeholders are:
+/// `{date}`, `{time}`, `{counter}`, `{width}`, `{height}`, `{hash8}`, `{hash}` and `{format}`.
+/// Unknown placeholders are left alone. A leading `~` is the home folder (see `expand_home`)
+pub fn expand_template(template: &str, values: &TemplateValues) -> String {
+    let hash = format!("{:016x}", values.hash);
+    expand_home(template)
+        .replace("{date}", values.now.format("%Y-%m-%d").to_string().as_str())
+        // Colons aren't allowed in Windows file names
+        .replace("{time}", values.now.format("%H-%M-%S").to_string().as_str())
+        .replace("{counter}", values.counter.to_string().as_str())
+        .replace("{width}", values.width.to_string().as_str())
+        .replace("{height}", values.height.to_string().as_str())
+        .replace("{hash8}", &hash[..8])
+        .replace("{hash}", hash.as_str())
+        .replace("{format}", values.format.extension())
+}
+
+/// Replace a leading `~` with the user's home folder, like shells do, e.g. `~/Pictures/image.png`.
+/// Without a home folder, the path is left alone
+pub fn expand_home(path: &str) -> String {
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(|c| c == '/' || c == '\\') => {
+            match dirs_next::home_dir() {
+                Some(home_dir) => format!("{}{}", home_dir.to_string_lossy(), rest),
+                None => path.to_string(),
+            }
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// Whether a template uses the counter, and so should increment it once saved
+pub fn uses_counter(template: &str) -> bool {
+    template.contains("{counter}")
+}
+
+/***** Counter *****/
+/// Load the `{counter}` value kept in a file (see `get_counter_path`), which starts at 1
+pub fn load_counter(counter_path: &Path) -> u64 {
+    match read_to_string(counter_path) {
+        Ok(counter_text) => counter_text.trim().parse().unwrap_or(1),
+        Err(_) => 1,
+    }
+}
+
+/// Save the `{counter}` value to a file so it persists between runs
+pub fn save_counter(counter_path: &Path, counter: u64) -> Result<(), Error> {
+    if let Some(counter_dir) = counter_path.parent() {
+        match create_dir_all(counter_dir) {
+            Ok(_) => (),
+            Err(e) => return Err(Error::io(counter_dir, e)),
+        };
+    }
+    match write_atomic(counter_path, counter.to_string().as_bytes()) {
+        Ok(_) => Ok(()),
+        Err(e) => Err(Error::io(counter_path, e)),
+    }
+}
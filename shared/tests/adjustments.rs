@@ -0,0 +1,149 @@
+/*
+ * Color adjustments applied to copies of the image
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{Rgba, RgbaImage};
+use shared::{
+    adjustments::apply_adjustments,
+    error::Error,
+    image_data::ImageData,
+    settings::{Adjustments, EncoderOptions, SaveFormat},
+};
+mod common;
+use common::{image_data, TestDir};
+
+/// The color of the test image, which is half transparent
+const COLOR: Rgba<u8> = Rgba([200, 100, 64, 0x80]);
+
+/// Get the first pixel of the test color once adjusted
+fn adjusted_pixel(adjustments: Adjustments) -> Rgba<u8> {
+    let image = image::DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, COLOR));
+    *apply_adjustments(&image, &adjustments)
+        .to_rgba8()
+        .get_pixel(0, 0)
+}
+
+/***** Tests *****/
+#[test]
+fn adjustments_are_saved_and_previewed_but_the_original_is_kept() {
+    let mut image_data = image_data(4, 4, |_, _| COLOR);
+    image_data
+        .set_adjustments(Adjustments {
+            invert: true,
+            ..Adjustments::default()
+        })
+        .unwrap();
+    let inverted = Rgba([55, 155, 191, 0x80]);
+
+    let dir = TestDir::new("adjusted");
+    let path = dir.join("image.png");
+    image_data
+        .save_to_file(
+            &path.to_string_lossy(),
+            SaveFormat::Png,
+            &EncoderOptions::default(),
+        )
+        .unwrap();
+    let saved = image::open(&path).unwrap().into_rgba8();
+    assert!(saved.pixels().all(|pixel| *pixel == inverted));
+
+    let preview = image::load_from_memory(&image_data.preview_png().unwrap())
+        .unwrap()
+        .into_rgba8();
+    assert!(preview.pixels().all(|pixel| *pixel == inverted));
+
+    assert!(image_data.clipboard_dynamic_image.to_rgba8() == RgbaImage::from_pixel(4, 4, COLOR));
+}
+
+#[test]
+fn each_adjustment() {
+    assert_eq!(adjusted_pixel(Adjustments::default()), COLOR);
+    assert_eq!(
+        adjusted_pixel(Adjustments {
+            brightness: 100,
+            ..Adjustments::default()
+        }),
+        Rgba([255, 200, 164, 0x80])
+    );
+    // A gamma of 2 is a square root, so 64 (a quarter) becomes half
+    assert_eq!(
+        adjusted_pixel(Adjustments {
+            gamma: 2.0,
+            ..Adjustments::default()
+        })[2],
+        128
+    );
+
+    let gray = adjusted_pixel(Adjustments {
+        grayscale: true,
+        ..Adjustments::default()
+    });
+    assert!(gray[0] == gray[1] && gray[1] == gray[2], "{:?}", gray);
+    assert_eq!(gray[3], 0x80);
+
+    let more_contrast = adjusted_pixel(Adjustments {
+        contrast: 50.0,
+        ..Adjustments::default()
+    });
+    assert!(more_contrast[0] > COLOR[0] && more_contrast[2] < COLOR[2]);
+
+    let rotated = adjusted_pixel(Adjustments {
+        hue_rotate: 180,
+        ..Adjustments::default()
+    });
+    assert!(rotated[0] < rotated[2], "{:?}", rotated);
+    assert!(Adjustments {
+        hue_rotate: 360,
+        ..Adjustments::default()
+    }
+    .is_identity());
+}
+
+#[test]
+fn invalid_adjustments_are_rejected() {
+    let mut image_data = image_data(4, 4, |_, _| COLOR);
+
+    for invalid in [
+        Adjustments {
+            brightness: 300,
+            ..Adjustments::default()
+        },
+        Adjustments {
+            contrast: f32::NAN,
+            ..Adjustments::default()
+        },
+        Adjustments {
+            gamma: 0.0,
+            ..Adjustments::default()
+        },
+    ] {
+        assert!(matches!(
+            image_data.set_adjustments(invalid),
+            Err(Error::InvalidEdit(_))
+        ));
+    }
+    assert!(image_data.adjustments.is_identity());
+    assert!(matches!(
+        ImageData::default().set_adjustments(Adjustments::default()),
+        Err(Error::NoImageInClipboard)
+    ));
+}
+
+#[test]
+fn new_images_forget_the_adjustments() {
+    let mut image_data = image_data(4, 4, |_, _| COLOR);
+    image_data
+        .set_adjustments(Adjustments {
+            grayscale: true,
+            ..Adjustments::default()
+        })
+        .unwrap();
+
+    image_data.set_clipboard_image(RgbaImage::from_pixel(2, 2, COLOR));
+
+    assert!(image_data.adjustments.is_identity());
+    assert_eq!(*image_data.edited_image().to_rgba8().get_pixel(0, 0), COLOR);
+}
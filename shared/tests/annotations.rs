@@ -0,0 +1,219 @@
+/*
+ * Drawing annotations onto images when they're saved or copied
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{Rgba, RgbaImage};
+use shared::{
+    clipboard::MemoryClipboard,
+    error::Error,
+    image_data::ImageData,
+    payloads::{Annotation, Shape},
+    settings::EncoderOptions,
+};
+use std::sync::RwLock;
+mod common;
+use common::image_data;
+
+const WHITE: Rgba<u8> = Rgba([0xff, 0xff, 0xff, 0xff]);
+
+/// Make image data with a white 60x40 image and annotations
+fn annotated_image_data(annotations: Vec<Annotation>) -> ImageData {
+    let mut image_data = image_data(60, 40, |_, _| WHITE);
+    image_data.set_annotations(annotations).unwrap();
+    image_data
+}
+
+/// Make an annotation in a color
+fn annotation(shape: Shape, color: &str, size: f32) -> Annotation {
+    Annotation {
+        shape,
+        color: color.to_string(),
+        size,
+    }
+}
+
+/// Get the image data's image as it's copied to the clipboard
+fn copied_image(image_data: &mut ImageData) -> RgbaImage {
+    let mut clipboard = MemoryClipboard::default();
+    image_data
+        .write_clipboard(&mut clipboard, None, &EncoderOptions::default())
+        .unwrap();
+    clipboard.image.unwrap()
+}
+
+/***** Tests *****/
+#[test]
+fn shapes_are_drawn_when_copying_but_not_in_the_preview() {
+    let mut image_data = annotated_image_data(vec![annotation(
+        Shape::Rectangle {
+            x: 10.0,
+            y: 10.0,
+            width: 20.0,
+            height: 10.0,
+        },
+        "#ff0000",
+        2.0,
+    )]);
+
+    let copied = copied_image(&mut image_data);
+    // The outline is drawn, but not the inside
+    assert_eq!(*copied.get_pixel(10, 15), Rgba([0xff, 0, 0, 0xff]));
+    assert_eq!(*copied.get_pixel(20, 15), WHITE);
+
+    let preview = image::load_from_memory(&image_data.preview_png().unwrap())
+        .unwrap()
+        .into_rgba8();
+    assert!(preview.pixels().all(|pixel| *pixel == WHITE));
+}
+
+#[test]
+fn previews_are_served_at_the_revision_after_annotating() {
+    let state = RwLock::new(annotated_image_data(Vec::new()));
+    let before = state.read().unwrap().revision;
+    ImageData::preview_png_of(&state, Some(before)).unwrap();
+
+    // The frontend asks for the preview at the revision annotating gave it
+    let after = {
+        let mut image_data = state.write().unwrap();
+        image_data
+            .set_annotations(vec![annotation(
+                Shape::Arrow {
+                    from: [0.0, 0.0],
+                    to: [59.0, 39.0],
+                },
+                "#0000ff",
+                3.0,
+            )])
+            .unwrap();
+        image_data.revision
+    };
+    assert_ne!(after, before);
+    let preview = ImageData::preview_png_of(&state, Some(after)).unwrap();
+    let preview = image::load_from_memory(&preview).unwrap().into_rgba8();
+    assert!(preview.pixels().all(|pixel| *pixel == WHITE));
+    assert!(matches!(
+        ImageData::preview_png_of(&state, Some(before)),
+        Err(Error::OutdatedRevision(_))
+    ));
+}
+
+#[test]
+fn highlights_are_translucent() {
+    let mut image_data = annotated_image_data(vec![annotation(
+        Shape::Highlight {
+            x: 30.0,
+            y: 30.0,
+            width: -20.0,
+            height: -20.0,
+        },
+        "#0000ff",
+        1.0,
+    )]);
+
+    let copied = copied_image(&mut image_data);
+    let highlighted = copied.get_pixel(20, 20);
+    assert!((150..=156).contains(&highlighted[0]), "{:?}", highlighted);
+    assert_eq!(highlighted[2], 0xff);
+    assert_eq!(*copied.get_pixel(5, 5), WHITE);
+}
+
+#[test]
+fn arrows_lines_and_text_are_drawn() {
+    let mut image_data = annotated_image_data(vec![
+        annotation(
+            Shape::Arrow {
+                from: [5.0, 5.0],
+                to: [50.0, 5.0],
+            },
+            "#00ff00",
+            2.0,
+        ),
+        annotation(
+            Shape::Freehand {
+                points: vec![[55.0, 35.0]],
+            },
+            "#0000ff",
+            4.0,
+        ),
+        annotation(
+            Shape::Text {
+                x: 2.0,
+                y: 30.0,
+                text: "Hi".to_string(),
+            },
+            "#000000",
+            16.0,
+        ),
+    ]);
+
+    let copied = copied_image(&mut image_data);
+    // The arrowhead is wider than the line
+    assert_eq!(*copied.get_pixel(42, 7), Rgba([0, 0xff, 0, 0xff]));
+    assert_eq!(*copied.get_pixel(20, 8), WHITE);
+    // A line of one point is a dot
+    assert_eq!(*copied.get_pixel(55, 35), Rgba([0, 0, 0xff, 0xff]));
+    // The text is drawn above its baseline, and is about 20 pixels wide at this size
+    let dark_pixels = |x_range: std::ops::Range<u32>, y_range: std::ops::Range<u32>| {
+        x_range
+            .flat_map(|x| y_range.clone().map(move |y| (x, y)))
+            .filter(|(x, y)| copied.get_pixel(*x, *y)[0] < 0x80)
+            .count()
+    };
+    assert!(dark_pixels(2..24, 17..30) > 20);
+    assert_eq!(dark_pixels(2..30, 31..40), 0);
+    assert_eq!(dark_pixels(28..50, 17..40), 0);
+}
+
+#[test]
+fn invalid_annotations_are_rejected() {
+    let mut image_data = annotated_image_data(vec![]);
+    let text = |text: &str| Shape::Text {
+        x: 0.0,
+        y: 10.0,
+        text: text.to_string(),
+    };
+
+    for invalid in [
+        annotation(text("Hi"), "red", 10.0),
+        annotation(text("Hi"), "#ff0000", 0.0),
+        annotation(text("Hi"), "#ff0000", f32::INFINITY),
+        annotation(text(" "), "#ff0000", 10.0),
+        annotation(Shape::Freehand { points: vec![] }, "#ff0000", 10.0),
+        annotation(
+            Shape::Arrow {
+                from: [0.0, f32::NAN],
+                to: [10.0, 10.0],
+            },
+            "#ff0000",
+            10.0,
+        ),
+    ] {
+        assert!(matches!(
+            image_data.set_annotations(vec![invalid]),
+            Err(Error::InvalidAnnotation(_))
+        ));
+    }
+    assert!(image_data.annotations.is_empty());
+    assert!(matches!(
+        ImageData::default().set_annotations(vec![]),
+        Err(Error::NoImageInClipboard)
+    ));
+}
+
+#[test]
+fn new_images_forget_the_annotations() {
+    let mut image_data = annotated_image_data(vec![annotation(
+        Shape::Freehand {
+            points: vec![[1.0, 1.0], [5.0, 5.0]],
+        },
+        "#ff000080",
+        3.0,
+    )]);
+
+    image_data.set_clipboard_image(RgbaImage::new(5, 5));
+
+    assert!(image_data.annotations.is_empty());
+}
@@ -0,0 +1,134 @@
+/*
+ * Reading and writing images with clipboard sources
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{Rgba, RgbaImage};
+use shared::{
+    clipboard::{ClipboardSource, MemoryClipboard},
+    error::Error,
+    image_data::{hash_image, ImageData},
+    resolver::SVG_BASE_DPI,
+    settings::{EncoderOptions, SaveFormat},
+};
+
+/// Make an image with a gradient, so every pixel is different
+fn gradient_image(width: u32, height: u32) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |x, y| Rgba([x as u8, y as u8, 0x80, 0xff]))
+}
+
+/// A clipboard that's always held by another application
+struct RefusingClipboard;
+impl ClipboardSource for RefusingClipboard {
+    fn get_image(&mut self) -> Result<RgbaImage, Error> {
+        Err(Error::NoImageInClipboard)
+    }
+
+    fn get_text(&mut self) -> Result<String, Error> {
+        Err(Error::NoImageInClipboard)
+    }
+
+    fn set_image(&mut self, _image_buf: &RgbaImage) -> Result<(), Error> {
+        Err(Error::ClipboardWriteRefused(
+            "held by another application".to_string(),
+        ))
+    }
+}
+
+/***** Tests *****/
+#[test]
+fn reading_an_image_replaces_the_current_one() {
+    let image = gradient_image(30, 20);
+    let mut clipboard = MemoryClipboard::with_image(image.clone());
+    let mut image_data = ImageData::default();
+
+    image_data
+        .read_clipboard(&mut clipboard, SVG_BASE_DPI)
+        .unwrap();
+
+    assert_eq!(image_data.clipboard_dynamic_image.to_rgba8(), image);
+    assert_eq!(image_data.clipboard_image_hash, hash_image(&image));
+}
+
+#[test]
+fn reading_an_empty_clipboard_keeps_the_current_image() {
+    let image = gradient_image(30, 20);
+    let mut image_data = ImageData::default();
+    image_data.set_clipboard_image(image.clone());
+
+    let result = image_data.read_clipboard(&mut MemoryClipboard::default(), SVG_BASE_DPI);
+
+    assert_eq!(result, Err(Error::NoImageInClipboard));
+    assert_eq!(image_data.clipboard_dynamic_image.to_rgba8(), image);
+}
+
+#[test]
+fn different_images_have_different_hashes() {
+    let mut clipboard = MemoryClipboard::with_image(gradient_image(30, 20));
+    let first_hash = hash_image(&clipboard.get_image().unwrap());
+    clipboard.image = Some(gradient_image(20, 30));
+    let second_hash = hash_image(&clipboard.get_image().unwrap());
+
+    assert_ne!(first_hash, second_hash);
+}
+
+#[test]
+fn writing_the_clipboard_round_trips_the_image() {
+    let image = RgbaImage::from_fn(30, 20, |x, y| Rgba([x as u8, y as u8, 0x80, x as u8 * 8]));
+    let mut image_data = ImageData::default();
+    image_data.set_clipboard_image(image.clone());
+    let mut clipboard = MemoryClipboard::default();
+
+    let image_hash = image_data
+        .write_clipboard(&mut clipboard, None, &EncoderOptions::default())
+        .unwrap();
+
+    assert_eq!(clipboard.get_image().unwrap(), image);
+    assert_eq!(image_hash, hash_image(&image));
+}
+
+#[test]
+fn writing_the_clipboard_converts_to_the_format() {
+    let mut image_data = ImageData::default();
+    image_data.set_clipboard_image(RgbaImage::from_pixel(30, 20, Rgba([0xff, 0, 0, 0])));
+    let mut clipboard = MemoryClipboard::default();
+
+    // JPEGs have no transparency, so it's flattened onto white
+    image_data
+        .write_clipboard(
+            &mut clipboard,
+            Some(SaveFormat::Jpg),
+            &EncoderOptions::default(),
+        )
+        .unwrap();
+
+    let written = clipboard.get_image().unwrap();
+    assert_eq!(written.dimensions(), (30, 20));
+    assert!(written
+        .pixels()
+        .all(|pixel| pixel[3] == 0xff && pixel[0] > 0xf0 && pixel[2] > 0xf0));
+}
+
+#[test]
+fn writing_a_refusing_clipboard_fails() {
+    let mut image_data = ImageData::default();
+    image_data.set_clipboard_image(gradient_image(30, 20));
+
+    let result =
+        image_data.write_clipboard(&mut RefusingClipboard, None, &EncoderOptions::default());
+
+    assert!(matches!(result, Err(Error::ClipboardWriteRefused(_))));
+}
+
+#[test]
+fn writing_the_clipboard_without_an_image_fails() {
+    let mut clipboard = MemoryClipboard::default();
+
+    let result =
+        ImageData::default().write_clipboard(&mut clipboard, None, &EncoderOptions::default());
+
+    assert_eq!(result, Err(Error::NoImageInClipboard));
+    assert!(clipboard.image.is_none());
+}
@@ -0,0 +1,56 @@
+/*
+ * Helpers shared by the tests
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+// Each test binary only uses some of the helpers
+#![allow(dead_code)]
+/* Imports */
+use image::{Rgba, RgbaImage};
+use shared::image_data::ImageData;
+use std::{
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+};
+
+/***** Temporary directories *****/
+/// An empty directory of a test's own in the system's temporary directory, which is removed
+/// with everything in it when dropped, even if the test panicked
+pub struct TestDir(PathBuf);
+impl TestDir {
+    /// Make an empty directory. The name has to be unique within a test binary
+    pub fn new(name: &str) -> TestDir {
+        let dir = std::env::temp_dir().join(format!(
+            "clipboard-image-saver-test-{}-{}",
+            process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TestDir(dir)
+    }
+}
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/***** Images *****/
+/// Make image data holding an image with each pixel's color given by its position, e.g.
+/// `image_data(30, 20, |x, y| Rgba([x as u8, y as u8, 0, 0xff]))` for a gradient
+pub fn image_data(width: u32, height: u32, pixel: impl Fn(u32, u32) -> Rgba<u8>) -> ImageData {
+    let mut image_data = ImageData::default();
+    image_data.set_clipboard_image(RgbaImage::from_fn(width, height, pixel));
+    image_data
+}
@@ -0,0 +1,286 @@
+/*
+ * Editing images without changing the original pixels
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{GenericImageView, Rgba, RgbaImage};
+use shared::{
+    error::Error,
+    image_data::ImageData,
+    payloads::{Edit, RedactStyle},
+};
+
+mod common;
+use common::image_data;
+
+/// The color of each pixel of the 30x20 test image, a gradient
+fn gradient(x: u32, y: u32) -> Rgba<u8> {
+    Rgba([x as u8, y as u8, 0x80, 0xff])
+}
+
+/// Decode the image data's preview PNG
+fn encoded_image(image_data: &mut ImageData) -> RgbaImage {
+    image::load_from_memory(&image_data.preview_png().unwrap())
+        .unwrap()
+        .into_rgba8()
+}
+
+/***** Tests *****/
+#[test]
+fn edits_are_applied_in_order_when_encoding() {
+    let mut image_data = image_data(30, 20, gradient);
+    image_data
+        .edit(Edit::Crop {
+            x: 10,
+            y: 5,
+            width: 8,
+            height: 4,
+        })
+        .unwrap();
+    image_data.edit(Edit::Rotate90).unwrap();
+    image_data.edit(Edit::FlipVertical).unwrap();
+
+    let encoded = encoded_image(&mut image_data);
+
+    assert_eq!(encoded.dimensions(), (4, 8));
+    // Rotating clockwise puts the crop's top left corner at the top right, then flipping moves it down
+    assert_eq!(*encoded.get_pixel(3, 7), Rgba([10, 5, 0x80, 0xff]));
+    // The original pixels are kept
+    assert_eq!(image_data.clipboard_dynamic_image.dimensions(), (30, 20));
+}
+
+#[test]
+fn undo_redo_and_reset() {
+    let mut image_data = image_data(30, 20, gradient);
+    image_data.edit(Edit::Rotate90).unwrap();
+    image_data.edit(Edit::FlipHorizontal).unwrap();
+
+    assert!(image_data.edits.undo());
+    assert!(image_data.edits.edits == vec![Edit::Rotate90]);
+    assert!(image_data.edits.redo());
+    assert!(!image_data.edits.redo());
+    assert!(image_data.edits.edits == vec![Edit::Rotate90, Edit::FlipHorizontal]);
+
+    // A new edit forgets what was undone
+    image_data.edits.undo();
+    image_data.edit(Edit::Rotate180).unwrap();
+    assert!(!image_data.edits.redo());
+
+    image_data.edits.reset();
+    assert_eq!(encoded_image(&mut image_data).dimensions(), (30, 20));
+}
+
+#[test]
+fn changes_give_the_image_a_new_revision() {
+    let mut image_data = image_data(30, 20, gradient);
+    let mut revisions = vec![image_data.revision];
+
+    image_data.edit(Edit::Rotate90).unwrap();
+    revisions.push(image_data.revision);
+    assert!(image_data.undo_edit());
+    revisions.push(image_data.revision);
+    assert!(image_data.redo_edit());
+    revisions.push(image_data.revision);
+    image_data.reset_edits();
+    revisions.push(image_data.revision);
+    image_data.set_clipboard_image(RgbaImage::new(2, 2));
+    revisions.push(image_data.revision);
+
+    assert!(revisions.windows(2).all(|pair| pair[0] < pair[1]));
+    // Nothing to undo or redo is no change
+    let revision = image_data.revision;
+    assert!(!image_data.undo_edit() && !image_data.redo_edit());
+    assert!(image_data
+        .edit(Edit::Crop {
+            x: 5,
+            y: 0,
+            width: 1,
+            height: 1
+        })
+        .is_err());
+    assert_eq!(image_data.revision, revision);
+}
+
+#[test]
+fn free_rotation_grows_the_image() {
+    let mut image_data = image_data(30, 20, gradient);
+    image_data.edit(Edit::Rotate { degrees: 90.0 }).unwrap();
+    assert_eq!(image_data.edited_image().dimensions(), (20, 30));
+
+    image_data.edits.reset();
+    image_data.edit(Edit::Rotate { degrees: 45.0 }).unwrap();
+    let rotated = image_data.edited_image().to_rgba8();
+
+    // 30x20 rotated by 45 degrees is about 35.4 pixels each way
+    assert_eq!(rotated.dimensions(), (36, 36));
+    // The corners are outside of the original image, so they're transparent
+    assert_eq!(rotated.get_pixel(0, 0)[3], 0);
+    assert_eq!(rotated.get_pixel(18, 18)[3], 0xff);
+}
+
+#[test]
+fn invalid_edits_are_rejected() {
+    let mut image_data = image_data(30, 20, gradient);
+    let empty_crop = Edit::Crop {
+        x: 0,
+        y: 0,
+        width: 0,
+        height: 10,
+    };
+    let outside_crop = Edit::Crop {
+        x: 30,
+        y: 0,
+        width: 10,
+        height: 10,
+    };
+
+    assert!(matches!(
+        image_data.edit(empty_crop),
+        Err(Error::InvalidEdit(_))
+    ));
+    assert!(matches!(
+        image_data.edit(outside_crop),
+        Err(Error::InvalidEdit(_))
+    ));
+    assert!(matches!(
+        image_data.edit(Edit::Rotate { degrees: f32::NAN }),
+        Err(Error::InvalidEdit(_))
+    ));
+    assert!(matches!(
+        ImageData::default().edit(Edit::Rotate90),
+        Err(Error::NoImageInClipboard)
+    ));
+    assert!(image_data.edits.edits.is_empty());
+}
+
+#[test]
+fn new_images_forget_the_edits() {
+    let mut image_data = image_data(30, 20, gradient);
+    image_data.edit(Edit::Rotate90).unwrap();
+
+    image_data.set_clipboard_image(RgbaImage::new(5, 5));
+
+    assert!(image_data.edits.edits.is_empty());
+}
+
+#[test]
+fn trimming_removes_the_border_within_the_tolerance() {
+    // A 10x6 red block with a nearly white border, which is wider on the left and bottom
+    let mut image_data = image_data(20, 12, |x, y| {
+        match (4..14).contains(&x) && (1..7).contains(&y) {
+            true => Rgba([0xff, 0, 0, 0xff]),
+            false => Rgba([0xff, 0xfc - (x % 2) as u8 * 4, 0xff, 0xff]),
+        }
+    });
+
+    assert!(image_data.auto_trim(8));
+    let trimmed = image_data.edited_image().to_rgba8();
+    assert_eq!(trimmed.dimensions(), (10, 6));
+    assert!(trimmed
+        .pixels()
+        .all(|pixel| *pixel == Rgba([0xff, 0, 0, 0xff])));
+
+    // Below the tolerance, every other column of the border is content, so only the first is trimmed
+    image_data.edits.reset();
+    assert!(image_data.auto_trim(2));
+    assert_eq!(image_data.edited_image().dimensions(), (19, 12));
+}
+
+#[test]
+fn trimming_removes_transparent_borders() {
+    let mut image_data = image_data(8, 8, |x, y| {
+        match x >= 3 && y < 5 {
+            true => Rgba([0x10, 0x20, 0x30, 0xff]),
+            // Transparent pixels match no matter their color
+            false => Rgba([x as u8, y as u8, 0, 0]),
+        }
+    });
+
+    assert!(image_data.auto_trim(0));
+    assert_eq!(image_data.edited_image().dimensions(), (5, 5));
+
+    // An image without a border isn't trimmed
+    assert!(!image_data.auto_trim(0));
+    assert_eq!(image_data.edits.edits.len(), 1);
+}
+
+#[test]
+fn padding_adds_a_border() {
+    let mut image_data = image_data(30, 20, gradient);
+    image_data
+        .edit(Edit::Pad {
+            size: 3,
+            color: "#00ff0080".to_string(),
+        })
+        .unwrap();
+
+    let padded = encoded_image(&mut image_data);
+    assert_eq!(padded.dimensions(), (36, 26));
+    assert_eq!(*padded.get_pixel(0, 0), Rgba([0, 0xff, 0, 0x80]));
+    assert_eq!(*padded.get_pixel(3, 3), Rgba([0, 0, 0x80, 0xff]));
+
+    // Padding and trimming it off again gives back the image
+    image_data.edit(Edit::Trim { tolerance: 0 }).unwrap();
+    assert_eq!(encoded_image(&mut image_data).dimensions(), (30, 20));
+
+    for color in ["#00ff0", "green", "#gg0000"] {
+        assert!(matches!(
+            image_data.edit(Edit::Pad {
+                size: 3,
+                color: color.to_string(),
+            }),
+            Err(Error::InvalidEdit(_))
+        ));
+    }
+    assert!(matches!(
+        image_data.edit(Edit::Pad {
+            size: 0,
+            color: "#ffffff".to_string(),
+        }),
+        Err(Error::InvalidEdit(_))
+    ));
+}
+
+#[test]
+fn edited_sizes_match_the_edited_image() {
+    let mut image_data = image_data(30, 20, gradient);
+    let edits = [
+        Edit::Pad {
+            size: 4,
+            color: "#ffffff".to_string(),
+        },
+        Edit::Rotate { degrees: 30.0 },
+        Edit::Rotate270,
+        // The rotation's transparent corners are trimmed off
+        Edit::Trim { tolerance: 0 },
+        Edit::FlipHorizontal,
+        Edit::Redact {
+            x: 5,
+            y: 5,
+            width: 10,
+            height: 10,
+            style: RedactStyle::Fill,
+        },
+        // Past the right and bottom edges
+        Edit::Crop {
+            x: 3,
+            y: 2,
+            width: 1000,
+            height: 1000,
+        },
+        Edit::Rotate { degrees: -10.0 },
+        Edit::Pad {
+            size: 1,
+            color: "#000000".to_string(),
+        },
+    ];
+    for edit in edits {
+        image_data.edit(edit).unwrap();
+        let edited_size = image_data
+            .edits
+            .edited_size(&image_data.clipboard_dynamic_image);
+        assert_eq!(edited_size, image_data.edited_image().dimensions());
+    }
+}
@@ -0,0 +1,189 @@
+/*
+ * Encoding images in every save format
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgba, RgbaImage};
+use shared::{
+    encoder::{can_encode, encode_image, has_transparency},
+    settings::{
+        EncoderOptions, ResizeFilter, ResizeMode, ResizeOptions, SaveFormat, TransparencyBackground,
+    },
+};
+use std::io::Cursor;
+use strum::IntoEnumIterator;
+
+/// Make an image that's transparent on the left half and opaque red on the right half
+fn half_transparent_image() -> DynamicImage {
+    DynamicImage::ImageRgba8(RgbaImage::from_fn(64, 48, |x, _| match x < 32 {
+        true => Rgba([0, 0, 0, 0]),
+        false => Rgba([0xff, 0, 0, 0xff]),
+    }))
+}
+
+/// Encode an image, returning the encoded bytes
+fn encode(image: &DynamicImage, format: &SaveFormat, options: &EncoderOptions) -> Vec<u8> {
+    let mut cursor = Cursor::new(Vec::new());
+    encode_image(image, format, options, &mut cursor).unwrap();
+    cursor.into_inner()
+}
+
+/***** Tests *****/
+#[test]
+fn every_format_encodes_and_decodes() {
+    let image = half_transparent_image();
+    let options = EncoderOptions::default();
+
+    for format in SaveFormat::iter().filter(can_encode) {
+        let encoded = encode(&image, &format, &options);
+        assert!(!encoded.is_empty(), "{} encoded to nothing", format);
+
+        // The `image` crate can't decode ICNSs or PAMs with alpha, so only check their headers
+        match format {
+            SaveFormat::Icns => {
+                assert_eq!(&encoded[..4], b"icns");
+                continue;
+            }
+            SaveFormat::Pam => {
+                assert_eq!(&encoded[..2], b"P7");
+                continue;
+            }
+            _ => (),
+        }
+        // TGAs have no magic bytes, so the format is given
+        let image_format = ImageFormat::from_extension(format.extension()).unwrap();
+        let decoded = image::load_from_memory_with_format(&encoded, image_format)
+            .unwrap_or_else(|e| panic!("{} didn't decode: {}", format, e));
+        assert_eq!(decoded.dimensions(), (64, 48), "{} changed size", format);
+    }
+}
+
+#[test]
+fn formats_without_alpha_are_flattened_onto_the_background() {
+    let image = half_transparent_image();
+    let options = EncoderOptions {
+        transparency_background: TransparencyBackground::Color,
+        transparency_color: "#00ff00".to_string(),
+        ..EncoderOptions::default()
+    };
+
+    let decoded = image::load_from_memory(&encode(&image, &SaveFormat::Ppm, &options)).unwrap();
+
+    assert_eq!(decoded.get_pixel(0, 0), Rgba([0, 0xff, 0, 0xff]));
+    assert_eq!(decoded.get_pixel(63, 0), Rgba([0xff, 0, 0, 0xff]));
+}
+
+#[test]
+fn formats_with_alpha_keep_transparency() {
+    let image = half_transparent_image();
+    assert!(has_transparency(&image));
+
+    let encoded = encode(&image, &SaveFormat::Png, &EncoderOptions::default());
+
+    assert!(has_transparency(
+        &image::load_from_memory(&encoded).unwrap()
+    ));
+}
+
+#[test]
+fn jpeg_quality_is_used() {
+    let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(64, 48, |x, y| {
+        Rgba([x as u8 * 4, y as u8 * 5, (x ^ y) as u8 * 4, 0xff])
+    }));
+    let encode_with_quality = |jpeg_quality| {
+        let options = EncoderOptions {
+            jpeg_quality,
+            ..EncoderOptions::default()
+        };
+        encode(&image, &SaveFormat::Jpg, &options).len()
+    };
+
+    assert!(encode_with_quality(10) < encode_with_quality(75));
+    assert!(encode_with_quality(75) < encode_with_quality(100));
+}
+
+#[test]
+fn multi_size_ico_holds_the_biggest_size() {
+    let image = half_transparent_image();
+    let options = EncoderOptions {
+        ico_multi_size: true,
+        icon_sizes: vec![16, 32, 128],
+        ..EncoderOptions::default()
+    };
+
+    let encoded = encode(&image, &SaveFormat::Ico, &options);
+
+    // ICOs start with a 6 byte header, whose last 2 bytes are the image count
+    assert_eq!(u16::from_le_bytes([encoded[4], encoded[5]]), 3);
+    // The `image` crate decodes the biggest image in an ICO
+    let decoded = image::load_from_memory(&encoded).unwrap();
+    assert_eq!(decoded.dimensions(), (128, 128));
+}
+
+#[test]
+fn resize_modes_give_the_expected_sizes() {
+    let resize = |mode: ResizeMode, width: u32, height: u32| ResizeOptions {
+        mode,
+        width,
+        height,
+        percentage: 50,
+        max_edge: 1000,
+        ..ResizeOptions::default()
+    };
+
+    assert_eq!(
+        resize(ResizeMode::None, 10, 10).output_size(3000, 2000),
+        (3000, 2000)
+    );
+    assert_eq!(
+        resize(ResizeMode::Exact, 100, 100).output_size(3000, 2000),
+        (100, 100)
+    );
+    assert_eq!(
+        resize(ResizeMode::Exact, 300, 0).output_size(3000, 2000),
+        (300, 200)
+    );
+    assert_eq!(
+        resize(ResizeMode::Percentage, 0, 0).output_size(3000, 2000),
+        (1500, 1000)
+    );
+    assert_eq!(
+        resize(ResizeMode::Fit, 600, 600).output_size(3000, 2000),
+        (600, 400)
+    );
+    // Images that already fit aren't enlarged
+    assert_eq!(
+        resize(ResizeMode::Fit, 600, 600).output_size(300, 200),
+        (300, 200)
+    );
+    assert_eq!(
+        resize(ResizeMode::MaxEdge, 0, 0).output_size(2000, 3000),
+        (667, 1000)
+    );
+    assert_eq!(
+        resize(ResizeMode::MaxEdge, 0, 0).output_size(800, 600),
+        (800, 600)
+    );
+}
+
+#[test]
+fn images_are_resized_when_encoded() {
+    let image = half_transparent_image();
+    let options = EncoderOptions {
+        resize: ResizeOptions {
+            mode: ResizeMode::Percentage,
+            percentage: 50,
+            filter: ResizeFilter::Nearest,
+            ..ResizeOptions::default()
+        },
+        ..EncoderOptions::default()
+    };
+
+    let decoded = image::load_from_memory(&encode(&image, &SaveFormat::Png, &options)).unwrap();
+
+    assert_eq!(decoded.dimensions(), (32, 24));
+    assert_eq!(decoded.get_pixel(0, 0), Rgba([0, 0, 0, 0]));
+    assert_eq!(decoded.get_pixel(31, 0), Rgba([0xff, 0, 0, 0xff]));
+}
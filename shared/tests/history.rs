@@ -0,0 +1,122 @@
+/*
+ * Keeping captured images in the history
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{DynamicImage, Rgba, RgbaImage};
+use shared::{error::Error, history::History, settings::Settings};
+use std::fs;
+mod common;
+use common::TestDir;
+
+/// Make an image filled with a color, which is also used as its hash
+fn image(width: u32, height: u32, color: u8) -> (DynamicImage, u64) {
+    let image = RgbaImage::from_pixel(width, height, Rgba([color, 0, 0, 0xff]));
+    (DynamicImage::ImageRgba8(image), color as u64)
+}
+
+/// Get the IDs of the entries, from oldest to newest
+fn ids(history: &History) -> Vec<u64> {
+    history.entries.iter().map(|entry| entry.id).collect()
+}
+
+/***** Tests *****/
+#[test]
+fn the_same_image_isnt_added_twice_in_a_row() {
+    let dir = TestDir::new("history-dedupe");
+    let mut history = History::load(&dir);
+    let (red, red_hash) = image(4, 4, 0xff);
+    let (black, black_hash) = image(4, 4, 0);
+
+    history.add(&red, red_hash, 10).unwrap();
+    history.add(&red, red_hash, 10).unwrap();
+    assert_eq!(ids(&history), [0]);
+
+    // Only the newest entry is compared, so going back to an image adds it again
+    history.add(&black, black_hash, 10).unwrap();
+    history.add(&red, red_hash, 10).unwrap();
+    assert_eq!(ids(&history), [0, 1, 2]);
+}
+
+#[test]
+fn the_oldest_entries_are_removed_past_the_limit() {
+    let dir = TestDir::new("history-limit");
+    let mut history = History::load(&dir);
+    for color in 0..4 {
+        let (image, hash) = image(4, 4, color);
+        history.add(&image, hash, 2).unwrap();
+    }
+    assert_eq!(ids(&history), [2, 3]);
+    assert!(!dir.join("0.png").exists() && !dir.join("1.thumb.png").exists());
+    assert!(matches!(
+        history.load_image(1),
+        Err(Error::HistoryEntryNotFound(1))
+    ));
+
+    // A limit of 0 turns the history off
+    let (image, hash) = image(4, 4, 0xff);
+    history.add(&image, hash, 0).unwrap();
+    assert_eq!(ids(&history), [2, 3]);
+}
+
+#[test]
+fn the_history_is_off_by_default() {
+    let dir = TestDir::new("history-default");
+    let mut history = History::load(&dir);
+    let (image, hash) = image(4, 4, 0xff);
+    history
+        .add(&image, hash, Settings::default().history_limit)
+        .unwrap();
+    assert!(history.entries.is_empty());
+    assert_eq!(fs::read_dir(&*dir).unwrap().count(), 0);
+}
+
+#[test]
+fn entries_keep_the_image_and_a_thumbnail() {
+    let dir = TestDir::new("history-images");
+    let mut history = History::load(&dir);
+    let (image, hash) = image(300, 150, 0x80);
+    history.add(&image, hash, 10).unwrap();
+
+    let entry = &history.entries[0];
+    assert!(entry.width == 300 && entry.height == 150);
+    assert_eq!(entry.hash, format!("{:016x}", hash));
+    assert_eq!(history.load_image(0).unwrap().to_rgba8(), image.to_rgba8());
+    // Thumbnails keep the aspect ratio
+    let thumbnail = image::load_from_memory(&history.load_thumbnail(0).unwrap()).unwrap();
+    assert_eq!((thumbnail.width(), thumbnail.height()), (128, 64));
+    // Only the index, image and thumbnail are left, without any temporary files
+    assert_eq!(fs::read_dir(&*dir).unwrap().count(), 3);
+
+    history.remove(0).unwrap();
+    assert!(history.entries.is_empty());
+    assert!(matches!(
+        history.remove(0),
+        Err(Error::HistoryEntryNotFound(0))
+    ));
+    assert_eq!(fs::read_dir(&*dir).unwrap().count(), 1);
+}
+
+#[test]
+fn the_index_is_reloaded() {
+    let dir = TestDir::new("history-index");
+    let mut history = History::load(&dir.join("history"));
+    assert!(history.entries.is_empty());
+    // Nothing is written until an image is added
+    assert!(!dir.join("history").exists());
+
+    for color in 0..3 {
+        let (image, hash) = image(4, 4, color);
+        history.add(&image, hash, 10).unwrap();
+    }
+    history.remove(1).unwrap();
+    let reloaded = History::load(&dir.join("history"));
+    assert!(reloaded.entries == history.entries);
+    assert_eq!(ids(&reloaded), [0, 2]);
+
+    // A broken index is an empty history
+    fs::write(dir.join("history/index.json"), "[{").unwrap();
+    assert!(History::load(&dir.join("history")).entries.is_empty());
+}
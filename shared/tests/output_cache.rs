@@ -0,0 +1,161 @@
+/*
+ * Reusing encoded outputs until the image changes
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{Rgba, RgbaImage};
+use shared::{
+    error::Error,
+    image_data::ImageData,
+    output_cache::{OutputCache, OutputKey},
+    payloads::Edit,
+    settings::{EncoderOptions, SaveFormat},
+};
+use std::sync::{Arc, RwLock};
+mod common;
+use common::image_data;
+
+/// The color of each pixel of the 16x16 test image, a gradient
+fn gradient(x: u32, y: u32) -> Rgba<u8> {
+    Rgba([x as u8 * 16, y as u8 * 16, 0x80, 0xff])
+}
+
+/// Make a key for the cache of a revision with a JPEG quality, so keys can differ by options
+fn key(revision: u64, jpeg_quality: u8) -> OutputKey {
+    OutputKey {
+        revision,
+        format: SaveFormat::Jpg,
+        options: EncoderOptions {
+            jpeg_quality,
+            ..EncoderOptions::default()
+        },
+        with_annotations: true,
+    }
+}
+
+/***** Tests *****/
+#[test]
+fn outputs_are_reused_per_format_and_options() {
+    let image_data = image_data(16, 16, gradient);
+    let options = EncoderOptions::default();
+
+    let png = image_data.encoded(SaveFormat::Png, &options).unwrap();
+    let bmp = image_data.encoded(SaveFormat::Bmp, &options).unwrap();
+    // Switching back to a format doesn't encode it again
+    assert!(Arc::ptr_eq(
+        &png,
+        &image_data.encoded(SaveFormat::Png, &options).unwrap()
+    ));
+    assert!(Arc::ptr_eq(
+        &bmp,
+        &image_data.encoded(SaveFormat::Bmp, &options).unwrap()
+    ));
+    // Other options are another output
+    let fast_png = image_data
+        .encoded(
+            SaveFormat::Png,
+            &EncoderOptions {
+                png_compression: shared::settings::PngCompression::Fast,
+                ..EncoderOptions::default()
+            },
+        )
+        .unwrap();
+    assert!(!Arc::ptr_eq(&png, &fast_png));
+
+    let stats = image_data.outputs.stats();
+    assert!(stats.hits == 2 && stats.misses == 3);
+    assert_eq!(stats.entries, 3);
+    assert_eq!(stats.bytes, (png.len() + bmp.len() + fast_png.len()) as u64);
+}
+
+#[test]
+fn edits_and_new_images_invalidate_the_outputs() {
+    let mut image_data = image_data(16, 16, gradient);
+    let options = EncoderOptions::default();
+    let original = image_data.encoded(SaveFormat::Bmp, &options).unwrap();
+
+    image_data.edit(Edit::Rotate90).unwrap();
+    assert_eq!(image_data.outputs.stats().entries, 0);
+    let rotated = image_data.encoded(SaveFormat::Bmp, &options).unwrap();
+    assert!(original != rotated);
+
+    image_data.set_clipboard_image(RgbaImage::new(2, 2));
+    assert_eq!(image_data.outputs.stats().entries, 0);
+    let pasted = image_data.encoded(SaveFormat::Bmp, &options).unwrap();
+    assert!(pasted != rotated);
+    assert_eq!(image_data.outputs.stats().misses, 3);
+}
+
+#[test]
+fn snapshots_share_the_outputs() {
+    let mut image_data = image_data(16, 16, gradient);
+    let options = EncoderOptions::default();
+
+    // A save of a snapshot fills the cache for the state
+    let saved = image_data
+        .snapshot()
+        .encoded(SaveFormat::Png, &options)
+        .unwrap();
+    assert!(Arc::ptr_eq(
+        &saved,
+        &image_data.encoded(SaveFormat::Png, &options).unwrap()
+    ));
+
+    // Unless the state changed before the save finished
+    let snapshot = image_data.snapshot();
+    image_data.edit(Edit::FlipHorizontal).unwrap();
+    snapshot.encoded(SaveFormat::Bmp, &options).unwrap();
+    assert_eq!(image_data.outputs.stats().entries, 0);
+}
+
+#[test]
+fn previews_are_served_from_the_state() {
+    let state = RwLock::new(image_data(16, 16, gradient));
+
+    // Encoded once from a snapshot, then the cached one is shared
+    let first = ImageData::preview_png_of(&state, Some(1)).unwrap();
+    let second = ImageData::preview_png_of(&state, None).unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    let stats = state.read().unwrap().outputs.stats();
+    assert_eq!((stats.hits, stats.misses), (1, 1));
+    assert!(Arc::ptr_eq(
+        &first,
+        &state.read().unwrap().preview_png().unwrap()
+    ));
+
+    // Older revisions are gone once the image changes
+    state.write().unwrap().edit(Edit::FlipVertical).unwrap();
+    assert!(matches!(
+        ImageData::preview_png_of(&state, Some(1)),
+        Err(Error::OutdatedRevision(1))
+    ));
+    assert!(!Arc::ptr_eq(
+        &first,
+        &ImageData::preview_png_of(&state, Some(2)).unwrap()
+    ));
+}
+
+#[test]
+fn least_recently_used_outputs_are_dropped() {
+    let cache = OutputCache::default();
+    for quality in 1..=8 {
+        cache.insert(key(1, quality), Arc::new(vec![quality]));
+    }
+    // Using the oldest one makes the second oldest the one dropped
+    assert!(cache.get(&key(1, 1)).is_some());
+    cache.insert(key(1, 9), Arc::new(vec![9]));
+
+    assert!(cache.get(&key(1, 2)).is_none());
+    for quality in [1, 3, 9] {
+        assert!(cache.get(&key(1, quality)).is_some(), "{}", quality);
+    }
+    assert_eq!(cache.stats().entries, 8);
+
+    // Outputs of older revisions than the cached ones aren't kept
+    cache.insert(key(2, 1), Arc::new(vec![1]));
+    cache.insert(key(1, 2), Arc::new(vec![2]));
+    assert_eq!(cache.stats().entries, 1);
+    assert!(cache.get(&key(1, 2)).is_none());
+}
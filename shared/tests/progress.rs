@@ -0,0 +1,141 @@
+/*
+ * Reporting the progress of saves, canceling them and saving snapshots
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{Rgba, RgbaImage};
+use shared::{
+    error::Error,
+    payloads::{Edit, SaveStage},
+    progress::Progress,
+    settings::{EncoderOptions, SaveFormat},
+};
+use std::sync::{Arc, Mutex};
+mod common;
+use common::{image_data, TestDir};
+
+/// The width and height of the test image, which is 4 MB as an uncompressed TIFF
+const SIZE: u32 = 1024;
+
+/// The color of each pixel of the test image
+fn pattern(x: u32, y: u32) -> Rgba<u8> {
+    Rgba([x as u8, y as u8, (x ^ y) as u8, 0xff])
+}
+
+/// The stages and encoded bytes a progress reported
+type Reports = Arc<Mutex<Vec<(SaveStage, u64)>>>;
+
+/// Make a progress that keeps what it reports
+fn recorded_progress() -> (Progress, Reports) {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let progress = {
+        let reports = reports.clone();
+        Progress::new(move |stage, bytes_encoded| {
+            reports.lock().unwrap().push((stage, bytes_encoded))
+        })
+    };
+    (progress, reports)
+}
+
+/***** Tests *****/
+#[test]
+fn saving_reports_each_stage() {
+    let dir = TestDir::new("progress");
+    let path = dir.join("image.tiff");
+    let (progress, reports) = recorded_progress();
+
+    image_data(SIZE, SIZE, pattern)
+        .save_to_file_with_progress(
+            &path.to_string_lossy(),
+            SaveFormat::Tiff,
+            &EncoderOptions::default(),
+            &progress,
+        )
+        .unwrap();
+    let file_size = std::fs::metadata(&path).unwrap().len();
+
+    let reports = reports.lock().unwrap();
+    let stages: Vec<SaveStage> = reports.iter().map(|(stage, _)| *stage).collect();
+    assert!(stages[0] == SaveStage::Rendering && stages[1] == SaveStage::Encoding);
+    assert!(*reports.last().unwrap() == (SaveStage::Writing, file_size));
+    // The encoded bytes are reported as they're written, and only go up
+    let encoded: Vec<u64> = reports
+        .iter()
+        .filter(|(stage, _)| *stage == SaveStage::Encoding)
+        .map(|(_, bytes_encoded)| *bytes_encoded)
+        .collect();
+    assert!(encoded.len() > 2);
+    assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn canceled_saves_leave_no_file() {
+    // Canceled before starting
+    let dir = TestDir::new("canceled");
+    let path = dir.join("early.png");
+    let progress = Progress::default();
+    progress.cancel();
+    assert!(matches!(
+        image_data(SIZE, SIZE, pattern).save_to_file_with_progress(
+            &path.to_string_lossy(),
+            SaveFormat::Png,
+            &EncoderOptions::default(),
+            &progress,
+        ),
+        Err(Error::Canceled)
+    ));
+    assert!(!path.exists());
+
+    // Canceled while encoding, from another clone like the backend does
+    let path = dir.join("encoding.tiff");
+    let canceler = Arc::new(Mutex::new(None::<Progress>));
+    let progress = {
+        let canceler = canceler.clone();
+        Progress::new(move |stage, bytes_encoded| {
+            if stage == SaveStage::Encoding && bytes_encoded > 0 {
+                if let Some(progress) = &*canceler.lock().unwrap() {
+                    progress.cancel();
+                }
+            }
+        })
+    };
+    *canceler.lock().unwrap() = Some(progress.clone());
+    assert!(matches!(
+        image_data(SIZE, SIZE, pattern).save_to_file_with_progress(
+            &path.to_string_lossy(),
+            SaveFormat::Tiff,
+            &EncoderOptions::default(),
+            &progress,
+        ),
+        Err(Error::Canceled)
+    ));
+    // Not even the temporary file is left
+    assert_eq!(std::fs::read_dir(&*dir).unwrap().count(), 0);
+}
+
+#[test]
+fn snapshots_are_saved_as_they_were_taken() {
+    let mut image_data = image_data(SIZE, SIZE, pattern);
+    image_data.edit(Edit::Rotate90).unwrap();
+    let snapshot = image_data.snapshot();
+
+    // Changing the state doesn't change a save that's running
+    image_data
+        .edit(Edit::Crop {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+        })
+        .unwrap();
+    image_data.set_clipboard_image(RgbaImage::new(1, 1));
+
+    assert_eq!(snapshot.revision + 2, image_data.revision);
+    let preview = image::load_from_memory(&snapshot.preview_png().unwrap())
+        .unwrap()
+        .into_rgba8();
+    assert_eq!(*preview.get_pixel(1023, 0), Rgba([0, 0, 0, 0xff]));
+    assert_eq!(*preview.get_pixel(0, 0), Rgba([0, 0xff, 0xff, 0xff]));
+}
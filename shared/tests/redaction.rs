@@ -0,0 +1,142 @@
+/*
+ * Redacting parts of images so the original pixels can't be recovered
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{GenericImageView, Rgba, RgbaImage};
+use shared::{
+    clipboard::MemoryClipboard,
+    error::Error,
+    image_data::ImageData,
+    payloads::{Edit, RedactStyle},
+    settings::{EncoderOptions, SaveFormat},
+};
+mod common;
+use common::TestDir;
+
+/// Where the secret is in the test image, as [x, y, width, height]
+const SECRET: [u32; 4] = [10, 10, 16, 8];
+
+/// Whether a pixel is in the secret
+fn in_secret(x: u32, y: u32) -> bool {
+    let [secret_x, secret_y, width, height] = SECRET;
+    (secret_x..secret_x + width).contains(&x) && (secret_y..secret_y + height).contains(&y)
+}
+
+/// Make a 40x30 image with a checkerboard secret, like text, on a gray background
+fn original_image() -> RgbaImage {
+    RgbaImage::from_fn(40, 30, |x, y| match (in_secret(x, y), (x + y) % 2) {
+        (true, 0) => Rgba([40, 40, 40, 0xff]),
+        (true, _) => Rgba([220, 220, 220, 0xff]),
+        (false, _) => Rgba([0x80, 0x80, 0x80, 0xff]),
+    })
+}
+
+/// Make image data with the secret redacted
+fn redacted_image_data(style: RedactStyle) -> ImageData {
+    let [x, y, width, height] = SECRET;
+    let mut image_data = ImageData::default();
+    image_data.set_clipboard_image(original_image());
+    image_data
+        .edit(Edit::Redact {
+            x,
+            y,
+            width,
+            height,
+            style,
+        })
+        .unwrap();
+    image_data
+}
+
+/// Check that no pixel of the secret is left, and that everything else is untouched
+fn assert_secret_is_gone(redacted: &RgbaImage) {
+    let original = original_image();
+    assert_eq!(redacted.dimensions(), original.dimensions());
+    for (x, y, pixel) in redacted.enumerate_pixels() {
+        match in_secret(x, y) {
+            true => assert_ne!(pixel, original.get_pixel(x, y), "at {}, {}", x, y),
+            false => assert_eq!(pixel, original.get_pixel(x, y), "at {}, {}", x, y),
+        }
+    }
+}
+
+/***** Tests *****/
+#[test]
+fn saved_images_have_no_secret_pixels() {
+    let dir = TestDir::new("redacted");
+    let path = dir.join("image.png");
+
+    for style in [RedactStyle::Fill, RedactStyle::Blur, RedactStyle::Pixelate] {
+        redacted_image_data(style)
+            .save_to_file(
+                &path.to_string_lossy(),
+                SaveFormat::Png,
+                &EncoderOptions::default(),
+            )
+            .unwrap();
+
+        assert_secret_is_gone(&image::open(&path).unwrap().into_rgba8());
+    }
+}
+
+#[test]
+fn copied_images_have_no_secret_pixels() {
+    for style in [RedactStyle::Fill, RedactStyle::Blur, RedactStyle::Pixelate] {
+        let mut clipboard = MemoryClipboard::default();
+        redacted_image_data(style)
+            .write_clipboard(&mut clipboard, None, &EncoderOptions::default())
+            .unwrap();
+
+        assert_secret_is_gone(&clipboard.image.unwrap());
+    }
+}
+
+#[test]
+fn redaction_styles() {
+    let filled = redacted_image_data(RedactStyle::Fill)
+        .edited_image()
+        .to_rgba8();
+    assert_eq!(
+        *filled.get_pixel(SECRET[0], SECRET[1]),
+        Rgba([0, 0, 0, 0xff])
+    );
+
+    // The secret is 16x8, so it's pixelated into two 8x8 blocks of the checkerboard's average
+    let pixelated = redacted_image_data(RedactStyle::Pixelate)
+        .edited_image()
+        .to_rgba8();
+    assert_eq!(
+        *pixelated.get_pixel(SECRET[0], SECRET[1]),
+        Rgba([130, 130, 130, 0xff])
+    );
+    assert_eq!(
+        *pixelated.get_pixel(SECRET[0] + 15, SECRET[1] + 7),
+        Rgba([130, 130, 130, 0xff])
+    );
+}
+
+#[test]
+fn redacting_keeps_the_original_until_saving() {
+    let mut image_data = redacted_image_data(RedactStyle::Fill);
+
+    // Undoing brings the secret back, as the edit is only applied when encoding
+    assert!(image_data.clipboard_dynamic_image.to_rgba8() == original_image());
+    image_data.edits.undo();
+    assert!(image_data.edited_image().to_rgba8() == original_image());
+
+    let outside = Edit::Redact {
+        x: 40,
+        y: 0,
+        width: 5,
+        height: 5,
+        style: RedactStyle::Blur,
+    };
+    assert!(matches!(
+        image_data.edit(outside),
+        Err(Error::InvalidEdit(_))
+    ));
+    assert_eq!(image_data.edited_image().dimensions(), (40, 30));
+}
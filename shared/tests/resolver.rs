@@ -0,0 +1,221 @@
+/*
+ * Resolving clipboard content that isn't a bitmap
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::{ImageOutputFormat, Rgba, RgbaImage};
+use shared::{
+    clipboard::MemoryClipboard,
+    error::Error,
+    image_data::ImageData,
+    payloads::ImageSource,
+    resolver::{resolve_image, resolve_text, SVG_BASE_DPI},
+};
+use std::io::Cursor;
+mod common;
+use common::TestDir;
+
+/// Make an image with a gradient, so every pixel is different
+fn gradient_image() -> RgbaImage {
+    RgbaImage::from_fn(30, 20, |x, y| Rgba([x as u8, y as u8, 0x80, 0xff]))
+}
+
+/// Encode an image as a base64 PNG data URL
+fn data_url(image: &RgbaImage) -> String {
+    let mut cursor = Cursor::new(Vec::new());
+    image.write_to(&mut cursor, ImageOutputFormat::Png).unwrap();
+    format!(
+        "data:image/png;base64,{}",
+        base64::encode(cursor.into_inner())
+    )
+}
+
+/***** Tests *****/
+#[test]
+fn bitmaps_are_preferred_over_text() {
+    let image = gradient_image();
+    let mut clipboard = MemoryClipboard::with_image(image.clone());
+    clipboard.text = Some(data_url(&RgbaImage::new(1, 1)));
+
+    let (resolved, source) = resolve_image(&mut clipboard, SVG_BASE_DPI).unwrap();
+
+    assert_eq!(resolved, image);
+    assert!(source == ImageSource::Bitmap);
+}
+
+#[test]
+fn data_urls_are_decoded() {
+    let image = gradient_image();
+
+    let (resolved, source) = resolve_text(&data_url(&image), SVG_BASE_DPI).unwrap();
+
+    assert_eq!(resolved, image);
+    assert!(source == ImageSource::DataUrl);
+}
+
+#[test]
+fn copied_files_are_opened() {
+    let dir = TestDir::new("file-list");
+    let image = gradient_image();
+    let path = dir.join("copied image.png");
+    image.save(&path).unwrap();
+    let uri = format!(
+        "file://{}",
+        path.to_string_lossy()
+            .replace(' ', "%20")
+            .replace('\\', "/")
+    );
+
+    // GNOME's list says whether the file was copied or cut first, and URI lists can have comments
+    let uri_list = format!("copy\n# A comment\n{}\n", uri);
+    let mut image_data = ImageData::default();
+    image_data
+        .read_clipboard(&mut MemoryClipboard::with_text(&uri_list), SVG_BASE_DPI)
+        .unwrap();
+
+    assert_eq!(image_data.clipboard_dynamic_image.to_rgba8(), image);
+    assert!(image_data.clipboard_image_source == ImageSource::FileList);
+}
+
+#[test]
+fn html_img_tags_are_resolved() {
+    let image = gradient_image();
+    let html = format!(
+        "<p>Look:</p><img alt=\"remote\" src=\"https://example.com/a.png\"><IMG data-src=\"x\" SRC='{}' />",
+        data_url(&image)
+    );
+
+    let (resolved, source) = resolve_text(&html, SVG_BASE_DPI).unwrap();
+
+    assert_eq!(resolved, image);
+    assert!(source == ImageSource::Html);
+}
+
+#[test]
+fn text_without_an_image_fails() {
+    for text in ["Hello world", "relative/path.png", "<p>No images</p>", ""] {
+        let result = resolve_text(text, SVG_BASE_DPI);
+        assert!(
+            result.err() == Some(Error::NoImageInClipboard),
+            "{:?}",
+            text
+        );
+    }
+}
+
+#[test]
+fn data_urls_that_arent_base64_fail() {
+    let result = resolve_text("data:image/png;base64,not*base64", SVG_BASE_DPI);
+
+    assert!(matches!(result, Err(Error::ClipboardDecodeFailed(_))));
+}
+
+#[test]
+fn data_urls_that_arent_images_fail() {
+    let result = resolve_text("data:image/png;base64,bm90IGFuIGltYWdl", SVG_BASE_DPI);
+
+    assert!(matches!(result, Err(Error::ClipboardDecodeFailed(_))));
+}
+
+#[test]
+fn copied_files_that_arent_images_fail() {
+    let dir = TestDir::new("broken-file");
+    let path = dir.join("broken.png");
+    std::fs::write(&path, "not an image").unwrap();
+
+    let result = resolve_text(&path.to_string_lossy(), SVG_BASE_DPI);
+
+    match result {
+        Err(Error::DecodeFailed {
+            path: failed_path, ..
+        }) => {
+            assert_eq!(failed_path, path.to_string_lossy())
+        }
+        _ => panic!("The file was decoded"),
+    }
+}
+
+#[test]
+fn broken_svg_markup_fails() {
+    let result = resolve_text("<svg><rect", SVG_BASE_DPI);
+
+    assert!(matches!(result, Err(Error::ClipboardDecodeFailed(_))));
+}
+
+#[test]
+fn broken_svg_data_urls_fail() {
+    let result = resolve_text("data:image/svg+xml,%3Csvg%3E%3Crect", SVG_BASE_DPI);
+
+    assert!(matches!(result, Err(Error::ClipboardDecodeFailed(_))));
+}
+
+#[test]
+fn broken_svg_files_fail() {
+    let dir = TestDir::new("broken-svg-file");
+    let path = dir.join("broken.svg");
+    std::fs::write(&path, "<svg><rect").unwrap();
+
+    let result = resolve_text(&path.to_string_lossy(), SVG_BASE_DPI);
+
+    match result {
+        Err(Error::DecodeFailed {
+            path: failed_path, ..
+        }) => {
+            assert_eq!(failed_path, path.to_string_lossy())
+        }
+        _ => panic!("The SVG was rasterized"),
+    }
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn svgs_are_rasterized_at_the_dpi() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="40" height="20"><rect width="40" height="20" fill="red"/></svg>"#;
+
+    let (actual_size, source) = resolve_text(svg, SVG_BASE_DPI).unwrap();
+    let (double_size, _) = resolve_text(svg, SVG_BASE_DPI * 2.0).unwrap();
+
+    assert!(source == ImageSource::Svg);
+    assert_eq!(actual_size.dimensions(), (40, 20));
+    assert_eq!(double_size.dimensions(), (80, 40));
+    assert_eq!(*actual_size.get_pixel(20, 10), Rgba([0xff, 0, 0, 0xff]));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn svgs_without_a_size_use_their_view_box() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 30 60"/>"#;
+
+    let (resolved, _) = resolve_text(svg, SVG_BASE_DPI * 1.5).unwrap();
+
+    assert_eq!(resolved.dimensions(), (45, 90));
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn svg_data_urls_and_files_are_rasterized_at_the_dpi() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="30"/>"#;
+    let data_url = format!("data:image/svg+xml;base64,{}", base64::encode(svg));
+    let dir = TestDir::new("svg-file");
+    let path = dir.join("drawing.SVG");
+    std::fs::write(&path, svg).unwrap();
+
+    let (from_data_url, source) = resolve_text(&data_url, SVG_BASE_DPI * 3.0).unwrap();
+    assert_eq!(from_data_url.dimensions(), (30, 90));
+    assert!(source == ImageSource::DataUrl);
+
+    let uri_list = format!("file://{}", path.to_string_lossy().replace('\\', "/"));
+    let (from_file, source) = resolve_text(&uri_list, SVG_BASE_DPI / 2.0).unwrap();
+    assert_eq!(from_file.dimensions(), (5, 15));
+    assert!(source == ImageSource::FileList);
+}
+
+#[cfg(not(feature = "svg"))]
+#[test]
+fn svgs_need_the_svg_feature() {
+    let result = resolve_text(r#"<svg width="40" height="20"></svg>"#, SVG_BASE_DPI);
+
+    assert!(matches!(result, Err(Error::ClipboardDecodeFailed(_))));
+}
@@ -0,0 +1,252 @@
+/*
+ * Saving images with templates and collision policies, and opening them again
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use image::Rgba;
+use shared::{
+    error::Error,
+    image_data::ImageData,
+    payloads::SaveOutcome,
+    settings::{CollisionPolicy, SaveFormat, Settings},
+    template,
+};
+use std::path::Path;
+mod common;
+use common::{image_data, TestDir};
+
+/// The color of each pixel of the 30x20 test image, a gradient
+fn gradient(x: u32, y: u32) -> Rgba<u8> {
+    Rgba([x as u8, y as u8, 0, 0xff])
+}
+
+/// Make settings with a collision policy
+fn settings_with_policy(collision_policy: CollisionPolicy) -> Settings {
+    Settings {
+        collision_policy,
+        ..Settings::default()
+    }
+}
+
+/***** Tests *****/
+#[test]
+fn saving_expands_the_template() {
+    let dir = TestDir::new("template");
+    // Missing folders are created, even ones named by placeholders
+    let template = dir.join("{format}/image-{width}x{height}.{format}");
+
+    let outcome = image_data(30, 20, gradient)
+        .save_with_template(
+            &template.to_string_lossy(),
+            SaveFormat::Webp,
+            &Settings::default(),
+            None,
+            &dir.join("save_counter"),
+        )
+        .unwrap();
+
+    let expected_path = dir.join("webp/image-30x20.webp");
+    assert!(outcome == SaveOutcome::Saved(expected_path.to_string_lossy().to_string()));
+    let saved = image::open(&expected_path).unwrap();
+    assert_eq!((saved.width(), saved.height()), (30, 20));
+}
+
+#[test]
+fn a_leading_tilde_is_the_home_folder() {
+    let home_dir = dirs_next::home_dir().unwrap();
+
+    assert_eq!(
+        Path::new(&template::expand_home("~/Pictures/image.png")),
+        home_dir.join("Pictures/image.png")
+    );
+    assert_eq!(Path::new(&template::expand_home("~")), home_dir);
+    // Other users' home folders and tildes elsewhere are left alone
+    for path in ["~user/image.png", "./~/image.png", "image~.png"] {
+        assert_eq!(template::expand_home(path), path);
+    }
+}
+
+#[test]
+fn existing_files_follow_the_collision_policy() {
+    let dir = TestDir::new("collision");
+    let path = dir.join("image.png").to_string_lossy().to_string();
+    let image_data = image_data(30, 20, gradient);
+    image_data
+        .save_with_template(
+            &path,
+            SaveFormat::Png,
+            &Settings::default(),
+            None,
+            &dir.join("save_counter"),
+        )
+        .unwrap();
+
+    let ask = image_data
+        .save_with_template(
+            &path,
+            SaveFormat::Png,
+            &settings_with_policy(CollisionPolicy::Ask),
+            None,
+            &dir.join("save_counter"),
+        )
+        .unwrap();
+    assert!(ask == SaveOutcome::Exists(path.clone()));
+
+    let skip = image_data
+        .save_with_template(
+            &path,
+            SaveFormat::Png,
+            &settings_with_policy(CollisionPolicy::Skip),
+            None,
+            &dir.join("save_counter"),
+        )
+        .unwrap();
+    assert!(skip == SaveOutcome::Skipped(path.clone()));
+
+    let rename = image_data
+        .save_with_template(
+            &path,
+            SaveFormat::Png,
+            &settings_with_policy(CollisionPolicy::Rename),
+            None,
+            &dir.join("save_counter"),
+        )
+        .unwrap();
+    let renamed_path = dir.join("image-1.png");
+    assert!(rename == SaveOutcome::Saved(renamed_path.to_string_lossy().to_string()));
+    assert!(renamed_path.exists());
+}
+
+#[test]
+fn saving_without_an_image_fails() {
+    let dir = TestDir::new("no-image");
+    let path = dir.join("image.png");
+
+    let result = ImageData::default().save_with_template(
+        &path.to_string_lossy(),
+        SaveFormat::Png,
+        &Settings::default(),
+        None,
+        &dir.join("save_counter"),
+    );
+
+    assert!(result == Err(Error::NoImageInClipboard));
+    assert!(!path.exists());
+}
+
+#[test]
+fn confirmed_paths_are_overwritten() {
+    let dir = TestDir::new("confirmed");
+    let path = dir.join("image.png").to_string_lossy().to_string();
+    std::fs::write(&path, "not an image").unwrap();
+
+    let outcome = image_data(30, 20, gradient)
+        .save_with_template(
+            &path,
+            SaveFormat::Png,
+            &settings_with_policy(CollisionPolicy::Ask),
+            Some(path.clone()),
+            &dir.join("save_counter"),
+        )
+        .unwrap();
+
+    assert!(outcome == SaveOutcome::Saved(path.clone()));
+    assert!(image::open(&path).is_ok());
+}
+
+#[test]
+fn saved_images_can_be_opened_again() {
+    let dir = TestDir::new("open");
+    let path = dir.join("image.tiff").to_string_lossy().to_string();
+    let saved = image_data(30, 20, gradient);
+    saved
+        .save_with_template(
+            &path,
+            SaveFormat::Tiff,
+            &Settings::default(),
+            None,
+            &dir.join("save_counter"),
+        )
+        .unwrap();
+
+    let mut opened = ImageData::default();
+    opened.open_file(&path).unwrap();
+
+    assert_eq!(
+        opened.clipboard_dynamic_image.to_rgba8(),
+        saved.clipboard_dynamic_image.to_rgba8()
+    );
+    assert_eq!(opened.clipboard_image_hash, saved.clipboard_image_hash);
+}
+
+#[test]
+fn opening_a_file_that_isnt_an_image_fails() {
+    let dir = TestDir::new("open-invalid");
+    let path = dir.join("image.png").to_string_lossy().to_string();
+    std::fs::write(&path, "not an image").unwrap();
+    let missing_path = dir.join("missing.png").to_string_lossy().to_string();
+    let mut image_data = ImageData::default();
+
+    assert!(matches!(
+        image_data.open_file(&path),
+        Err(Error::DecodeFailed { .. })
+    ));
+    assert!(matches!(
+        image_data.open_file(&missing_path),
+        Err(Error::IoError { .. })
+    ));
+    assert_eq!(image_data.clipboard_dynamic_image.width(), 0);
+}
+
+#[test]
+fn the_counter_is_incremented_after_each_save() {
+    let dir = TestDir::new("counter");
+    let counter_path = dir.join("data/save_counter");
+    let path_template = dir
+        .join("image-{counter}.png")
+        .to_string_lossy()
+        .to_string();
+    let image_data = image_data(30, 20, gradient);
+    assert_eq!(template::load_counter(&counter_path), 1);
+
+    for counter in [1, 2] {
+        let outcome = image_data
+            .save_with_template(
+                &path_template,
+                SaveFormat::Png,
+                &Settings::default(),
+                None,
+                &counter_path,
+            )
+            .unwrap();
+        let expected_path = dir.join(format!("image-{}.png", counter));
+        assert!(outcome == SaveOutcome::Saved(expected_path.to_string_lossy().to_string()));
+    }
+    assert_eq!(template::load_counter(&counter_path), 3);
+
+    // The counter persists, and is left alone by templates without it
+    template::save_counter(&counter_path, 7).unwrap();
+    image_data
+        .save_with_template(
+            &dir.join("image.png").to_string_lossy(),
+            SaveFormat::Png,
+            &Settings::default(),
+            None,
+            &counter_path,
+        )
+        .unwrap();
+    let outcome = image_data
+        .save_with_template(
+            &path_template,
+            SaveFormat::Png,
+            &Settings::default(),
+            None,
+            &counter_path,
+        )
+        .unwrap();
+    let expected_path = dir.join("image-7.png");
+    assert!(outcome == SaveOutcome::Saved(expected_path.to_string_lossy().to_string()));
+    assert_eq!(template::load_counter(&counter_path), 8);
+}
@@ -0,0 +1,222 @@
+/*
+ * Migrating settings files saved by older versions
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use serde_json::Value;
+use shared::{
+    error::Error,
+    settings::{CollisionPolicy, SaveFormat, Settings, SETTINGS_VERSION},
+    settings_file,
+};
+use std::{fs, path::Path};
+mod common;
+use common::TestDir;
+
+/// Settings files as each version saved them, from the oldest. The first is the example in the
+/// repository root, the second has the first version's defaults, and the others are named after
+/// what they added
+const FIXTURES: [&str; 13] = [
+    "../settings.json",
+    "tests/fixtures/settings/v0-defaults.json",
+    "tests/fixtures/settings/v0-clipboard-watcher.json",
+    "tests/fixtures/settings/v0-history.json",
+    "tests/fixtures/settings/v0-collision-policy.json",
+    "tests/fixtures/settings/v0-encoder-options.json",
+    "tests/fixtures/settings/v0-transparency.json",
+    "tests/fixtures/settings/v0-icons.json",
+    "tests/fixtures/settings/v0-svg.json",
+    "tests/fixtures/settings/v0-resize.json",
+    "tests/fixtures/settings/v0-auto-trim.json",
+    "tests/fixtures/settings/v0-adjustments.json",
+    "tests/fixtures/settings/v1.json",
+];
+/// How many fixtures are from before the collision policy was added
+const BEFORE_COLLISION_POLICY: usize = 4;
+/// The fixture with the first version's defaults, whose save path had no extension
+const FIRST_DEFAULTS: usize = 1;
+
+/// Read a fixture
+fn read_fixture(fixture: &str) -> String {
+    fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(fixture)).unwrap()
+}
+
+/// Write settings to `settings.json` in an empty directory of their own, as loading them can
+/// write backups next to them
+fn settings_dir(name: &str, settings_text: &str) -> TestDir {
+    let dir = TestDir::new(name);
+    fs::write(dir.join("settings.json"), settings_text).unwrap();
+    dir
+}
+
+/// Check every value in the fixture is still in the parsed settings
+fn assert_kept(fixture: &str, saved: &Value, parsed: &Value) {
+    match (saved, parsed) {
+        (Value::Object(saved), Value::Object(parsed)) => {
+            for (key, value) in saved {
+                assert_kept(fixture, value, &parsed[key]);
+            }
+        }
+        _ => assert_eq!(saved, parsed, "{}", fixture),
+    }
+}
+
+/***** Tests *****/
+#[test]
+fn every_version_parses_keeping_its_values() {
+    for (index, fixture) in FIXTURES.iter().enumerate() {
+        let settings_text = read_fixture(fixture);
+        let parsed = match Settings::parse(settings_text.clone()) {
+            Ok(parsed) => parsed,
+            Err(e) => panic!("{}: {}", fixture, e),
+        };
+        assert_eq!(parsed.version, SETTINGS_VERSION, "{}", fixture);
+
+        // Older files always overwrote, which they keep doing
+        let mut saved: Value = serde_json::from_str(&settings_text).unwrap();
+        if index < BEFORE_COLLISION_POLICY {
+            assert!(parsed.collision_policy == CollisionPolicy::Overwrite);
+            saved.as_object_mut().unwrap().remove("collision_policy");
+        }
+        // Save paths without an extension get their format's
+        if let Some(Value::String(save_path)) = saved.get("save_path") {
+            if *save_path != parsed.save_path {
+                let extended = format!("{}.{}", save_path, parsed.save_format.extension());
+                assert_eq!(parsed.save_path, extended, "{}", fixture);
+                saved.as_object_mut().unwrap().remove("save_path");
+            }
+        }
+        saved.as_object_mut().unwrap().remove("version");
+        assert_kept(fixture, &saved, &serde_json::to_value(&parsed).unwrap());
+    }
+}
+
+#[test]
+fn missing_fields_get_their_defaults() {
+    let parsed = Settings::parse(read_fixture(FIXTURES[0])).unwrap();
+    let defaults = Settings::default();
+
+    assert_eq!(parsed.save_path, "./image.ico");
+    assert!(parsed.save_format == SaveFormat::Ico);
+    assert_eq!(parsed.zoom_by, 10);
+    assert_eq!(parsed.history_limit, defaults.history_limit);
+    assert!(parsed.encoder_options == defaults.encoder_options);
+    assert_eq!(parsed.svg_dpi, defaults.svg_dpi);
+    assert!(parsed.adjustment_presets.is_empty());
+
+    // Nested options that gained fields too
+    let parsed = Settings::parse(read_fixture(FIXTURES[5])).unwrap();
+    assert_eq!(parsed.encoder_options.jpeg_quality, 90);
+    assert!(parsed.encoder_options.resize == defaults.encoder_options.resize);
+    assert_eq!(
+        parsed.encoder_options.icon_sizes,
+        defaults.encoder_options.icon_sizes
+    );
+}
+
+#[test]
+fn extensionless_save_paths_get_their_formats_extension() {
+    // The first version's defaults can be saved again once migrated
+    let parsed = Settings::parse(read_fixture(FIXTURES[FIRST_DEFAULTS])).unwrap();
+    assert_eq!(parsed.save_path, "./image.png");
+    assert!(settings_file::validate_settings(&parsed).is_ok());
+
+    for (save_path, save_format, migrated_path) in [
+        (
+            r"C:\Shots\shot-{counter}",
+            "Jpg",
+            r"C:\Shots\shot-{counter}.jpg",
+        ),
+        (
+            "./screenshots.d/image",
+            "Tiff",
+            "./screenshots.d/image.tiff",
+        ),
+        ("./image.ico", "Ico", "./image.ico"),
+        ("./image.jpeg", "Jpg", "./image.jpeg"),
+    ] {
+        let settings_text = format!(
+            r#"{{"save_path":{:?},"save_format":"{}"}}"#,
+            save_path, save_format
+        );
+        assert_eq!(
+            Settings::parse(settings_text).unwrap().save_path,
+            migrated_path
+        );
+    }
+
+    // Only older versions are migrated
+    let settings_text = format!(
+        r#"{{"version":{},"save_path":"./image"}}"#,
+        SETTINGS_VERSION
+    );
+    assert_eq!(Settings::parse(settings_text).unwrap().save_path, "./image");
+}
+
+#[test]
+fn old_files_are_migrated_in_place_with_a_backup() {
+    let original = read_fixture(FIXTURES[3]);
+    let dir = settings_dir("migrated", &original);
+    let path = dir.join("settings.json");
+    let backup_path = path.with_file_name("settings.v0.json");
+
+    let migrated = settings_file::load_settings_file(&path).unwrap().unwrap();
+    assert_eq!(fs::read_to_string(&backup_path).unwrap(), original);
+    assert_eq!(fs::read_to_string(&path).unwrap(), migrated);
+    let parsed = Settings::parse(migrated.clone()).unwrap();
+    assert_eq!(parsed.version, SETTINGS_VERSION);
+    assert!(parsed == Settings::parse(original).unwrap());
+
+    // It's only migrated once
+    assert_eq!(
+        settings_file::load_settings_file(&path).unwrap().unwrap(),
+        migrated
+    );
+    assert!(!path.with_file_name("settings.v0-1.json").exists());
+}
+
+#[test]
+fn current_newer_and_broken_files_are_left_alone() {
+    for (name, settings_text) in [
+        ("current", read_fixture(FIXTURES[12])),
+        // e.g. saved by a newer build, whose new fields are ignored
+        (
+            "newer",
+            r#"{"version":99,"zoom_by":3,"new_field":true}"#.to_string(),
+        ),
+    ] {
+        let dir = settings_dir(name, &settings_text);
+        let path = dir.join("settings.json");
+        assert_eq!(
+            settings_file::load_settings_file(&path).unwrap().unwrap(),
+            settings_text
+        );
+        assert_eq!(fs::read_dir(&*dir).unwrap().count(), 1);
+    }
+    let newer = Settings::parse(r#"{"version":99,"zoom_by":3}"#.to_string()).unwrap();
+    assert!(newer.version == 99 && newer.zoom_by == 3);
+
+    for (name, settings_text) in [
+        ("broken", r#"{"zoom_by":"#),
+        ("wrong-type", r#"{"zoom_by":"ten"}"#),
+        ("not-an-object", r#"[]"#),
+    ] {
+        let dir = settings_dir(name, settings_text);
+        let path = dir.join("settings.json");
+        assert!(matches!(
+            settings_file::load_settings_file(&path),
+            Err(Error::SettingsParse(_))
+        ));
+        assert_eq!(fs::read_to_string(&path).unwrap(), settings_text);
+        assert_eq!(fs::read_dir(&*dir).unwrap().count(), 1);
+    }
+
+    let dir = TestDir::new("missing");
+    assert!(
+        settings_file::load_settings_file(&dir.join("settings.json"))
+            .unwrap()
+            .is_none()
+    );
+}
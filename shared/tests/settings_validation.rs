@@ -0,0 +1,162 @@
+/*
+ * Validating settings before they're saved
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use shared::{
+    error::Error,
+    settings::{SaveFormat, Settings, MAX_ZOOM_BY, MIN_ZOOM_BY, SETTINGS_VERSION},
+    settings_file,
+};
+use std::{fs, path::Path};
+mod common;
+use common::TestDir;
+
+/// Get the fields validating the settings found errors in, in order
+fn invalid_fields(settings: &Settings) -> Vec<String> {
+    match settings_file::validate_settings(settings) {
+        Ok(_) => Vec::new(),
+        Err(Error::InvalidSettings(errors)) => {
+            errors.into_iter().map(|error| error.field).collect()
+        }
+        Err(e) => panic!("{}", e),
+    }
+}
+
+/***** Tests *****/
+#[test]
+fn zoom_by_must_be_in_range() {
+    for zoom_by in [MIN_ZOOM_BY, 10, MAX_ZOOM_BY] {
+        let settings = Settings {
+            zoom_by,
+            ..Settings::default()
+        };
+        assert!(invalid_fields(&settings).is_empty(), "{}", zoom_by);
+    }
+    for zoom_by in [-5, 0, MAX_ZOOM_BY + 1, i32::MAX] {
+        let settings = Settings {
+            zoom_by,
+            ..Settings::default()
+        };
+        assert_eq!(invalid_fields(&settings), ["zoom_by"], "{}", zoom_by);
+    }
+}
+
+#[test]
+fn extension_must_match_the_format() {
+    let dir = TestDir::new("extension");
+    for (file_name, save_format, valid) in [
+        ("image.png", SaveFormat::Png, true),
+        ("IMAGE.PNG", SaveFormat::Png, true),
+        ("image-{counter}.{format}", SaveFormat::Bmp, true),
+        // Other extensions of the same format
+        ("image.jpeg", SaveFormat::Jpg, true),
+        ("image.tif", SaveFormat::Tiff, true),
+        ("image.jpg", SaveFormat::Png, false),
+        ("image-{counter}", SaveFormat::Png, false),
+        ("image.png.bak", SaveFormat::Png, false),
+    ] {
+        let settings = Settings {
+            save_path: dir.join(file_name).to_string_lossy().to_string(),
+            save_format,
+            ..Settings::default()
+        };
+        match valid {
+            true => assert!(invalid_fields(&settings).is_empty(), "{}", file_name),
+            false => assert_eq!(invalid_fields(&settings), ["save_path"], "{}", file_name),
+        }
+    }
+}
+
+#[test]
+fn save_directory_must_be_creatable() {
+    let dir = TestDir::new("directory");
+    let invalid_save_path_fields = |save_path: &Path| {
+        invalid_fields(&Settings {
+            save_path: save_path.to_string_lossy().to_string(),
+            ..Settings::default()
+        })
+    };
+
+    // Missing folders are fine, but they're only created once an image is saved
+    assert!(invalid_save_path_fields(&dir.join("screenshots/{date}/image.png")).is_empty());
+    assert!(!dir.join("screenshots").exists());
+    assert!(invalid_fields(&Settings {
+        save_path: "~/screenshots/image.png".to_string(),
+        ..Settings::default()
+    })
+    .is_empty());
+
+    // A file is in the way
+    fs::write(dir.join("file"), "").unwrap();
+    assert_eq!(
+        invalid_save_path_fields(&dir.join("file/screenshots/image.png")),
+        ["save_path"]
+    );
+
+    // The folder can't be written to
+    let read_only = dir.join("read-only");
+    fs::create_dir(&read_only).unwrap();
+    let permissions = fs::metadata(&read_only).unwrap().permissions();
+    let mut read_only_permissions = permissions.clone();
+    read_only_permissions.set_readonly(true);
+    fs::set_permissions(&read_only, read_only_permissions).unwrap();
+    let fields = invalid_save_path_fields(&read_only.join("new/image.png"));
+    // It has to be writable again to be cleaned up
+    fs::set_permissions(&read_only, permissions).unwrap();
+    assert_eq!(fields, ["save_path"]);
+}
+
+#[test]
+fn every_invalid_setting_is_reported() {
+    let dir = TestDir::new("every-error");
+    fs::write(dir.join("file"), "").unwrap();
+    let settings = Settings {
+        save_path: dir
+            .join("file/screenshots/image.jpg")
+            .to_string_lossy()
+            .to_string(),
+        zoom_by: 0,
+        ..Settings::default()
+    };
+
+    // The extension doesn't match the format, and a file is in the way
+    assert_eq!(
+        invalid_fields(&settings),
+        ["save_path", "save_path", "zoom_by"]
+    );
+    assert!(!dir.join("file/screenshots").exists());
+}
+
+#[test]
+fn only_valid_settings_are_saved() {
+    let dir = TestDir::new("saving");
+    let path = dir.join("config/settings.json");
+
+    let invalid = Settings {
+        zoom_by: 0,
+        ..Settings::default()
+    };
+    let result = settings_file::save_settings_file(&path, serde_json::to_string(&invalid).unwrap());
+    assert!(matches!(result, Err(Error::InvalidSettings(errors)) if errors.len() == 1));
+    assert!(!path.exists());
+    assert!(matches!(
+        settings_file::save_settings_file(&path, r#"{"zoom_by":"#.to_string()),
+        Err(Error::SettingsParse(_))
+    ));
+    assert!(!path.exists());
+
+    // Saved settings have every field, even if the frontend sent only some of them
+    let settings_text = format!(r#"{{"version":{},"zoom_by":25}}"#, SETTINGS_VERSION);
+    settings_file::save_settings_file(&path, settings_text).unwrap();
+    let saved = Settings::parse(fs::read_to_string(&path).unwrap()).unwrap();
+    assert!(
+        saved
+            == Settings {
+                zoom_by: 25,
+                ..Settings::default()
+            }
+    );
+}
@@ -0,0 +1,275 @@
+/*
+ * Headless command line interface
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use shared::{
+    clipboard::ArboardClipboard, get_counter_path, get_settings_path, image_data::ImageData,
+    payloads, settings, settings_file,
+};
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+/***** Constants *****/
+const USAGE: &str = "\
+Usage: clipboard-image-saver [save [OPTIONS]]
+
+Without a command, the window is opened.
+
+Commands:
+  save                   Save the image in the clipboard and exit
+
+Options for save:
+  -o, --output <PATH>    Where to save the image. Can be a template like the save path setting
+                         Defaults to the save path setting
+  -f, --format <FORMAT>  The format to save as, e.g. png or webp. Defaults to the output's
+                         extension, or else the save format setting
+      --overwrite        Overwrite the file if it exists, instead of following the collision
+                         policy setting
+  -h, --help             Show this help";
+
+/***** Arguments *****/
+/// The options for the `save` command
+#[derive(Default)]
+struct SaveArgs {
+    output: Option<String>,
+    format: Option<String>,
+    overwrite: bool,
+    help: bool,
+}
+
+/// Parse the arguments after `save`
+fn parse_save_args(args: &[String]) -> Result<SaveArgs, String> {
+    let mut save_args = SaveArgs::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => match args.next() {
+                Some(output) => save_args.output = Some(output.clone()),
+                None => return Err(format!("{} needs a path", arg)),
+            },
+            "-f" | "--format" => match args.next() {
+                Some(format) => save_args.format = Some(format.clone()),
+                None => return Err(format!("{} needs a format", arg)),
+            },
+            "--overwrite" => save_args.overwrite = true,
+            "-h" | "--help" => save_args.help = true,
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+    Ok(save_args)
+}
+
+/// Find a save format by its name or extension, ignoring case
+fn parse_format(name: &str) -> Option<settings::SaveFormat> {
+    settings::SaveFormat::iter().find(|format| {
+        format.to_string().eq_ignore_ascii_case(name)
+            || format.extension().eq_ignore_ascii_case(name)
+    })
+}
+
+/***** Running *****/
+/// Run the command line interface if a command was given, returning the exit code.
+/// If there's no command, `None` is returned and the window should be opened instead
+pub fn run(args: &[String]) -> Option<i32> {
+    let result = match args.first().map(String::as_str) {
+        None => return None,
+        Some("-h" | "--help") => {
+            attach_parent_console();
+            println!("{}", USAGE);
+            return Some(0);
+        }
+        Some("save") => {
+            attach_parent_console();
+            match parse_save_args(&args[1..]) {
+                Ok(save_args) if save_args.help => {
+                    println!("{}", USAGE);
+                    return Some(0);
+                }
+                Ok(save_args) => save(save_args),
+                Err(e) => {
+                    eprintln!("{}\n\n{}", e, USAGE);
+                    return Some(2);
+                }
+            }
+        }
+        // Anything else, e.g. arguments added by the OS or a window manager, opens the window
+        Some(_) => return None,
+    };
+
+    match result {
+        Ok(saved_path) => {
+            println!("{}", saved_path);
+            Some(0)
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            Some(1)
+        }
+    }
+}
+
+/// Attach to the console of the process that ran this one (e.g. a terminal or a script).
+/// Release builds on Windows are GUI applications without a console of their own, so what the
+/// commands print would otherwise go nowhere
+#[cfg(windows)]
+fn attach_parent_console() {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    /// Attach to the parent's console rather than the console of a process ID
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    // This fails if there's no console to attach to (e.g. when run from Explorer) or there
+    // already is one (e.g. in debug builds), where there's nothing better to do anyway
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+/// Other platforms keep the console of the process that ran this one
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
+/// Load the settings file the window saves, or the default settings if there isn't one
+fn load_settings() -> Result<settings::Settings, String> {
+    match settings_file::load_settings_file(&get_settings_path()) {
+        Ok(Some(file_text)) => match settings::Settings::parse(file_text) {
+            Ok(settings) => Ok(settings),
+            Err(e) => Err(e.to_string()),
+        },
+        Ok(None) => Ok(settings::Settings::default()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Save the image in the clipboard, returning the path it was saved to
+fn save(save_args: SaveArgs) -> Result<String, String> {
+    let mut current_settings = match load_settings() {
+        Ok(current_settings) => current_settings,
+        Err(e) => return Err(e),
+    };
+    if save_args.overwrite {
+        current_settings.collision_policy = settings::CollisionPolicy::Overwrite;
+    }
+    let path = save_args
+        .output
+        .unwrap_or_else(|| current_settings.save_path.clone());
+    let format = match save_args.format {
+        Some(format) => match parse_format(&format) {
+            Some(format) => format,
+            None => return Err(format!("Unknown save format {}", format)),
+        },
+        None => Path::new(&path)
+            .extension()
+            .and_then(|extension| parse_format(&extension.to_string_lossy()))
+            .unwrap_or_else(|| current_settings.save_format.clone()),
+    };
+
+    // Get the image from the clipboard
+    let mut clipboard = match ArboardClipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(e) => return Err(e.to_string()),
+    };
+    let mut image_data = ImageData::default();
+    match image_data.read_clipboard(&mut clipboard, current_settings.svg_dpi) {
+        Ok(_) => (),
+        Err(e) => return Err(e.to_string()),
+    };
+    if current_settings.auto_trim {
+        image_data.auto_trim(current_settings.trim_tolerance);
+    }
+    // Only the saved path goes to stdout, so scripts can use it
+    if image_data.clipboard_image_source != payloads::ImageSource::Bitmap {
+        eprintln!(
+            "Pasted from {}",
+            image_data.clipboard_image_source.description()
+        );
+    }
+
+    let counter_path = get_counter_path();
+    match image_data.save_with_template(&path, format, &current_settings, None, &counter_path) {
+        Ok(payloads::SaveOutcome::Saved(saved_path)) => Ok(saved_path),
+        Ok(payloads::SaveOutcome::Exists(existing_path)) => Err(format!(
+            "{} already exists. Use --overwrite to overwrite it",
+            existing_path
+        )),
+        Ok(payloads::SaveOutcome::Skipped(existing_path)) => Err(format!(
+            "{} already exists, so the image wasn't saved",
+            existing_path
+        )),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/***** Tests *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse `save` arguments given as string slices
+    fn parse(args: &[&str]) -> Result<SaveArgs, String> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        parse_save_args(&args)
+    }
+
+    #[test]
+    fn no_arguments_use_the_settings() {
+        let save_args = parse(&[]).unwrap();
+        assert!(save_args.output.is_none() && save_args.format.is_none());
+        assert!(!save_args.overwrite && !save_args.help);
+    }
+
+    #[test]
+    fn options_are_parsed_in_any_order() {
+        for args in [
+            ["-o", "shot.png", "-f", "webp", "--overwrite"],
+            ["--overwrite", "--format", "webp", "--output", "shot.png"],
+        ] {
+            let save_args = parse(&args).unwrap();
+            assert_eq!(save_args.output.as_deref(), Some("shot.png"));
+            assert_eq!(save_args.format.as_deref(), Some("webp"));
+            assert!(save_args.overwrite && !save_args.help);
+        }
+    }
+
+    #[test]
+    fn help_can_be_asked_for() {
+        assert!(parse(&["-h"]).unwrap().help);
+        assert!(parse(&["-o", "shot.png", "--help"]).unwrap().help);
+    }
+
+    #[test]
+    fn unknown_arguments_fail() {
+        assert_eq!(
+            parse(&["--nope"]).err().as_deref(),
+            Some("Unknown argument --nope")
+        );
+        // A value without an option isn't taken as the output
+        assert_eq!(
+            parse(&["shot.png"]).err().as_deref(),
+            Some("Unknown argument shot.png")
+        );
+    }
+
+    #[test]
+    fn options_without_a_value_fail() {
+        assert_eq!(parse(&["-o"]).err().as_deref(), Some("-o needs a path"));
+        assert_eq!(
+            parse(&["--overwrite", "--format"]).err().as_deref(),
+            Some("--format needs a format")
+        );
+    }
+
+    #[test]
+    fn formats_are_found_by_name_or_extension() {
+        assert!(parse_format("webp") == Some(settings::SaveFormat::Webp));
+        assert!(parse_format("JPG") == Some(settings::SaveFormat::Jpg));
+        assert!(parse_format("farbfeld") == Some(settings::SaveFormat::Farbfeld));
+        assert!(parse_format("ff") == Some(settings::SaveFormat::Farbfeld));
+        assert!(parse_format("bitmap").is_none());
+        assert!(parse_format("").is_none());
+    }
+}
@@ -0,0 +1,93 @@
+/*
+ * Running saves on a worker pool, with progress events and cancellation
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use shared::{
+    error::Error,
+    payloads::{SaveProgress, SaveStage},
+    progress::Progress,
+};
+use std::{collections::HashMap, sync::Mutex};
+use tauri::{async_runtime, AppHandle, Manager};
+
+/***** Constants *****/
+/// The event emitted to the frontend with a `SaveProgress` while a save runs
+pub const SAVE_PROGRESS_EVENT: &str = "save-progress";
+
+/***** State *****/
+/// The saves that are running, which can be canceled
+#[derive(Default)]
+pub struct Jobs {
+    next_id: u64,
+    running: HashMap<u64, Progress>,
+}
+#[derive(Default)]
+pub struct JobsState(pub Mutex<Jobs>);
+
+/***** Running *****/
+/// Run blocking work (e.g. encoding) on the worker pool, so it doesn't freeze the window
+pub async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, Error> + Send + 'static,
+) -> Result<T, Error> {
+    match async_runtime::spawn_blocking(work).await {
+        Ok(result) => result,
+        Err(e) => Err(Error::Other(format!("The background task failed: {}", e))),
+    }
+}
+
+/// Run a save on the worker pool. Its progress is emitted to the frontend, ending with the
+/// `Finished` stage whether it succeeded or not, and it can be canceled with `cancel_jobs`
+pub async fn run_save_job<T: Send + 'static>(
+    app_handle: AppHandle,
+    job: impl FnOnce(&Progress) -> Result<T, Error> + Send + 'static,
+) -> Result<T, Error> {
+    let emit = {
+        let app_handle = app_handle.clone();
+        move |job_id: u64, stage: SaveStage, bytes_encoded: u64| {
+            let _ = app_handle.emit_all(
+                SAVE_PROGRESS_EVENT,
+                SaveProgress {
+                    job_id,
+                    stage,
+                    bytes_encoded,
+                },
+            );
+        }
+    };
+
+    let (job_id, progress) = match app_handle.state::<JobsState>().0.lock() {
+        Ok(mut jobs_guard) => {
+            jobs_guard.next_id += 1;
+            let job_id = jobs_guard.next_id;
+            let progress = {
+                let emit = emit.clone();
+                Progress::new(move |stage, bytes_encoded| emit(job_id, stage, bytes_encoded))
+            };
+            jobs_guard.running.insert(job_id, progress.clone());
+            (job_id, progress)
+        }
+        Err(_) => return Err(Error::LockPoisoned("jobs".to_string())),
+    };
+
+    let result = run_blocking(move || job(&progress)).await;
+
+    if let Ok(mut jobs_guard) = app_handle.state::<JobsState>().0.lock() {
+        jobs_guard.running.remove(&job_id);
+    }
+    emit(job_id, SaveStage::Finished, 0);
+    result
+}
+
+/// Cancel every running save
+pub fn cancel_jobs(jobs_state: &JobsState) -> Result<(), Error> {
+    match jobs_state.0.lock() {
+        Ok(jobs_guard) => {
+            jobs_guard.running.values().for_each(Progress::cancel);
+            Ok(())
+        }
+        Err(_) => Err(Error::LockPoisoned("jobs".to_string())),
+    }
+}
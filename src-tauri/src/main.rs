@@ -9,128 +9,275 @@
     windows_subsystem = "windows"
 )]
 #![feature(const_io_structs)]
-#![allow(clippy::significant_drop_in_scrutinee)]
+#![allow(clippy::significant_drop_in_scrutinee, clippy::question_mark)]
 
 /* Imports */
 extern crate base64;
-use arboard::Clipboard;
-use image::{DynamicImage, ImageBuffer, ImageFormat, ImageOutputFormat, RgbaImage};
 #[allow(unused_imports)]
 use serde::{Deserialize, Serialize};
+use shared::{
+    clipboard::{ArboardClipboard, ClipboardSource},
+    encoder,
+    error::Error,
+    get_counter_path, get_history_dir, get_settings_path,
+    history::History,
+    image_data::{self, ImageData},
+    payloads, settings, settings_file, template,
+};
 use std::{
-    fs::{create_dir_all, read_to_string, write},
-    io::{Cursor, ErrorKind},
     path::PathBuf,
     str::FromStr,
-    sync::RwLock,
+    sync::{Mutex, RwLock},
 };
+use strum::IntoEnumIterator;
 use tauri::{
-    api::{
-        dialog::blocking::FileDialogBuilder,
-        path::{local_data_dir, picture_dir},
-    },
-    State,
+    api::{dialog::blocking::FileDialogBuilder, path::picture_dir},
+    AppHandle, Manager, State,
 };
-#[path = "../../shared/settings.rs"]
-mod settings;
+mod cli;
+mod jobs;
+mod protocol;
+mod watcher;
 
 /***** Globals *****/
 #[derive(Default)]
+pub struct SettingsState(pub RwLock<settings::Settings>);
+#[derive(Default)]
 pub struct ImageDataState(pub RwLock<ImageData>);
+pub struct HistoryState(pub RwLock<History>);
+/// The clipboard handler images are copied with, and the hash of the last image copied.
+/// The handler is kept alive, as on Linux the copied image is only served while it exists
 #[derive(Default)]
-pub struct ImageData {
-    /// The clipboard image encoded as a dynamic image
-    pub clipboard_dynamic_image: DynamicImage,
-    /// The image converted to whatever format the user selected
-    /// The vector must be wrapped in a cursor so it satisfies `Seek` and `Read` traits
-    pub clipboard_image_cursor: Cursor<Vec<u8>>,
-}
-impl ImageData {
-    /// Convert an image to the format specified
-    pub fn convert_encoded_cursor_with_format(
-        &mut self,
-        format: settings::SaveFormat,
-    ) -> Result<(), String> {
-        let format = ImageOutputFormat::from(
-            ImageFormat::from_extension((format).to_string().to_lowercase()).unwrap(),
-        );
-
-        // Clear cursor
-        self.clipboard_image_cursor.set_position(0);
-        self.clipboard_image_cursor.get_mut().clear();
-
-        // Write into cursor
-        match self
-            .clipboard_dynamic_image
-            .write_to(&mut self.clipboard_image_cursor, format)
-        {
-            Ok(_) => (),
-            Err(e) => {
-                return Err(format!(
-                    "Failed to write dynamic image from clipboard into buffer: {}",
-                    e
-                ))
-            }
-        }
+pub struct CopiedClipboard {
+    pub clipboard: Option<ArboardClipboard>,
+    pub image_hash: Option<u64>,
+}
+#[derive(Default)]
+pub struct ClipboardState(pub Mutex<CopiedClipboard>);
 
-        Ok(())
+/// The event emitted to the frontend with an `Error` when an image couldn't be added to the history
+const HISTORY_ERROR_EVENT: &str = "history-error";
+
+/***** Auxiliary functions *****/
+/// Get a copy of the settings synced from the frontend
+fn get_settings(settings_state: &SettingsState) -> Result<settings::Settings, Error> {
+    match settings_state.0.read() {
+        Ok(settings_guard) => Ok(settings_guard.clone()),
+        Err(_) => Err(Error::LockPoisoned("settings".to_string())),
     }
 }
 
-/***** Auxiliary functions *****/
-/// Get the settings path
-fn get_settings_path() -> PathBuf {
-    match local_data_dir() {
-        Some(local_data_dir) => local_data_dir.join("clipboard-image-saver/settings.json"),
-        None => PathBuf::from("settings.json"),
+/// Add the current image to the history, if the settings allow it.
+/// Failing to do so shouldn't stop the image from being shown, so errors are emitted to the
+/// frontend as a `HISTORY_ERROR_EVENT` instead of being returned.
+/// It's given a snapshot rather than the state, so the image isn't locked while it's stored
+fn add_to_history(app_handle: &AppHandle, image_data: &ImageData) {
+    let result = match get_settings(&app_handle.state::<SettingsState>()) {
+        Ok(current_settings) => match app_handle.state::<HistoryState>().0.write() {
+            Ok(mut history_guard) => history_guard.add(
+                &image_data.clipboard_dynamic_image,
+                image_data.clipboard_image_hash,
+                current_settings.history_limit,
+            ),
+            Err(_) => Err(Error::LockPoisoned("history".to_string())),
+        },
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        let _ = app_handle.emit_all(HISTORY_ERROR_EVENT, e);
     }
 }
 
-/***** Commands *****/
-/// Read the clipboard image information.
-#[tauri::command]
-fn read_clipboard(state: State<ImageDataState>) -> Result<String, String> {
+/// Change the edits of the current image, returning its revision like `read_clipboard` does
+fn change_edits(
+    state: &ImageDataState,
+    change: impl FnOnce(&mut ImageData) -> Result<(), Error>,
+) -> Result<u64, Error> {
     let mut state_guard = match state.0.write() {
         Ok(state_guard) => state_guard,
-        Err(e) => return Err(format!("Failed to get a state RwLockGuard: {}", e)),
+        Err(_) => return Err(Error::LockPoisoned("image".to_string())),
     };
 
-    // Get raw image data from clipboard
-    let mut clipboard = match Clipboard::new() {
-        Ok(clipboard) => clipboard,
-        Err(e) => return Err(format!("Failed to get clipboard handler: {}", e)),
-    };
-    let image_data = match clipboard.get_image() {
-        Ok(image_data) => image_data,
-        Err(e) => return Err(format!("Failed to get an image from the clipboard: {}", e)),
+    match change(&mut state_guard) {
+        Ok(_) => (),
+        Err(e) => return Err(e),
     };
+    Ok(state_guard.revision)
+}
 
-    // Convert the raw image data (bytes is image::ImageBuffer.into_raw())
-    let image_buf: RgbaImage = match ImageBuffer::from_raw(
-        image_data.width as u32,
-        image_data.height as u32,
-        image_data.bytes.into_owned(),
-    ) {
-        Some(buf) => buf,
-        None => return Err("Failed to convert the raw bytes into an image buffer".to_string()),
+/***** Commands *****/
+/// Read the clipboard image into the state, returning its revision. The frontend loads the image
+/// itself over the preview protocol, as sending it as base64 is slow for big images
+#[tauri::command]
+async fn read_clipboard(app_handle: AppHandle) -> Result<u64, Error> {
+    jobs::run_blocking(move || {
+        let state = app_handle.state::<ImageDataState>();
+        let settings_state = app_handle.state::<SettingsState>();
+        let mut clipboard = match ArboardClipboard::new() {
+            Ok(clipboard) => clipboard,
+            Err(e) => return Err(e),
+        };
+        let current_settings = match get_settings(&settings_state) {
+            Ok(current_settings) => current_settings,
+            Err(e) => return Err(e),
+        };
+
+        // Read the clipboard into the state, keeping the lock only until the image is stored
+        let snapshot = {
+            let mut state_guard = match state.0.write() {
+                Ok(state_guard) => state_guard,
+                Err(_) => return Err(Error::LockPoisoned("image".to_string())),
+            };
+            match state_guard.read_clipboard(&mut clipboard, current_settings.svg_dpi) {
+                Ok(_) => (),
+                Err(e) => return Err(e),
+            };
+            if current_settings.auto_trim {
+                state_guard.auto_trim(current_settings.trim_tolerance);
+            }
+            state_guard.snapshot()
+        };
+        add_to_history(&app_handle, &snapshot);
+        Ok(snapshot.revision)
+    })
+    .await
+}
+
+/// Open an image file as the current image, returning it like `read_clipboard` does.
+/// Without a path (e.g. one dropped onto the window), the user is asked to pick a file
+#[tauri::command]
+async fn open_image(app_handle: AppHandle, path: Option<String>) -> Result<u64, Error> {
+    let path = match path {
+        Some(path) => path,
+        None => match FileDialogBuilder::new()
+            .set_title("Which image would you like to open?")
+            .add_filter("Images", &image_data::openable_extensions())
+            .pick_file()
+        {
+            Some(file) => file.to_string_lossy().to_string(),
+            None => return Err(Error::Canceled),
+        },
     };
 
-    // Write dynamic image to state
-    let dynamic_image = DynamicImage::ImageRgba8(image_buf);
-    state_guard.clipboard_dynamic_image = dynamic_image;
+    jobs::run_blocking(move || {
+        let snapshot = match app_handle.state::<ImageDataState>().0.write() {
+            Ok(mut state_guard) => match state_guard.open_file(&path) {
+                Ok(_) => state_guard.snapshot(),
+                Err(e) => return Err(e),
+            },
+            Err(_) => return Err(Error::LockPoisoned("image".to_string())),
+        };
+        add_to_history(&app_handle, &snapshot);
+        Ok(snapshot.revision)
+    })
+    .await
+}
 
-    // Get encoded PNG cursor
-    match state_guard.convert_encoded_cursor_with_format(settings::SaveFormat::Png) {
-        Ok(_) => (),
-        Err(e) => return Err(e),
+/// Get information about the current image, e.g. to warn about transparency that'll be lost
+#[tauri::command]
+fn get_image_info(state: State<ImageDataState>) -> Result<payloads::ImageInfo, Error> {
+    let state_guard = match state.0.read() {
+        Ok(state_guard) => state_guard,
+        Err(_) => return Err(Error::LockPoisoned("image".to_string())),
     };
+    let image = state_guard.edited_image();
 
-    Ok(base64::encode(state_guard.clipboard_image_cursor.get_ref()))
+    Ok(payloads::ImageInfo {
+        width: image.width(),
+        height: image.height(),
+        has_transparency: encoder::has_transparency(&image),
+        source: state_guard.clipboard_image_source.clone(),
+        can_undo: !state_guard.edits.edits.is_empty(),
+        can_redo: !state_guard.edits.undone.is_empty(),
+        annotations: state_guard.annotations.clone(),
+    })
+}
+
+/// Add an edit to the current image
+#[tauri::command]
+fn edit_image(state: State<ImageDataState>, edit: payloads::Edit) -> Result<u64, Error> {
+    change_edits(&state, |image_data| image_data.edit(edit))
+}
+
+/// Undo the last edit of the current image
+#[tauri::command]
+fn undo_edit(state: State<ImageDataState>) -> Result<u64, Error> {
+    change_edits(&state, |image_data| {
+        image_data.undo_edit();
+        Ok(())
+    })
+}
+
+/// Redo the last undone edit of the current image
+#[tauri::command]
+fn redo_edit(state: State<ImageDataState>) -> Result<u64, Error> {
+    change_edits(&state, |image_data| {
+        image_data.redo_edit();
+        Ok(())
+    })
+}
+
+/// Forget every edit of the current image
+#[tauri::command]
+fn reset_edits(state: State<ImageDataState>) -> Result<u64, Error> {
+    change_edits(&state, |image_data| {
+        image_data.reset_edits();
+        Ok(())
+    })
+}
+
+/// Get the color adjustments of the current image
+#[tauri::command]
+fn get_adjustments(state: State<ImageDataState>) -> Result<settings::Adjustments, Error> {
+    match state.0.read() {
+        Ok(state_guard) => Ok(state_guard.adjustments.clone()),
+        Err(_) => Err(Error::LockPoisoned("image".to_string())),
+    }
+}
+
+/// Replace the color adjustments of the current image
+#[tauri::command]
+fn set_adjustments(
+    state: State<ImageDataState>,
+    adjustments: settings::Adjustments,
+) -> Result<u64, Error> {
+    change_edits(&state, |image_data| image_data.set_adjustments(adjustments))
+}
+
+/// Get how often the encoded outputs of the current image were reused, for the debug view
+#[tauri::command]
+fn get_cache_stats(state: State<ImageDataState>) -> Result<payloads::CacheStats, Error> {
+    match state.0.read() {
+        Ok(state_guard) => Ok(state_guard.outputs.stats()),
+        Err(_) => Err(Error::LockPoisoned("image".to_string())),
+    }
+}
+
+/// Replace the annotations drawn over the current image when it's saved or copied, returning its
+/// new revision like `edit_image` does
+#[tauri::command]
+fn set_annotations(
+    state: State<ImageDataState>,
+    annotations: Vec<payloads::Annotation>,
+) -> Result<u64, Error> {
+    change_edits(&state, |image_data| image_data.set_annotations(annotations))
+}
+
+/// List the save formats that this build is able to encode
+#[tauri::command]
+fn list_save_formats() -> Vec<settings::SaveFormat> {
+    settings::SaveFormat::iter()
+        .filter(encoder::can_encode)
+        .collect()
 }
 
 /// Get the path to save the image
 #[tauri::command]
-async fn get_save_path(format: String) -> Result<String, String> {
+async fn get_save_path(format: String) -> Result<String, Error> {
+    let format = match settings::SaveFormat::from_str(format.as_str()) {
+        Ok(format) => format,
+        Err(_) => return Err(Error::InvalidFormat(format)),
+    };
     let directory = match picture_dir() {
         Some(picture_dir) => picture_dir,
         None => PathBuf::from("."),
@@ -138,79 +285,326 @@ async fn get_save_path(format: String) -> Result<String, String> {
     let file = match FileDialogBuilder::new()
         .set_title("Where would you like to save the image?")
         .set_directory(directory)
-        .add_filter(format.to_uppercase(), &[format.to_lowercase().as_str()])
+        .add_filter(format.to_string().to_uppercase(), &[format.extension()])
         .save_file()
     {
-        Some(file) => file.into_os_string().into_string().unwrap(),
-        None => return Err("User canceled save".to_string()),
+        Some(file) => file.to_string_lossy().to_string(),
+        None => return Err(Error::Canceled),
     };
 
     Ok(file)
 }
 
-/// Save the image to a file with a specified format
+/// Save the image to a file with a specified format.
+/// The path is a template (see `template::expand_template`). If the file exists, the collision
+/// policy decides what happens; `confirmed_path` is given when the user agreed to overwrite it.
+/// A snapshot of the image is saved on the worker pool, reporting its progress as events
 #[tauri::command]
-fn save_image(state: State<ImageDataState>, path: String, format: String) -> Result<(), String> {
-    let mut state_guard = match state.0.write() {
-        Ok(state_guard) => state_guard,
-        Err(e) => return Err(format!("Failed to get a state RwLockGuard: {}", e)),
+async fn save_image(
+    app_handle: AppHandle,
+    state: State<'_, ImageDataState>,
+    settings_state: State<'_, SettingsState>,
+    path: String,
+    format: String,
+    confirmed_path: Option<String>,
+) -> Result<payloads::SaveOutcome, Error> {
+    let format = match settings::SaveFormat::from_str(format.as_str()) {
+        Ok(format) => format,
+        Err(_) => return Err(Error::InvalidFormat(format)),
+    };
+    let current_settings = match get_settings(&settings_state) {
+        Ok(current_settings) => current_settings,
+        Err(e) => return Err(e),
     };
 
-    let format = settings::SaveFormat::from_str(format.as_str()).unwrap();
+    // The image can be edited or replaced while it's saved, so only its snapshot is locked for
+    let snapshot = match state.0.read() {
+        Ok(state_guard) => state_guard.snapshot(),
+        Err(_) => return Err(Error::LockPoisoned("image".to_string())),
+    };
 
-    // Convert the buffer to the save format
-    match state_guard.convert_encoded_cursor_with_format(format) {
-        Ok(_) => (),
+    jobs::run_save_job(app_handle, move |progress| {
+        snapshot.save_with_progress(
+            &path,
+            format,
+            &current_settings,
+            confirmed_path,
+            &get_counter_path(),
+            progress,
+        )
+    })
+    .await
+}
+
+/// Get the path that a save path template would currently expand to, without saving anything
+#[tauri::command]
+fn preview_save_path(
+    state: State<ImageDataState>,
+    settings_state: State<SettingsState>,
+    path: String,
+    format: String,
+) -> Result<String, Error> {
+    let state_guard = match state.0.read() {
+        Ok(state_guard) => state_guard,
+        Err(_) => return Err(Error::LockPoisoned("image".to_string())),
+    };
+    let format = match settings::SaveFormat::from_str(format.as_str()) {
+        Ok(format) => format,
+        Err(_) => return Err(Error::InvalidFormat(format)),
+    };
+    let current_settings = match get_settings(&settings_state) {
+        Ok(current_settings) => current_settings,
         Err(e) => return Err(e),
     };
 
-    // Write to file
-    match write(&path, state_guard.clipboard_image_cursor.get_ref()) {
-        Ok(_) => (),
-        Err(e) => return Err(format!("Failed to save image to {}: {}", path, e)),
+    Ok(template::expand_template(
+        &path,
+        &state_guard.template_values(
+            template::load_counter(&get_counter_path()),
+            &format,
+            &current_settings.encoder_options,
+        ),
+    ))
+}
+
+/// List the clipboard image history from oldest to newest
+#[tauri::command]
+fn list_history(history_state: State<HistoryState>) -> Result<Vec<payloads::HistoryEntry>, Error> {
+    match history_state.0.read() {
+        Ok(history_guard) => Ok(history_guard.entries.clone()),
+        Err(_) => Err(Error::LockPoisoned("history".to_string())),
     }
+}
 
-    Ok(())
+/// Get the thumbnail of a history entry encoded as a base64 PNG
+#[tauri::command]
+fn preview_history_entry(history_state: State<HistoryState>, id: u64) -> Result<String, Error> {
+    let history_guard = match history_state.0.read() {
+        Ok(history_guard) => history_guard,
+        Err(_) => return Err(Error::LockPoisoned("history".to_string())),
+    };
+
+    match history_guard.load_thumbnail(id) {
+        Ok(thumbnail) => Ok(base64::encode(thumbnail)),
+        Err(e) => Err(e),
+    }
 }
 
-/// Load the settings file and return the text contents of it
-/// If the file wasn't found, it'll return the String "NotFound"
+/// Make a history entry the current image, returning it like `read_clipboard` does
 #[tauri::command]
-fn load_settings() -> Result<String, String> {
-    let file_text = match read_to_string(get_settings_path()) {
-        Ok(file_text) => file_text,
-        Err(e) => match e {
-            _ if e.kind() == ErrorKind::NotFound => return Err("NotFound".to_string()),
-            _ => return Err(format!("Failed to load settings file: {}", e)),
+fn restore_history_entry(
+    state: State<ImageDataState>,
+    history_state: State<HistoryState>,
+    id: u64,
+) -> Result<u64, Error> {
+    let image = match history_state.0.read() {
+        Ok(history_guard) => match history_guard.load_image(id) {
+            Ok(image) => image,
+            Err(e) => return Err(e),
         },
+        Err(_) => return Err(Error::LockPoisoned("history".to_string())),
+    };
+    let mut state_guard = match state.0.write() {
+        Ok(state_guard) => state_guard,
+        Err(_) => return Err(Error::LockPoisoned("image".to_string())),
     };
 
-    Ok(file_text)
+    state_guard.set_clipboard_image(image.into_rgba8());
+    state_guard.clipboard_image_source = payloads::ImageSource::History;
+    Ok(state_guard.revision)
 }
 
-/// Save settings
+/// Delete a history entry
 #[tauri::command]
-fn save_settings(settings: String) -> Result<String, String> {
-    let settings_path = get_settings_path();
-    let settings_parent_dir_path = match settings_path.parent() {
-        Some(parent_dir_path) => parent_dir_path,
-        None => return Err("Settings path has no parent directory, what?".to_string()),
+fn delete_history_entry(history_state: State<HistoryState>, id: u64) -> Result<(), Error> {
+    match history_state.0.write() {
+        Ok(mut history_guard) => history_guard.remove(id),
+        Err(_) => Err(Error::LockPoisoned("history".to_string())),
+    }
+}
+
+/// Save a history entry to a file with a specified format, like `save_image` does
+#[tauri::command]
+async fn save_history_entry(
+    app_handle: AppHandle,
+    history_state: State<'_, HistoryState>,
+    settings_state: State<'_, SettingsState>,
+    id: u64,
+    path: String,
+    format: String,
+    confirmed_path: Option<String>,
+) -> Result<payloads::SaveOutcome, Error> {
+    let image = match history_state.0.read() {
+        Ok(history_guard) => match history_guard.load_image(id) {
+            Ok(image) => image,
+            Err(e) => return Err(e),
+        },
+        Err(_) => return Err(Error::LockPoisoned("history".to_string())),
+    };
+    let format = match settings::SaveFormat::from_str(format.as_str()) {
+        Ok(format) => format,
+        Err(_) => return Err(Error::InvalidFormat(format)),
+    };
+    let current_settings = match get_settings(&settings_state) {
+        Ok(current_settings) => current_settings,
+        Err(e) => return Err(e),
     };
 
-    // Create directory path (won't do anything if already created)
-    match create_dir_all(settings_parent_dir_path) {
-        Ok(_) => (),
-        Err(e) => return Err(format!("Failed to create the settings directory: {}", e)),
+    // Save through a temporary image data so it's encoded the same way as the current image
+    jobs::run_save_job(app_handle, move |progress| {
+        let mut image_data = ImageData::default();
+        image_data.set_clipboard_image(image.into_rgba8());
+        image_data.save_with_progress(
+            &path,
+            format,
+            &current_settings,
+            confirmed_path,
+            &get_counter_path(),
+            progress,
+        )
+    })
+    .await
+}
+
+/// Cancel the saves that are running. They end with `Error::Canceled`, leaving no file behind
+#[tauri::command]
+fn cancel_saves(jobs_state: State<jobs::JobsState>) -> Result<(), Error> {
+    jobs::cancel_jobs(&jobs_state)
+}
+
+/// Copy the current image or a history entry to the clipboard.
+/// If a format is given, the image is copied as it'd look once saved in it.
+/// It's async so converting the image doesn't freeze the window
+#[tauri::command]
+async fn write_clipboard(
+    app_handle: AppHandle,
+    history_id: Option<u64>,
+    format: Option<String>,
+) -> Result<(), Error> {
+    let format = match format {
+        Some(format) => match settings::SaveFormat::from_str(format.as_str()) {
+            Ok(format) => Some(format),
+            Err(_) => return Err(Error::InvalidFormat(format)),
+        },
+        None => None,
+    };
+
+    // Converted on the worker pool, so neither the image nor the clipboard is locked meanwhile
+    let converted = jobs::run_blocking({
+        let app_handle = app_handle.clone();
+        move || {
+            let current_settings = match get_settings(&app_handle.state::<SettingsState>()) {
+                Ok(current_settings) => current_settings,
+                Err(e) => return Err(e),
+            };
+            let image_data = match history_id {
+                // Copied through a temporary image data, so it's converted the same way as the
+                // current image
+                Some(id) => {
+                    let image = match app_handle.state::<HistoryState>().0.read() {
+                        Ok(history_guard) => match history_guard.load_image(id) {
+                            Ok(image) => image,
+                            Err(e) => return Err(e),
+                        },
+                        Err(_) => return Err(Error::LockPoisoned("history".to_string())),
+                    };
+                    let mut image_data = ImageData::default();
+                    image_data.set_clipboard_image(image.into_rgba8());
+                    image_data
+                }
+                None => match app_handle.state::<ImageDataState>().0.read() {
+                    Ok(state_guard) => state_guard.snapshot(),
+                    Err(_) => return Err(Error::LockPoisoned("image".to_string())),
+                },
+            };
+            match image_data.clipboard_image(format, &current_settings.encoder_options) {
+                Ok(image_buf) => {
+                    let image_hash = image_data::hash_image(&image_buf);
+                    Ok((image_buf, image_hash))
+                }
+                Err(e) => Err(e),
+            }
+        }
+    })
+    .await;
+    let (image_buf, image_hash) = match converted {
+        Ok(converted) => converted,
+        Err(e) => return Err(e),
+    };
+
+    // The clipboard is only locked to put the image in it
+    let clipboard_state = app_handle.state::<ClipboardState>();
+    let mut clipboard_guard = match clipboard_state.0.lock() {
+        Ok(clipboard_guard) => clipboard_guard,
+        Err(_) => return Err(Error::LockPoisoned("clipboard".to_string())),
+    };
+    if clipboard_guard.clipboard.is_none() {
+        clipboard_guard.clipboard = match ArboardClipboard::new() {
+            Ok(clipboard) => Some(clipboard),
+            Err(e) => return Err(e),
+        };
+    }
+    let result = clipboard_guard
+        .clipboard
+        .as_mut()
+        .unwrap()
+        .set_image(&image_buf);
+
+    // Remember what was copied, so the watcher doesn't capture it as a new image
+    match result {
+        Ok(_) => {
+            clipboard_guard.image_hash = Some(image_hash);
+            Ok(())
+        }
+        Err(e) => {
+            // The handler might be broken, so it's recreated next time
+            clipboard_guard.clipboard = None;
+            Err(e)
+        }
+    }
+}
+
+/// Load the settings file and return the text contents of it, migrated if an older version
+/// saved it. If the file wasn't found, `None` is returned and the default settings should be used
+#[tauri::command]
+fn load_settings() -> Result<Option<String>, Error> {
+    settings_file::load_settings_file(&get_settings_path())
+}
+
+/// Update the settings the backend uses with the ones currently in the frontend
+#[tauri::command]
+fn sync_settings(state: State<SettingsState>, settings: String) -> Result<(), Error> {
+    let new_settings = match settings::Settings::parse(settings) {
+        Ok(new_settings) => new_settings,
+        Err(e) => return Err(e),
+    };
+    match state.0.write() {
+        Ok(mut state_guard) => *state_guard = new_settings,
+        Err(_) => return Err(Error::LockPoisoned("settings".to_string())),
     };
 
-    match write(&settings_path, settings) {
+    Ok(())
+}
+
+/// Save settings, once they're parsed and validated. Settings that can't be used are returned
+/// as `Error::InvalidSettings`, which the frontend shows next to each setting
+#[tauri::command]
+fn save_settings(settings: String) -> Result<String, Error> {
+    let settings_path = get_settings_path();
+    match settings_file::save_settings_file(&settings_path, settings) {
         Ok(_) => Ok(settings_path.to_str().unwrap_or_default().to_string()),
-        Err(e) => Err(format!("Failed to save settings: {}", e)),
+        Err(e) => Err(e),
     }
 }
 
 /***** Main *****/
 fn main() {
+    // Commands like `save` run without opening the window
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(exit_code) = cli::run(&args) {
+        std::process::exit(exit_code);
+    }
+
     let context = tauri::generate_context!();
     tauri::Builder::default()
         .menu(if cfg!(target_os = "macos") {
@@ -219,12 +613,41 @@ fn main() {
             tauri::Menu::default()
         })
         .manage(ImageDataState(Default::default()))
+        .manage(SettingsState(Default::default()))
+        .manage(HistoryState(RwLock::new(History::load(&get_history_dir()))))
+        .manage(ClipboardState(Default::default()))
+        .manage(jobs::JobsState::default())
+        .register_uri_scheme_protocol(protocol::PREVIEW_PROTOCOL, protocol::serve_preview)
+        .setup(|app| {
+            watcher::spawn_clipboard_watcher(app.handle());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             read_clipboard,
+            open_image,
             save_image,
+            preview_save_path,
+            get_image_info,
+            edit_image,
+            undo_edit,
+            redo_edit,
+            reset_edits,
+            get_adjustments,
+            set_adjustments,
+            set_annotations,
+            get_cache_stats,
             save_settings,
             load_settings,
+            sync_settings,
             get_save_path,
+            list_save_formats,
+            list_history,
+            preview_history_entry,
+            restore_history_entry,
+            delete_history_entry,
+            save_history_entry,
+            cancel_saves,
+            write_clipboard,
         ])
         .run(context)
         .expect("error while running tauri application");
@@ -0,0 +1,69 @@
+/*
+ * Custom URI protocol serving the current image to the frontend
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::ImageDataState;
+use shared::{error::Error, image_data::ImageData};
+use std::{error::Error as StdError, num::ParseIntError};
+use tauri::{
+    http::{Request, Response, ResponseBuilder},
+    AppHandle, Manager,
+};
+
+/***** Constants *****/
+/// The URI scheme the preview is served on, e.g. `clip://localhost/current?rev=3`.
+/// Windows serves custom protocols as `https://clip.localhost/current?rev=3` instead
+pub const PREVIEW_PROTOCOL: &str = "clip";
+/// The path of the current image
+const CURRENT_PATH: &str = "/current";
+
+/***** Protocol *****/
+/// Serve the preview PNG of the current image from the state. The revision in the query makes the
+/// URL change with the image, so the webview doesn't show a cached one, and a request for an
+/// older revision is gone rather than answered with a newer image
+pub fn serve_preview(app: &AppHandle, request: &Request) -> Result<Response, Box<dyn StdError>> {
+    let (path, query) = request.uri().split_once('?').unwrap_or((request.uri(), ""));
+    let revision = match query_revision(query) {
+        Ok(revision) => revision,
+        Err(_) => return ResponseBuilder::new().status(400).body(Vec::new()),
+    };
+    // Anything but the current image (e.g. a typo) doesn't exist
+    if !path.ends_with(CURRENT_PATH) {
+        return ResponseBuilder::new().status(404).body(Vec::new());
+    }
+
+    match ImageData::preview_png_of(&app.state::<ImageDataState>().0, revision) {
+        // Responses own their body, so the cached PNG is copied into each one. For a 4K
+        // screenshot that's about a millisecond, next to tens of them to encode it again (see the
+        // preview benchmark)
+        Ok(png) => ResponseBuilder::new()
+            .mimetype("image/png")
+            .header("Cache-Control", "no-store")
+            .status(200)
+            .body(png.to_vec()),
+        Err(Error::OutdatedRevision(_)) => ResponseBuilder::new().status(410).body(Vec::new()),
+        Err(e @ Error::LockPoisoned(_)) => ResponseBuilder::new()
+            .mimetype("text/plain")
+            .status(500)
+            .body(e.to_string().into_bytes()),
+        // e.g. nothing was pasted yet
+        Err(e) => ResponseBuilder::new()
+            .mimetype("text/plain")
+            .status(404)
+            .body(e.to_string().into_bytes()),
+    }
+}
+
+/// Get the revision from a query like `rev=3`, if it has one
+fn query_revision(query: &str) -> Result<Option<u64>, ParseIntError> {
+    match query
+        .split('&')
+        .find_map(|parameter| parameter.strip_prefix("rev="))
+    {
+        Some(revision) => revision.parse().map(Some),
+        None => Ok(None),
+    }
+}
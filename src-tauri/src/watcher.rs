@@ -0,0 +1,95 @@
+/*
+ * Background clipboard watcher
+ * Created on 2026-10-18
+ */
+
+/***** Setup *****/
+/* Imports */
+use crate::{add_to_history, ClipboardState, ImageDataState, SettingsState};
+use shared::{
+    clipboard::{ArboardClipboard, ClipboardSource},
+    image_data::hash_image,
+};
+use std::{thread, time::Duration};
+use tauri::{AppHandle, Manager};
+
+/***** Constants *****/
+/// How often the clipboard is checked for a new image
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// The event emitted to the frontend with the revision of the new image
+pub const IMAGE_CHANGED_EVENT: &str = "clipboard-image-changed";
+
+/***** Watcher *****/
+/// Spawn a thread that polls the clipboard and emits an event whenever a new image shows up.
+/// `arboard` has no way to be notified of clipboard changes, so polling it is.
+pub fn spawn_clipboard_watcher(app_handle: AppHandle) {
+    thread::spawn(move || {
+        // The clipboard handler is kept between polls, and is only recreated if it breaks
+        let mut clipboard: Option<ArboardClipboard> = None;
+        // The hash of the last image seen in the clipboard. This is separate from the current
+        // image's hash, as that can change without the clipboard changing (e.g. from the history)
+        let mut last_clipboard_hash: Option<u64> = None;
+
+        loop {
+            thread::sleep(POLL_INTERVAL);
+
+            // Only watch if enabled in the settings
+            let (watch_clipboard, auto_trim) = match app_handle.state::<SettingsState>().0.read() {
+                Ok(settings_guard) => (
+                    settings_guard.watch_clipboard,
+                    settings_guard
+                        .auto_trim
+                        .then(|| settings_guard.trim_tolerance),
+                ),
+                Err(_) => (false, None),
+            };
+            if !watch_clipboard {
+                continue;
+            }
+
+            if clipboard.is_none() {
+                clipboard = ArboardClipboard::new().ok();
+            }
+            let image_buf = match clipboard.as_mut().map(ClipboardSource::get_image) {
+                Some(Ok(image_buf)) => image_buf,
+                // The clipboard doesn't have an image in it, which is fine
+                Some(Err(_)) => continue,
+                None => continue,
+            };
+
+            // Check if the clipboard changed and the image is different from the current one
+            let image_hash = hash_image(&image_buf);
+            if last_clipboard_hash == Some(image_hash) {
+                continue;
+            }
+            last_clipboard_hash = Some(image_hash);
+            // Images copied by this application aren't new
+            let copied_hash = match app_handle.state::<ClipboardState>().0.lock() {
+                Ok(clipboard_guard) => clipboard_guard.image_hash,
+                Err(_) => None,
+            };
+            if copied_hash == Some(image_hash) {
+                continue;
+            }
+            let image_data_state = app_handle.state::<ImageDataState>();
+            let mut state_guard = match image_data_state.0.write() {
+                Ok(state_guard) => state_guard,
+                Err(_) => continue,
+            };
+            if image_hash == state_guard.clipboard_image_hash {
+                continue;
+            }
+
+            // Update the state and tell the frontend
+            state_guard.set_clipboard_image(image_buf);
+            if let Some(trim_tolerance) = auto_trim {
+                state_guard.auto_trim(trim_tolerance);
+            }
+            let snapshot = state_guard.snapshot();
+            drop(state_guard);
+            add_to_history(&app_handle, &snapshot);
+            let revision = snapshot.revision;
+            let _ = app_handle.emit_all(IMAGE_CHANGED_EVENT, revision);
+        }
+    });
+}